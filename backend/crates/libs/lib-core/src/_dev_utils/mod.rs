@@ -100,3 +100,55 @@ pub async fn clean_users(
 
 	Ok(count)
 }
+
+// endregion: --- User seed/clean
+
+// region:    --- Company/Product seed
+
+pub async fn seed_company(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	name: &str,
+) -> model::Result<i64> {
+	model::company::CompanyBmc::create(
+		ctx,
+		mm,
+		model::company::CompanyForCreate {
+			name: name.to_string(),
+			description: None,
+			logo: None,
+			website_url: None,
+			location: None,
+			founded_year: None,
+		},
+	)
+	.await
+}
+
+pub async fn seed_products(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	company_id: i64,
+	names: &[&str],
+) -> model::Result<Vec<i64>> {
+	let mut ids = Vec::new();
+
+	for name in names {
+		let id = model::product::ProductBmc::create(
+			ctx,
+			mm,
+			model::product::ProductForCreate {
+				company_id,
+				name: name.to_string(),
+				description: None,
+				banner: None,
+			},
+		)
+		.await?;
+		ids.push(id);
+	}
+
+	Ok(ids)
+}
+
+// endregion: --- Company/Product seed
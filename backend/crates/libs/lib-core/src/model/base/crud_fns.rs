@@ -1,16 +1,100 @@
 use crate::ctx::Ctx;
 use crate::model::base::{
 	prep_fields_for_create, prep_fields_for_update, CommonIden, DbBmc,
-	LIST_LIMIT_DEFAULT, LIST_LIMIT_MAX,
+	TimestampIden, BIND_PARAMS_MAX, LIST_LIMIT_DEFAULT, LIST_LIMIT_MAX,
 };
+use crate::model::entity_change::{EntityChangeBmc, EntityChangeOp};
 use crate::model::ModelManager;
 use crate::model::{Error, Result};
-use modql::field::HasSeaFields;
-use modql::filter::{FilterGroups, ListOptions};
-use sea_query::{Condition, Expr, PostgresQueryBuilder, Query};
+use lib_utils::b64::{b64u_decode, b64u_encode};
+use modql::field::{HasSeaFields, SeaField, SeaFields};
+use modql::filter::{FilterGroups, ListOptions, OrderBy, OrderBys};
+use sea_query::{
+	Alias, Condition, DynIden, Expr, Func, LockType, Order, PostgresQueryBuilder,
+	Query, SimpleExpr,
+};
 use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
 use sqlx::postgres::PgRow;
-use sqlx::FromRow;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::{FromRow, Row};
+use std::collections::HashMap;
+
+/// The metadata of a list (see `list_with_meta`).
+#[derive(Debug, Clone, Serialize)]
+pub struct ListMeta {
+	/// The applied offset.
+	pub offset: i64,
+	/// The applied limit (`LIST_LIMIT_DEFAULT` if none was given).
+	pub limit: i64,
+	/// True if more entities match the filter after this list.
+	pub has_more: bool,
+	/// The number of entities matching the filter (only when requested).
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub total: Option<i64>,
+}
+
+/// The entities affected by a bulk operation
+/// (see `create_many`, `update_many`, and `delete_many`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BulkAffected {
+	pub ids: Vec<i64>,
+	pub count: i64,
+}
+
+impl From<Vec<i64>> for BulkAffected {
+	fn from(ids: Vec<i64>) -> Self {
+		let count = ids.len() as i64;
+		BulkAffected { ids, count }
+	}
+}
+
+/// The options of a cursor list (see `list_by_cursor`).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ListCursorOptions {
+	/// The `next_cursor` of the previous page (None for the first page).
+	pub cursor: Option<String>,
+	/// Default to `LIST_LIMIT_DEFAULT`, and at most `LIST_LIMIT_MAX`.
+	pub limit: Option<i64>,
+	/// Default to `id`. The `id` is always appended as the last key.
+	pub order_bys: Option<OrderBys>,
+}
+
+/// The metadata of a cursor list (see `list_by_cursor`).
+#[derive(Debug, Clone, Serialize)]
+pub struct ListCursorMeta {
+	/// The applied limit (`LIST_LIMIT_DEFAULT` if none was given).
+	pub limit: i64,
+	/// The cursor of the next page (None if this is the last page).
+	pub next_cursor: Option<String>,
+}
+
+/// The content of the opaque cursor: the keys of the list order,
+/// and the key values of the last entity of the page (by column name).
+#[derive(Serialize, Deserialize)]
+struct ListCursor {
+	keys: Vec<String>,
+	values: JsonMap<String, JsonValue>,
+}
+
+/// An entity row of a cursor list, with its key values for the next cursor.
+struct CursorRow<E> {
+	entity: E,
+	cursor_values: JsonValue,
+}
+
+impl<'r, E> FromRow<'r, PgRow> for CursorRow<E>
+where
+	E: FromRow<'r, PgRow>,
+{
+	fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+		Ok(CursorRow {
+			entity: E::from_row(row)?,
+			cursor_values: row.try_get("cursor_values")?,
+		})
+	}
+}
 
 pub async fn create<MC, E>(ctx: &Ctx, mm: &ModelManager, data: E) -> Result<i64>
 where
@@ -37,12 +121,49 @@ where
 	let sqlx_query = sqlx::query_as_with::<_, (i64,), _>(&sql, values);
 	// NOTE: For now, we will use the _txn for all create.
 	//       We could have a with_txn as function argument if perf is an issue (it should not be)
+	let mm = mm.new_with_txn()?;
+	mm.dbx().begin_txn().await?;
+
 	let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
 
+	// -- Record the change
+	let after = EntityChangeBmc::snapshot::<MC>(&mm, id).await?;
+	EntityChangeBmc::record::<MC>(ctx, &mm, id, EntityChangeOp::Create, None, after)
+		.await?;
+
+	mm.dbx().commit_txn().await?;
+
 	Ok(id)
 }
 
 pub async fn get<MC, E>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
+where
+	MC: DbBmc,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasSeaFields,
+{
+	select_by_id::<MC, E>(mm, id, false).await
+}
+
+/// Same as `get`, but also returns the soft-deleted entity (e.g., for admin views).
+pub async fn get_including_deleted<MC, E>(
+	_ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+) -> Result<E>
+where
+	MC: DbBmc,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasSeaFields,
+{
+	select_by_id::<MC, E>(mm, id, true).await
+}
+
+async fn select_by_id<MC, E>(
+	mm: &ModelManager,
+	id: i64,
+	include_deleted: bool,
+) -> Result<E>
 where
 	MC: DbBmc,
 	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
@@ -54,6 +175,9 @@ where
 		.from(MC::table_ref())
 		.columns(E::sea_column_refs())
 		.and_where(Expr::col(CommonIden::Id).eq(id));
+	if !include_deleted && MC::has_soft_delete() {
+		query.and_where(Expr::col(CommonIden::Deleted).eq(false));
+	}
 
 	// -- Exec query
 	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
@@ -119,18 +243,305 @@ where
 	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
 	E: HasSeaFields,
 {
+	select_list::<MC, E, F>(mm, filter, list_options, false).await
+}
+
+/// Same as `list`, but also returns the soft-deleted entities (e.g., for admin views).
+pub async fn list_including_deleted<MC, E, F>(
+	_ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<F>,
+	list_options: Option<ListOptions>,
+) -> Result<Vec<E>>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasSeaFields,
+{
+	select_list::<MC, E, F>(mm, filter, list_options, true).await
+}
+
+/// Same as `list`, but also returns the list metadata (applied offset/limit,
+/// has more, and the total count when `with_total`, with the same filter).
+pub async fn list_with_meta<MC, E, F>(
+	_ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<F>,
+	list_options: Option<ListOptions>,
+	with_total: bool,
+) -> Result<(Vec<E>, ListMeta)>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasSeaFields,
+{
+	let cond = list_cond::<MC, F>(filter, false)?;
+	let list_options = compute_list_options(list_options)?;
+	let offset = list_options.offset.unwrap_or(0);
+	let limit = list_options.limit.unwrap_or(LIST_LIMIT_DEFAULT);
+
+	// -- Fetch one more entity than the limit to know if there is more
+	let list_options = ListOptions {
+		limit: Some(limit + 1),
+		..list_options
+	};
+	let mut entities =
+		select_list_cond::<MC, E>(mm, cond.clone(), list_options).await?;
+	let has_more = entities.len() as i64 > limit;
+	entities.truncate(limit as usize);
+
+	// -- Count the total if requested
+	let total = if with_total {
+		Some(count_cond::<MC>(mm, cond).await?)
+	} else {
+		None
+	};
+
+	let meta = ListMeta {
+		offset,
+		limit,
+		has_more,
+		total,
+	};
+
+	Ok((entities, meta))
+}
+
+/// Returns the number of entities matching the filter (soft-deleted ones excluded).
+pub async fn count<MC, F>(
+	_ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<F>,
+) -> Result<i64>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+{
+	let cond = list_cond::<MC, F>(filter, false)?;
+	count_cond::<MC>(mm, cond).await
+}
+
+/// Keyset (cursor) pagination, stable and index friendly on large tables.
+///
+/// The page starts after the entity of the `cursor`, keyed on the order-by
+/// columns plus `id`, and the returned `next_cursor` continues after the last entity.
+///
+/// Notes:
+///   - The cursor is only valid for the same order-by keys.
+///   - The cursor holds the key values of the last entity, so the next page does
+///     not depend on that entity still being there (or unchanged).
+///   - The order-by columns should not be nullable (NULL keys are skipped).
+pub async fn list_by_cursor<MC, E, F>(
+	_ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<F>,
+	cursor_options: Option<ListCursorOptions>,
+) -> Result<(Vec<E>, ListCursorMeta)>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasSeaFields,
+{
+	let ListCursorOptions {
+		cursor,
+		limit,
+		order_bys,
+	} = cursor_options.unwrap_or_default();
+
+	// -- Validate the limit
+	let limit = limit.unwrap_or(LIST_LIMIT_DEFAULT);
+	if limit < 0 {
+		return Err(Error::ListLimitInvalid { actual: limit });
+	}
+	if limit > LIST_LIMIT_MAX {
+		return Err(Error::ListLimitOverMax {
+			max: LIST_LIMIT_MAX,
+			actual: limit,
+		});
+	}
+
+	// -- The order keys, always ending with the id (unique)
+	let mut keys: Vec<OrderBy> =
+		order_bys.map(|o| o.order_bys()).unwrap_or_default();
+	if !keys.iter().any(|key| order_by_col(key).0 == "id") {
+		keys.push(OrderBy::Asc("id".to_string()));
+	}
+	let key_names: Vec<String> = keys.iter().map(order_by_key).collect();
+
+	// -- Build the condition
+	let mut cond = list_cond::<MC, F>(filter, false)?;
+	if let Some(cursor) = cursor {
+		let cursor = decode_cursor(&cursor)?;
+		let has_values = keys
+			.iter()
+			.all(|key| cursor.values.contains_key(order_by_col(key).0));
+		if cursor.keys != key_names || !has_values {
+			return Err(Error::ListCursorInvalid);
+		}
+		cond = cond.add(cursor_cond::<MC>(&keys, &cursor.values));
+	}
+
 	// -- Build the query
+	// Note: The key values of each row, as a json object, for the next cursor.
+	let cursor_values = keys.iter().flat_map(|key| {
+		let (name, _) = order_by_col(key);
+		[Expr::val(name).into(), Expr::col(Alias::new(name)).into()]
+	});
 	let mut query = Query::select();
-	query.from(MC::table_ref()).columns(E::sea_column_refs());
+	query
+		.from(MC::table_ref())
+		.columns(E::sea_column_refs())
+		.expr_as(
+			Func::cust(Alias::new("jsonb_build_object")).args(cursor_values),
+			Alias::new("cursor_values"),
+		)
+		.cond_where(cond)
+		// Fetch one more entity than the limit to know if there is a next page
+		.limit(limit as u64 + 1);
+	for key in &keys {
+		let (name, order) = order_by_col(key);
+		query.order_by(Alias::new(name), order);
+	}
 
-	// condition from filter
-	if let Some(filter) = filter {
-		let filters: FilterGroups = filter.into();
-		let cond: Condition = filters.try_into()?;
-		query.cond_where(cond);
+	// -- Execute the query
+	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+	let sqlx_query = sqlx::query_as_with::<_, CursorRow<E>, _>(&sql, values);
+	let mut rows = mm.dbx().fetch_all(sqlx_query).await?;
+
+	// -- Compute the next cursor
+	let has_more = rows.len() as i64 > limit;
+	rows.truncate(limit as usize);
+	let next_cursor = match rows.last() {
+		Some(last) if has_more => {
+			let JsonValue::Object(values) = last.cursor_values.clone() else {
+				return Err(Error::ListCursorInvalid);
+			};
+			Some(encode_cursor(&ListCursor {
+				keys: key_names,
+				values,
+			})?)
+		}
+		_ => None,
+	};
+
+	let entities = rows.into_iter().map(|row| row.entity).collect();
+	let meta = ListCursorMeta { limit, next_cursor };
+
+	Ok((entities, meta))
+}
+
+/// The condition of the rows after the cursor key `values`, in the order of the `keys`:
+/// `(k1 > c1) OR (k1 = c1 AND k2 > c2) OR ...` (`<` for the descending keys).
+///
+/// Note: The cursor values are converted from json to the column types by
+///       `jsonb_populate_record` (on the table row type).
+fn cursor_cond<MC>(
+	keys: &[OrderBy],
+	values: &JsonMap<String, JsonValue>,
+) -> Condition
+where
+	MC: DbBmc,
+{
+	let values = JsonValue::Object(values.clone()).to_string();
+	let cursor_val = |name: &str| -> SimpleExpr {
+		Expr::cust_with_values(
+			format!(
+				"(jsonb_populate_record(NULL::{}, CAST($1 AS jsonb))).{}",
+				quote_ident(MC::TABLE),
+				quote_ident(name)
+			),
+			[values.clone()],
+		)
+	};
+
+	let mut cond = Condition::any();
+	for (i, key) in keys.iter().enumerate() {
+		let mut key_cond = Condition::all();
+		for prev_key in &keys[..i] {
+			let (name, _) = order_by_col(prev_key);
+			key_cond =
+				key_cond.add(Expr::col(Alias::new(name)).eq(cursor_val(name)));
+		}
+		let (name, order) = order_by_col(key);
+		let after = match order {
+			Order::Desc => Expr::col(Alias::new(name)).lt(cursor_val(name)),
+			_ => Expr::col(Alias::new(name)).gt(cursor_val(name)),
+		};
+		cond = cond.add(key_cond.add(after));
+	}
+
+	cond
+}
+
+/// The quoted (Postgres) identifier (e.g., `"user"`).
+fn quote_ident(name: &str) -> String {
+	format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn order_by_col(order_by: &OrderBy) -> (&str, Order) {
+	match order_by {
+		OrderBy::Asc(name) => (name, Order::Asc),
+		OrderBy::Desc(name) => (name, Order::Desc),
 	}
-	// list options
+}
+
+/// The key of an order by, as in `ListOptions` (e.g., `name` or `!name` for desc).
+fn order_by_key(order_by: &OrderBy) -> String {
+	match order_by {
+		OrderBy::Asc(name) => name.to_string(),
+		OrderBy::Desc(name) => format!("!{name}"),
+	}
+}
+
+fn encode_cursor(cursor: &ListCursor) -> Result<String> {
+	let json = serde_json::to_vec(cursor).map_err(|_| Error::ListCursorInvalid)?;
+	Ok(b64u_encode(json))
+}
+
+fn decode_cursor(cursor: &str) -> Result<ListCursor> {
+	b64u_decode(cursor)
+		.ok()
+		.and_then(|json| serde_json::from_slice(&json).ok())
+		.ok_or(Error::ListCursorInvalid)
+}
+
+async fn select_list<MC, E, F>(
+	mm: &ModelManager,
+	filter: Option<F>,
+	list_options: Option<ListOptions>,
+	include_deleted: bool,
+) -> Result<Vec<E>>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasSeaFields,
+{
+	let cond = list_cond::<MC, F>(filter, include_deleted)?;
 	let list_options = compute_list_options(list_options)?;
+
+	select_list_cond::<MC, E>(mm, cond, list_options).await
+}
+
+async fn select_list_cond<MC, E>(
+	mm: &ModelManager,
+	cond: Condition,
+	list_options: ListOptions,
+) -> Result<Vec<E>>
+where
+	MC: DbBmc,
+	E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+	E: HasSeaFields,
+{
+	// -- Build the query
+	let mut query = Query::select();
+	query
+		.from(MC::table_ref())
+		.columns(E::sea_column_refs())
+		.cond_where(cond);
 	list_options.apply_to_sea_query(&mut query);
 
 	// -- Execute the query
@@ -142,6 +553,51 @@ where
 	Ok(entities)
 }
 
+async fn count_cond<MC>(mm: &ModelManager, cond: Condition) -> Result<i64>
+where
+	MC: DbBmc,
+{
+	// -- Build the query
+	let mut query = Query::select();
+	query
+		.from(MC::table_ref())
+		.expr(Func::count(Expr::col(CommonIden::Id)))
+		.cond_where(cond);
+
+	// -- Execute the query
+	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+	let sqlx_query = sqlx::query_as_with::<_, (i64,), _>(&sql, values);
+	let (count,) = mm.dbx().fetch_one(sqlx_query).await?;
+
+	Ok(count)
+}
+
+/// The where condition of a list: the filter, and the exclusion of
+/// the soft-deleted rows (unless `include_deleted`).
+pub fn list_cond<MC, F>(
+	filter: Option<F>,
+	include_deleted: bool,
+) -> Result<Condition>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+{
+	let mut cond = Condition::all();
+
+	// condition from filter
+	if let Some(filter) = filter {
+		let filters: FilterGroups = filter.into();
+		let filter_cond: Condition = filters.try_into()?;
+		cond = cond.add(filter_cond);
+	}
+	// exclude the soft-deleted rows
+	if !include_deleted && MC::has_soft_delete() {
+		cond = cond.add(Expr::col(CommonIden::Deleted).eq(false));
+	}
+
+	Ok(cond)
+}
+
 pub async fn update<MC, E>(
 	ctx: &Ctx,
 	mm: &ModelManager,
@@ -151,9 +607,53 @@ pub async fn update<MC, E>(
 where
 	MC: DbBmc,
 	E: HasSeaFields,
+{
+	update_row::<MC>(ctx, mm, id, data.not_none_sea_fields(), None).await
+}
+
+/// Same as `update`, with the fields to update
+/// (e.g., to set a column to NULL, which `update` skips).
+pub(in crate::model) async fn update_fields<MC>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	fields: SeaFields,
+) -> Result<()>
+where
+	MC: DbBmc,
+{
+	update_row::<MC>(ctx, mm, id, fields, None).await
+}
+
+/// Same as `update`, but only if the entity `mtime` is still `expected_mtime`
+/// (i.e., not modified since it was read), otherwise fails with
+/// `Error::ConcurrentModification` (optimistic concurrency).
+pub async fn update_if_unchanged<MC, E>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	data: E,
+	expected_mtime: OffsetDateTime,
+) -> Result<()>
+where
+	MC: DbBmc,
+	E: HasSeaFields,
+{
+	let fields = data.not_none_sea_fields();
+	update_row::<MC>(ctx, mm, id, fields, Some(expected_mtime)).await
+}
+
+async fn update_row<MC>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	id: i64,
+	mut fields: SeaFields,
+	expected_mtime: Option<OffsetDateTime>,
+) -> Result<()>
+where
+	MC: DbBmc,
 {
 	// -- Prep Fields
-	let mut fields = data.not_none_sea_fields();
 	prep_fields_for_update::<MC>(&mut fields, ctx.user_id());
 
 	// -- Build query
@@ -163,47 +663,397 @@ where
 		.table(MC::table_ref())
 		.values(fields)
 		.and_where(Expr::col(CommonIden::Id).eq(id));
+	if MC::has_soft_delete() {
+		query.and_where(Expr::col(CommonIden::Deleted).eq(false));
+	}
+	if let Some(expected_mtime) = expected_mtime {
+		query.and_where(Expr::col(TimestampIden::Mtime).eq(expected_mtime));
+	}
 
 	// -- Execute query
 	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
 	let sqlx_query = sqlx::query_with(&sql, values);
+	let mm = mm.new_with_txn()?;
+	mm.dbx().begin_txn().await?;
+
+	let before = EntityChangeBmc::snapshot::<MC>(&mm, id).await?;
 	let count = mm.dbx().execute(sqlx_query).await?;
 
 	// -- Check result
 	if count == 0 {
-		Err(Error::EntityNotFound {
+		// If the entity exists, then the mtime did not match.
+		if expected_mtime.is_some() && exists::<MC>(&mm, id).await? {
+			return Err(Error::ConcurrentModification {
+				entity: MC::TABLE,
+				id,
+			});
+		}
+		return Err(Error::EntityNotFound {
 			entity: MC::TABLE,
 			id,
-		})
-	} else {
-		Ok(())
+		});
 	}
+
+	// -- Record the change
+	let after = EntityChangeBmc::snapshot::<MC>(&mm, id).await?;
+	EntityChangeBmc::record::<MC>(
+		ctx,
+		&mm,
+		id,
+		EntityChangeOp::Update,
+		before,
+		after,
+	)
+	.await?;
+
+	mm.dbx().commit_txn().await?;
+
+	Ok(())
 }
 
-pub async fn delete<MC>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+/// Note: If the Bmc has soft-delete, the row is only marked as deleted
+///       (and `mid`/`mtime` record who deleted it, and when).
+pub async fn delete<MC>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
 where
 	MC: DbBmc,
 {
 	// -- Build query
-	let mut query = Query::delete();
-	query
-		.from_table(MC::table_ref())
-		.and_where(Expr::col(CommonIden::Id).eq(id));
+	let (sql, values) = if MC::has_soft_delete() {
+		let mut fields =
+			SeaFields::new(vec![SeaField::new(CommonIden::Deleted, true)]);
+		prep_fields_for_update::<MC>(&mut fields, ctx.user_id());
+
+		let mut query = Query::update();
+		query
+			.table(MC::table_ref())
+			.values(fields.for_sea_update())
+			.and_where(Expr::col(CommonIden::Id).eq(id))
+			.and_where(Expr::col(CommonIden::Deleted).eq(false));
+		query.build_sqlx(PostgresQueryBuilder)
+	} else {
+		let mut query = Query::delete();
+		query
+			.from_table(MC::table_ref())
+			.and_where(Expr::col(CommonIden::Id).eq(id));
+		query.build_sqlx(PostgresQueryBuilder)
+	};
 
 	// -- Execute query
-	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
 	let sqlx_query = sqlx::query_with(&sql, values);
+	let mm = mm.new_with_txn()?;
+	mm.dbx().begin_txn().await?;
+
+	let before = EntityChangeBmc::snapshot::<MC>(&mm, id).await?;
 	let count = mm.dbx().execute(sqlx_query).await?;
 
 	// -- Check result
 	if count == 0 {
-		Err(Error::EntityNotFound {
+		return Err(Error::EntityNotFound {
 			entity: MC::TABLE,
 			id,
-		})
+		});
+	}
+
+	// -- Record the change
+	let after = if MC::has_soft_delete() {
+		EntityChangeBmc::snapshot::<MC>(&mm, id).await?
 	} else {
-		Ok(())
+		None
+	};
+	EntityChangeBmc::record::<MC>(
+		ctx,
+		&mm,
+		id,
+		EntityChangeOp::Delete,
+		before,
+		after,
+	)
+	.await?;
+
+	mm.dbx().commit_txn().await?;
+
+	Ok(())
+}
+
+/// Creates all the entities with multi-row inserts, in one transaction.
+///
+/// Note: The columns absent from some of the entities (None fields)
+///       get their `DEFAULT` value for those.
+/// Note: The rows are inserted by chunks, to stay under the Postgres
+///       bind parameter limit (see `BIND_PARAMS_MAX`).
+pub async fn create_many<MC, E>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	data: Vec<E>,
+) -> Result<BulkAffected>
+where
+	MC: DbBmc,
+	E: HasSeaFields,
+{
+	if data.is_empty() {
+		return Ok(BulkAffected::default());
 	}
+	let user_id = ctx.user_id();
+
+	// -- Extract fields (name / sea-query value expression)
+	let rows: Vec<Vec<SeaField>> = data
+		.into_iter()
+		.map(|entity_c| {
+			let mut fields = entity_c.not_none_sea_fields();
+			prep_fields_for_create::<MC>(&mut fields, user_id);
+			fields.into_vec()
+		})
+		.collect();
+
+	// -- The columns of all the rows
+	let mut columns: Vec<DynIden> = Vec::new();
+	for field in rows.iter().flatten() {
+		let name = field.iden.to_string();
+		if !columns.iter().any(|column| column.to_string() == name) {
+			columns.push(field.iden.clone());
+		}
+	}
+
+	let chunk_size = (BIND_PARAMS_MAX / columns.len().max(1)).max(1);
+
+	let mm = mm.new_with_txn()?;
+	mm.dbx().begin_txn().await?;
+
+	let mut ids: Vec<i64> = Vec::with_capacity(rows.len());
+	for chunk in rows.chunks(chunk_size) {
+		// -- Build query
+		let mut query = Query::insert();
+		query
+			.into_table(MC::table_ref())
+			.columns(columns.clone())
+			.returning(Query::returning().columns([CommonIden::Id]));
+		for row in chunk {
+			let mut row_values: HashMap<String, SimpleExpr> = row
+				.iter()
+				.map(|field| (field.iden.to_string(), field.value.clone()))
+				.collect();
+			let sea_values: Vec<SimpleExpr> = columns
+				.iter()
+				.map(|column| {
+					row_values
+						.remove(&column.to_string())
+						.unwrap_or_else(|| Expr::cust("DEFAULT"))
+				})
+				.collect();
+			query.values(sea_values)?;
+		}
+
+		// -- Exec query
+		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+		let sqlx_query = sqlx::query_as_with::<_, (i64,), _>(&sql, values);
+		let chunk_ids = mm.dbx().fetch_all(sqlx_query).await?;
+		ids.extend(chunk_ids.into_iter().map(|(id,)| id));
+	}
+
+	// -- Record the changes
+	let mut afters = EntityChangeBmc::snapshots::<MC>(&mm, &ids).await?;
+	let changes = ids
+		.iter()
+		.map(|id| (*id, None, afters.remove(id)))
+		.collect();
+	EntityChangeBmc::record_many::<MC>(ctx, &mm, EntityChangeOp::Create, changes)
+		.await?;
+
+	mm.dbx().commit_txn().await?;
+
+	Ok(ids.into())
+}
+
+/// Updates all the entities matching the filter (soft-deleted ones excluded)
+/// with the same data, in one transaction.
+/// Fails with `Error::BulkFilterEmpty` if the filter would match all the rows.
+pub async fn update_many<MC, E, F>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	filter: F,
+	data: E,
+) -> Result<BulkAffected>
+where
+	MC: DbBmc,
+	E: HasSeaFields,
+	F: Into<FilterGroups>,
+{
+	let filter = bulk_filter(filter)?;
+	let cond = list_cond::<MC, _>(Some(filter), false)?;
+
+	// -- Prep Fields
+	let mut fields = data.not_none_sea_fields();
+	prep_fields_for_update::<MC>(&mut fields, ctx.user_id());
+
+	let mm = mm.new_with_txn()?;
+	mm.dbx().begin_txn().await?;
+
+	let ids = lock_ids_cond::<MC>(&mm, cond).await?;
+	if !ids.is_empty() {
+		// -- Build query
+		let mut query = Query::update();
+		query
+			.table(MC::table_ref())
+			.values(fields.for_sea_update())
+			.and_where(Expr::col(CommonIden::Id).is_in(ids.clone()));
+
+		// -- Execute query
+		let mut befores = EntityChangeBmc::snapshots::<MC>(&mm, &ids).await?;
+		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+		let sqlx_query = sqlx::query_with(&sql, values);
+		mm.dbx().execute(sqlx_query).await?;
+
+		// -- Record the changes
+		let mut afters = EntityChangeBmc::snapshots::<MC>(&mm, &ids).await?;
+		let changes = ids
+			.iter()
+			.map(|id| (*id, befores.remove(id), afters.remove(id)))
+			.collect();
+		EntityChangeBmc::record_many::<MC>(
+			ctx,
+			&mm,
+			EntityChangeOp::Update,
+			changes,
+		)
+		.await?;
+	}
+
+	mm.dbx().commit_txn().await?;
+
+	Ok(ids.into())
+}
+
+/// Deletes all the entities matching the filter, in one transaction
+/// (soft delete if the Bmc has soft-delete, as `delete`).
+/// Fails with `Error::BulkFilterEmpty` if the filter would match all the rows.
+pub async fn delete_many<MC, F>(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	filter: F,
+) -> Result<BulkAffected>
+where
+	MC: DbBmc,
+	F: Into<FilterGroups>,
+{
+	let filter = bulk_filter(filter)?;
+	let cond = list_cond::<MC, _>(Some(filter), false)?;
+
+	let mm = mm.new_with_txn()?;
+	mm.dbx().begin_txn().await?;
+
+	let ids = lock_ids_cond::<MC>(&mm, cond).await?;
+	if !ids.is_empty() {
+		// -- Build query
+		let (sql, values) = if MC::has_soft_delete() {
+			let mut fields =
+				SeaFields::new(vec![SeaField::new(CommonIden::Deleted, true)]);
+			prep_fields_for_update::<MC>(&mut fields, ctx.user_id());
+
+			let mut query = Query::update();
+			query
+				.table(MC::table_ref())
+				.values(fields.for_sea_update())
+				.and_where(Expr::col(CommonIden::Id).is_in(ids.clone()));
+			query.build_sqlx(PostgresQueryBuilder)
+		} else {
+			let mut query = Query::delete();
+			query
+				.from_table(MC::table_ref())
+				.and_where(Expr::col(CommonIden::Id).is_in(ids.clone()));
+			query.build_sqlx(PostgresQueryBuilder)
+		};
+
+		// -- Execute query
+		let mut befores = EntityChangeBmc::snapshots::<MC>(&mm, &ids).await?;
+		let sqlx_query = sqlx::query_with(&sql, values);
+		mm.dbx().execute(sqlx_query).await?;
+
+		// -- Record the changes
+		let mut afters = if MC::has_soft_delete() {
+			EntityChangeBmc::snapshots::<MC>(&mm, &ids).await?
+		} else {
+			HashMap::new()
+		};
+		let changes = ids
+			.iter()
+			.map(|id| (*id, befores.remove(id), afters.remove(id)))
+			.collect();
+		EntityChangeBmc::record_many::<MC>(
+			ctx,
+			&mm,
+			EntityChangeOp::Delete,
+			changes,
+		)
+		.await?;
+	}
+
+	mm.dbx().commit_txn().await?;
+
+	Ok(ids.into())
+}
+
+/// Returns the filter groups of a bulk update/delete.
+/// Fails with `Error::BulkFilterEmpty` if there are no groups, or if one of
+/// the (OR'd) groups has no condition, as the filter would match all the rows.
+pub(in crate::model) fn bulk_filter<F>(filter: F) -> Result<FilterGroups>
+where
+	F: Into<FilterGroups>,
+{
+	let filters: FilterGroups = filter.into();
+	let groups = filters.groups();
+	if groups.is_empty() || groups.iter().any(|group| group.nodes().is_empty()) {
+		return Err(Error::BulkFilterEmpty);
+	}
+
+	Ok(filters)
+}
+
+/// Returns the ids of the rows matching the condition, locked for update
+/// (so that a bulk update/delete affects exactly those).
+///
+/// Note: Must be called within the transaction of the bulk operation.
+async fn lock_ids_cond<MC>(mm: &ModelManager, cond: Condition) -> Result<Vec<i64>>
+where
+	MC: DbBmc,
+{
+	// -- Build query
+	let mut query = Query::select();
+	query
+		.from(MC::table_ref())
+		.column(CommonIden::Id)
+		.cond_where(cond)
+		.order_by(CommonIden::Id, Order::Asc)
+		.lock(LockType::Update);
+
+	// -- Exec query
+	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+	let sqlx_query = sqlx::query_as_with::<_, (i64,), _>(&sql, values);
+	let ids = mm.dbx().fetch_all(sqlx_query).await?;
+
+	Ok(ids.into_iter().map(|(id,)| id).collect())
+}
+
+/// Returns true if the entity exists (and is not soft-deleted).
+async fn exists<MC>(mm: &ModelManager, id: i64) -> Result<bool>
+where
+	MC: DbBmc,
+{
+	// -- Build query
+	let mut query = Query::select();
+	query
+		.from(MC::table_ref())
+		.column(CommonIden::Id)
+		.and_where(Expr::col(CommonIden::Id).eq(id));
+	if MC::has_soft_delete() {
+		query.and_where(Expr::col(CommonIden::Deleted).eq(false));
+	}
+
+	// -- Exec query
+	let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+	let sqlx_query = sqlx::query_as_with::<_, (i64,), _>(&sql, values);
+	let row = mm.dbx().fetch_optional(sqlx_query).await?;
+
+	Ok(row.is_some())
 }
 
 pub fn compute_list_options(
@@ -212,6 +1062,9 @@ pub fn compute_list_options(
 	if let Some(mut list_options) = list_options {
 		// Validate the limit.
 		if let Some(limit) = list_options.limit {
+			if limit < 0 {
+				return Err(Error::ListLimitInvalid { actual: limit });
+			}
 			if limit > LIST_LIMIT_MAX {
 				return Err(Error::ListLimitOverMax {
 					max: LIST_LIMIT_MAX,
@@ -1,14 +1,60 @@
 /// Convenience macro rules to generate default CRUD functions for a Bmc/Entity.
 /// Note: If custom functionality is required, use the code below as foundational
 ///       code for the custom implementations.
+///
+/// The `update_many` (by filter) is only generated with both `ForUpdate` and `Filter`.
 #[macro_export]
 macro_rules! generate_common_bmc_fns {
+	(
+		Bmc: $struct_name:ident,
+		Entity: $entity:ty,
+		$(ForCreate: $for_create:ty,)?
+		ForUpdate: $for_update:ty,
+		Filter: $filter:ty,
+	) => {
+		$crate::generate_common_bmc_fns!(
+			@common
+			Bmc: $struct_name,
+			Entity: $entity,
+			$(ForCreate: $for_create,)?
+			ForUpdate: $for_update,
+			Filter: $filter,
+		);
+
+		impl $struct_name {
+				pub async fn update_many(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					filter: Vec<$filter>,
+					entity_u: $for_update,
+				) -> Result<$crate::model::BulkAffected> {
+					base::update_many::<Self, _, _>(ctx, mm, filter, entity_u).await
+				}
+		}
+	};
 	(
 		Bmc: $struct_name:ident,
 		Entity: $entity:ty,
 		$(ForCreate: $for_create:ty,)?
 		$(ForUpdate: $for_update:ty,)?
 		$(Filter: $filter:ty,)?
+	) => {
+		$crate::generate_common_bmc_fns!(
+			@common
+			Bmc: $struct_name,
+			Entity: $entity,
+			$(ForCreate: $for_create,)?
+			$(ForUpdate: $for_update,)?
+			$(Filter: $filter,)?
+		);
+	};
+	(
+		@common
+		Bmc: $struct_name:ident,
+		Entity: $entity:ty,
+		$(ForCreate: $for_create:ty,)?
+		$(ForUpdate: $for_update:ty,)?
+		$(Filter: $filter:ty,)?
 	) => {
 		impl $struct_name {
 			$(
@@ -19,6 +65,14 @@ macro_rules! generate_common_bmc_fns {
 				) -> Result<i64> {
 					base::create::<Self, _>(ctx, mm, entity_c).await
 				}
+
+				pub async fn create_many(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					entities_c: Vec<$for_create>,
+				) -> Result<$crate::model::BulkAffected> {
+					base::create_many::<Self, _>(ctx, mm, entities_c).await
+				}
 			)?
 
 				pub async fn get(
@@ -29,6 +83,14 @@ macro_rules! generate_common_bmc_fns {
 					base::get::<Self, _>(ctx, mm, id).await
 				}
 
+				pub async fn get_including_deleted(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					id: i64,
+				) -> Result<$entity> {
+					base::get_including_deleted::<Self, _>(ctx, mm, id).await
+				}
+
 			$(
 				pub async fn first(
 					ctx: &Ctx,
@@ -47,6 +109,52 @@ macro_rules! generate_common_bmc_fns {
 				) -> Result<Vec<$entity>> {
 					base::list::<Self, _, _>(ctx, mm, filter, list_options).await
 				}
+
+				pub async fn list_including_deleted(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					filter: Option<Vec<$filter>>,
+					list_options: Option<ListOptions>,
+				) -> Result<Vec<$entity>> {
+					base::list_including_deleted::<Self, _, _>(ctx, mm, filter, list_options)
+						.await
+				}
+
+				pub async fn list_with_meta(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					filter: Option<Vec<$filter>>,
+					list_options: Option<ListOptions>,
+					with_total: bool,
+				) -> Result<(Vec<$entity>, $crate::model::ListMeta)> {
+					base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+						.await
+				}
+
+				pub async fn list_by_cursor(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					filter: Option<Vec<$filter>>,
+					cursor_options: Option<$crate::model::ListCursorOptions>,
+				) -> Result<(Vec<$entity>, $crate::model::ListCursorMeta)> {
+					base::list_by_cursor::<Self, _, _>(ctx, mm, filter, cursor_options).await
+				}
+
+				pub async fn count(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					filter: Option<Vec<$filter>>,
+				) -> Result<i64> {
+					base::count::<Self, _>(ctx, mm, filter).await
+				}
+
+				pub async fn delete_many(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					filter: Vec<$filter>,
+				) -> Result<$crate::model::BulkAffected> {
+					base::delete_many::<Self, _>(ctx, mm, filter).await
+				}
 			)?
 
 			$(
@@ -58,6 +166,17 @@ macro_rules! generate_common_bmc_fns {
 				) -> Result<()> {
 					base::update::<Self, _>(ctx, mm, id, entity_u).await
 				}
+
+				pub async fn update_if_unchanged(
+					ctx: &Ctx,
+					mm: &ModelManager,
+					id: i64,
+					entity_u: $for_update,
+					expected_mtime: sqlx::types::time::OffsetDateTime,
+				) -> Result<()> {
+					base::update_if_unchanged::<Self, _>(ctx, mm, id, entity_u, expected_mtime)
+						.await
+				}
 			)?
 
 				pub async fn delete(
@@ -17,6 +17,8 @@ use sea_query::{Iden, IntoIden, TableRef};
 
 const LIST_LIMIT_DEFAULT: i64 = 1000;
 const LIST_LIMIT_MAX: i64 = 5000;
+/// The max number of bind parameters of a Postgres statement.
+const BIND_PARAMS_MAX: usize = 65535;
 
 // endregion: --- Consts
 
@@ -26,6 +28,7 @@ const LIST_LIMIT_MAX: i64 = 5000;
 pub enum CommonIden {
 	Id,
 	OwnerId,
+	Deleted,
 }
 
 #[derive(Iden)]
@@ -67,4 +70,30 @@ pub trait DbBmc {
 	fn has_owner_id() -> bool {
 		false
 	}
+
+	/// Specifies if the entity table managed by this BMC has a `deleted` column.
+	/// If so, `base::delete` marks the row as deleted rather than removing it,
+	/// and `base::get`/`first`/`list` exclude the deleted rows
+	/// (use the `..._including_deleted` variants for admin views).
+	///
+	/// default: false
+	fn has_soft_delete() -> bool {
+		false
+	}
+
+	/// The fields (e.g., secrets, PII) whose values must not be recorded
+	/// in the `entity_change` audit log (recorded as "[REDACTED]").
+	///
+	/// default: none
+	fn audit_redacted_fields() -> &'static [&'static str] {
+		&[]
+	}
+
+	/// The derived columns (e.g., generated search columns) that are not part of
+	/// the `entity_change` audit log snapshots.
+	///
+	/// default: none
+	fn audit_excluded_fields() -> &'static [&'static str] {
+		&[]
+	}
 }
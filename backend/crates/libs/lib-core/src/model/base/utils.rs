@@ -26,6 +26,19 @@ where
 	}
 }
 
+/// Applies the restriction (e.g., an access condition) to each of the filters,
+/// as the filters are OR'd, or to a default filter if None.
+pub fn restrict_filters<F>(
+	filter: Option<Vec<F>>,
+	restriction: impl Fn(F) -> F,
+) -> Option<Vec<F>>
+where
+	F: Default,
+{
+	let filters = filter.unwrap_or_else(|| vec![F::default()]);
+	Some(filters.into_iter().map(restriction).collect())
+}
+
 /// Update the timestamps info for create
 /// (e.g., cid, ctime, and mid, mtime will be updated with the same values)
 fn add_timestamps_for_create(fields: &mut SeaFields, user_id: i64) {
@@ -0,0 +1,600 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::product::ProductBmc;
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::Result;
+use crate::model::{BulkAffected, ListCursorMeta, ListCursorOptions, ListMeta};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, IntoSeaError, ListOptions, OpValInt64, OpValValue, OpValsInt64,
+	OpValsString, OpValsValue, SeaResult,
+};
+use sea_query::{ColumnRef, ConditionExpression, Expr};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- Category Types
+
+/// A node of the product category taxonomy
+/// (e.g., Health > Hospitalization > Cashless). `parent_id` is None for the roots.
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct Category {
+	pub id: i64,
+	pub parent_id: Option<i64>,
+
+	pub name: String,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize)]
+pub struct CategoryForCreate {
+	pub parent_id: Option<i64>,
+	pub name: String,
+}
+
+/// Note: The `parent_id` cannot be updated (no moves, hence no cycles).
+#[derive(Fields, Default, Deserialize)]
+pub struct CategoryForUpdate {
+	pub name: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct CategoryFilter {
+	pub id: Option<OpValsInt64>,
+	pub parent_id: Option<OpValsInt64>,
+
+	pub name: Option<OpValsString>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+/// A category name of a product (see `CategoryBmc::names_by_products`).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ProductCategoryName {
+	pub product_id: i64,
+	pub name: String,
+}
+
+/// A product/category link (see `CategoryBmc::add_product`).
+#[derive(Debug, Clone, Fields, FromRow)]
+struct ProductCategory {
+	id: i64,
+}
+
+#[derive(Fields)]
+struct ProductCategoryForCreate {
+	product_id: i64,
+	category_id: i64,
+}
+
+#[derive(FilterNodes, Default, Debug)]
+struct ProductCategoryFilter {
+	product_id: Option<OpValsInt64>,
+	category_id: Option<OpValsInt64>,
+}
+
+// endregion: --- Category Types
+
+// region:    --- CategoryBmc
+
+pub struct CategoryBmc;
+
+impl DbBmc for CategoryBmc {
+	const TABLE: &'static str = "category";
+}
+
+/// The product/category links, through the base crud fns
+/// (so recorded in the `entity_change` audit log).
+struct ProductCategoryBmc;
+
+impl DbBmc for ProductCategoryBmc {
+	const TABLE: &'static str = "product_category";
+}
+
+/// The category `$1` and all its descendants, by depth then name.
+const SQL_SUBTREE: &str = r#"
+WITH RECURSIVE subtree AS (
+	SELECT category.*, 0 AS depth FROM category WHERE id = $1
+	UNION ALL
+	SELECT c.*, s.depth + 1 FROM category c JOIN subtree s ON c.parent_id = s.id
+)
+SELECT id, parent_id, name, cid, ctime, mid, mtime FROM subtree
+ORDER BY depth, name
+"#;
+
+/// The ids of the categories `$1` and all their descendants.
+const SQL_SUBTREES_IDS: &str = r#"
+WITH RECURSIVE subtree(id) AS (
+	SELECT id FROM category WHERE id = ANY($1)
+	UNION
+	SELECT c.id FROM category c JOIN subtree s ON c.parent_id = s.id
+)
+SELECT id FROM subtree
+"#;
+
+/// The categories of the product `$1`, by name.
+const SQL_BY_PRODUCT: &str = r#"
+SELECT c.id, c.parent_id, c.name, c.cid, c.ctime, c.mid, c.mtime
+FROM category c
+	JOIN product_category pc ON pc.category_id = c.id
+WHERE pc.product_id = $1
+ORDER BY c.name
+"#;
+
+/// The names of the categories of the products `$1`, with their ancestors,
+/// by product id then name.
+const SQL_NAMES_BY_PRODUCTS: &str = r#"
+WITH RECURSIVE lineage(product_id, category_id) AS (
+	SELECT pc.product_id, pc.category_id FROM product_category pc
+	WHERE pc.product_id = ANY($1)
+	UNION
+	SELECT l.product_id, c.parent_id FROM lineage l
+		JOIN category c ON c.id = l.category_id
+	WHERE c.parent_id IS NOT NULL
+)
+SELECT l.product_id, c.name
+FROM lineage l
+	JOIN category c ON c.id = l.category_id
+ORDER BY l.product_id, c.name
+"#;
+
+impl CategoryBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		category_c: CategoryForCreate,
+	) -> Result<i64> {
+		base::create::<Self, _>(ctx, mm, category_c).await
+	}
+
+	pub async fn create_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		categories_c: Vec<CategoryForCreate>,
+	) -> Result<BulkAffected> {
+		base::create_many::<Self, _>(ctx, mm, categories_c).await
+	}
+
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Category> {
+		base::get::<Self, _>(ctx, mm, id).await
+	}
+
+	pub async fn first(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CategoryFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Option<Category>> {
+		base::first::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CategoryFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Category>> {
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CategoryFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<Category>, ListMeta)> {
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	pub async fn list_by_cursor(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CategoryFilter>>,
+		cursor_options: Option<ListCursorOptions>,
+	) -> Result<(Vec<Category>, ListCursorMeta)> {
+		base::list_by_cursor::<Self, _, _>(ctx, mm, filter, cursor_options).await
+	}
+
+	pub async fn count(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CategoryFilter>>,
+	) -> Result<i64> {
+		base::count::<Self, _>(ctx, mm, filter).await
+	}
+
+	pub async fn update(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		category_u: CategoryForUpdate,
+	) -> Result<()> {
+		base::update::<Self, _>(ctx, mm, id, category_u).await
+	}
+
+	pub async fn update_if_unchanged(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		category_u: CategoryForUpdate,
+		expected_mtime: OffsetDateTime,
+	) -> Result<()> {
+		base::update_if_unchanged::<Self, _>(ctx, mm, id, category_u, expected_mtime)
+			.await
+	}
+
+	pub async fn update_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Vec<CategoryFilter>,
+		category_u: CategoryForUpdate,
+	) -> Result<BulkAffected> {
+		base::update_many::<Self, _, _>(ctx, mm, filter, category_u).await
+	}
+
+	/// Deletes the category, its descendants, and their product links,
+	/// in one transaction (each recorded in the `entity_change` audit log).
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		// Fail with EntityNotFound if the category does not exist.
+		Self::get(ctx, mm, id).await?;
+
+		delete_subtrees(ctx, mm, vec![id]).await?;
+
+		Ok(())
+	}
+
+	/// Same as `delete`, for all the categories matching the filter.
+	/// Note: The affected ids include the deleted descendants.
+	pub async fn delete_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Vec<CategoryFilter>,
+	) -> Result<BulkAffected> {
+		// Note: Fails with BulkFilterEmpty (as the base `delete_many`).
+		let filter = base::bulk_filter(filter)?;
+		let ids = base::list::<Self, Category, _>(ctx, mm, Some(filter), None)
+			.await?
+			.into_iter()
+			.map(|c| c.id)
+			.collect();
+
+		delete_subtrees(ctx, mm, ids).await
+	}
+
+	/// Only an admin (or `Sys`) user can create a category.
+	pub async fn check_create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		_category_c: &CategoryForCreate,
+	) -> Result<()> {
+		UserBmc::check_typ(ctx, mm, &[UserTyp::Sys, UserTyp::Admin]).await
+	}
+
+	/// Only an admin (or `Sys`) user can update or delete a category.
+	pub async fn check_write(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		Self::get(ctx, mm, id).await?;
+		UserBmc::check_typ(ctx, mm, &[UserTyp::Sys, UserTyp::Admin]).await
+	}
+
+	/// Returns the category and all its descendants (the category first).
+	pub async fn list_subtree(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+	) -> Result<Vec<Category>> {
+		// Fail with EntityNotFound if the category does not exist.
+		Self::get(ctx, mm, id).await?;
+
+		let sqlx_query = sqlx::query_as::<_, Category>(SQL_SUBTREE).bind(id);
+		let categories = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(categories)
+	}
+
+	/// Returns the categories the product was added to (not their ancestors).
+	pub async fn list_by_product(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+	) -> Result<Vec<Category>> {
+		let sqlx_query =
+			sqlx::query_as::<_, Category>(SQL_BY_PRODUCT).bind(product_id);
+		let categories = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(categories)
+	}
+
+	/// Returns the category names of the given products, including the names
+	/// of the category ancestors (e.g., "Health" for Health > Hospitalization).
+	pub async fn names_by_products(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		product_ids: &[i64],
+	) -> Result<Vec<ProductCategoryName>> {
+		let sqlx_query =
+			sqlx::query_as::<_, ProductCategoryName>(SQL_NAMES_BY_PRODUCTS)
+				.bind(product_ids);
+		let names = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(names)
+	}
+
+	/// Adds the product to the category (no-op if already added).
+	/// Note: Only the users who can write the product (see `ProductBmc::check_write`).
+	pub async fn add_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		product_id: i64,
+	) -> Result<()> {
+		// Fail with EntityNotFound if the category or product does not exist.
+		Self::get(ctx, mm, id).await?;
+		ProductBmc::check_write(ctx, mm, product_id).await?;
+
+		if first_product_category(ctx, mm, id, product_id)
+			.await?
+			.is_none()
+		{
+			let link_c = ProductCategoryForCreate {
+				product_id,
+				category_id: id,
+			};
+			base::create::<ProductCategoryBmc, _>(ctx, mm, link_c).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Removes the product from the category (no-op if not in the category).
+	/// Note: Only the users who can write the product (see `ProductBmc::check_write`).
+	pub async fn remove_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		product_id: i64,
+	) -> Result<()> {
+		ProductBmc::check_write(ctx, mm, product_id).await?;
+
+		if let Some(link) = first_product_category(ctx, mm, id, product_id).await? {
+			base::delete::<ProductCategoryBmc>(ctx, mm, link.id).await?;
+		}
+
+		Ok(())
+	}
+}
+
+/// The link between the category and the product, if any.
+async fn first_product_category(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	category_id: i64,
+	product_id: i64,
+) -> Result<Option<ProductCategory>> {
+	let filter = ProductCategoryFilter {
+		product_id: Some(OpValInt64::Eq(product_id).into()),
+		category_id: Some(OpValInt64::Eq(category_id).into()),
+	};
+	base::first::<ProductCategoryBmc, _, _>(ctx, mm, Some(vec![filter]), None).await
+}
+
+/// Deletes the categories, their descendants, and their product links
+/// (the links first, as the foreign keys do not cascade).
+async fn delete_subtrees(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	ids: Vec<i64>,
+) -> Result<BulkAffected> {
+	if ids.is_empty() {
+		return Ok(BulkAffected::default());
+	}
+
+	let mm = mm.new_with_txn()?;
+	mm.dbx().begin_txn().await?;
+
+	let sqlx_query = sqlx::query_as::<_, (i64,)>(SQL_SUBTREES_IDS).bind(ids);
+	let ids: Vec<i64> = mm
+		.dbx()
+		.fetch_all(sqlx_query)
+		.await?
+		.into_iter()
+		.map(|(id,)| id)
+		.collect();
+
+	let links_filter = ProductCategoryFilter {
+		category_id: Some(OpValInt64::In(ids.clone()).into()),
+		..Default::default()
+	};
+	base::delete_many::<ProductCategoryBmc, _>(ctx, &mm, vec![links_filter]).await?;
+
+	let filter = CategoryFilter {
+		id: Some(OpValInt64::In(ids).into()),
+		..Default::default()
+	};
+	let affected =
+		base::delete_many::<CategoryBmc, _>(ctx, &mm, vec![filter]).await?;
+
+	mm.dbx().commit_txn().await?;
+
+	Ok(affected)
+}
+
+/// The `ProductFilter.category_id` condition: the products in the subtree of
+/// the category (i.e., in the category or any of its descendants).
+/// Supports `$eq`, and `$in` for any of the category subtrees.
+pub fn product_category_subtree_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let ids = match op_value {
+		OpValValue::Eq(id) => vec![id],
+		OpValValue::In(ids) => ids,
+		_ => {
+			return Err(IntoSeaError::Custom(
+				"category_id only supports $eq and $in".to_string(),
+			))
+		}
+	};
+	let ids = ids
+		.into_iter()
+		.map(|id| {
+			id.as_i64().ok_or_else(|| {
+				IntoSeaError::Custom(format!("category_id '{id}' is not an i64"))
+			})
+		})
+		.collect::<SeaResult<Vec<i64>>>()?;
+	if ids.is_empty() {
+		return Ok(Expr::cust("FALSE").into());
+	}
+
+	let placeholders = (1..=ids.len())
+		.map(|i| format!("${i}"))
+		.collect::<Vec<_>>()
+		.join(", ");
+	let sql = format!(
+		r#""product"."id" IN (
+			SELECT pc.product_id FROM product_category pc WHERE pc.category_id IN (
+				WITH RECURSIVE subtree(id) AS (
+					SELECT id FROM category WHERE id IN ({placeholders})
+					UNION
+					SELECT c.id FROM category c JOIN subtree s ON c.parent_id = s.id
+				)
+				SELECT id FROM subtree
+			)
+		)"#
+	);
+
+	Ok(Expr::cust_with_values(sql, ids).into())
+}
+
+// endregion: --- CategoryBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::company::CompanyBmc;
+	use crate::model::entity_change::EntityChangeBmc;
+	use crate::model::product::ProductFilter;
+	use crate::model::Error as ModelError;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_products_by_category_subtree_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let category = |parent_id: Option<i64>, name: &str| CategoryForCreate {
+			parent_id,
+			name: format!("test_list_products_by_category_subtree_ok {name}"),
+		};
+		let health_id =
+			CategoryBmc::create(&ctx, &mm, category(None, "health")).await?;
+		let hospi_id =
+			CategoryBmc::create(&ctx, &mm, category(Some(health_id), "hospi"))
+				.await?;
+		let cashless_id =
+			CategoryBmc::create(&ctx, &mm, category(Some(hospi_id), "cashless"))
+				.await?;
+		let life_id = CategoryBmc::create(&ctx, &mm, category(None, "life")).await?;
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_list_products_by_category_subtree_ok company 01",
+		)
+		.await?;
+		let fx_product_ids = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&[
+				"test_list_products_by_category_subtree_ok product 01",
+				"test_list_products_by_category_subtree_ok product 02",
+				"test_list_products_by_category_subtree_ok product 03",
+			],
+		)
+		.await?;
+		CategoryBmc::add_product(&ctx, &mm, cashless_id, fx_product_ids[0]).await?;
+		CategoryBmc::add_product(&ctx, &mm, hospi_id, fx_product_ids[1]).await?;
+		CategoryBmc::add_product(&ctx, &mm, life_id, fx_product_ids[2]).await?;
+		CategoryBmc::add_product(&ctx, &mm, life_id, fx_product_ids[2]).await?;
+		let link_id =
+			first_product_category(&ctx, &mm, cashless_id, fx_product_ids[0])
+				.await?
+				.ok_or("product_category not found")?
+				.id;
+
+		// -- Exec
+		let list_by_category = |category_id: i64| {
+			let filter = ProductFilter {
+				category_id: Some(OpValValue::Eq(category_id.into()).into()),
+				..Default::default()
+			};
+			ProductBmc::list(&ctx, &mm, Some(vec![filter]), None)
+		};
+		let health_products = list_by_category(health_id).await?;
+		let cashless_products = list_by_category(cashless_id).await?;
+		let subtree = CategoryBmc::list_subtree(&ctx, &mm, health_id).await?;
+
+		// -- Check
+		let ids: Vec<i64> = health_products.iter().map(|p| p.id).collect();
+		assert_eq!(ids, &fx_product_ids[..2]);
+		let ids: Vec<i64> = cashless_products.iter().map(|p| p.id).collect();
+		assert_eq!(ids, &fx_product_ids[..1]);
+		let ids: Vec<i64> = subtree.iter().map(|c| c.id).collect();
+		assert_eq!(ids, [health_id, hospi_id, cashless_id]);
+
+		// -- Clean
+		CategoryBmc::delete(&ctx, &mm, health_id).await?;
+		CategoryBmc::delete(&ctx, &mm, life_id).await?;
+		for product_id in fx_product_ids {
+			ProductBmc::delete(&ctx, &mm, product_id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		// -- Check (the subtree and its product links deleted, and audited)
+		let res = CategoryBmc::get(&ctx, &mm, cashless_id).await;
+		assert!(
+			matches!(res, Err(ModelError::EntityNotFound { entity: "category", id }) if id == cashless_id),
+			"EntityNotFound not matching"
+		);
+		for (entity, id) in
+			[("category", cashless_id), ("product_category", link_id)]
+		{
+			let changes =
+				EntityChangeBmc::list_by_entity(&ctx, &mm, entity, id).await?;
+			let ops: Vec<String> =
+				changes.iter().map(|c| c.op.to_string()).collect();
+			assert_eq!(ops, ["Create", "Delete"]);
+		}
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -0,0 +1,889 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, CommonIden, DbBmc};
+use crate::model::company::CompanyBmc;
+use crate::model::company_member::CompanyMemberBmc;
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::product::{Product, ProductBmc};
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::{BulkAffected, ListCursorMeta, ListCursorOptions, ListMeta};
+use crate::model::{Error, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, IntoSeaError, ListOptions, OpValValue, OpValsInt64, OpValsString,
+	OpValsValue, SeaResult,
+};
+use sea_query::{
+	Alias, ColumnRef, ConditionExpression, Expr, Func, Iden, Nullable,
+	PostgresQueryBuilder, Query,
+};
+use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- Claim Types
+
+/// Note: The `Approved` and `Paid` claims are the settled ones.
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	PartialEq,
+	Eq,
+	sqlx::Type,
+	derive_more::Display,
+	Deserialize,
+	Serialize,
+)]
+#[sqlx(type_name = "claim_status")]
+pub enum ClaimStatus {
+	Submitted,
+	Approved,
+	Rejected,
+	Paid,
+}
+impl From<ClaimStatus> for sea_query::Value {
+	fn from(val: ClaimStatus) -> Self {
+		val.to_string().into()
+	}
+}
+impl Nullable for ClaimStatus {
+	fn null() -> sea_query::Value {
+		sea_query::Value::String(None)
+	}
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct Claim {
+	pub id: i64,
+	pub product_id: i64,
+
+	pub status: ClaimStatus,
+	#[serde_as(as = "Rfc3339")]
+	pub submit_time: OffsetDateTime,
+	/// When the claim was approved or rejected.
+	#[serde_as(as = "Option<Rfc3339>")]
+	pub decision_time: Option<OffsetDateTime>,
+	#[serde_as(as = "Option<Rfc3339>")]
+	pub payment_time: Option<OffsetDateTime>,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[serde_as]
+#[derive(Fields, Deserialize)]
+pub struct ClaimForCreate {
+	pub product_id: i64,
+	#[field(cast_as = "claim_status")]
+	pub status: Option<ClaimStatus>,
+	#[serde_as(as = "Rfc3339")]
+	pub submit_time: OffsetDateTime,
+	#[serde_as(as = "Option<Rfc3339>")]
+	pub decision_time: Option<OffsetDateTime>,
+	#[serde_as(as = "Option<Rfc3339>")]
+	pub payment_time: Option<OffsetDateTime>,
+}
+
+#[serde_as]
+#[derive(Fields, Default, Deserialize)]
+pub struct ClaimForUpdate {
+	#[field(cast_as = "claim_status")]
+	pub status: Option<ClaimStatus>,
+	#[serde_as(as = "Option<Rfc3339>")]
+	pub submit_time: Option<OffsetDateTime>,
+	#[serde_as(as = "Option<Rfc3339>")]
+	pub decision_time: Option<OffsetDateTime>,
+	#[serde_as(as = "Option<Rfc3339>")]
+	pub payment_time: Option<OffsetDateTime>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ClaimFilter {
+	pub id: Option<OpValsInt64>,
+	pub product_id: Option<OpValsInt64>,
+
+	#[modql(cast_as = "claim_status")]
+	pub status: Option<OpValsString>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub submit_time: Option<OpValsValue>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub decision_time: Option<OpValsValue>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub payment_time: Option<OpValsValue>,
+
+	/// The claims the user (`$eq` a user id) can read: the claims of the products
+	/// with visible claim data (see `Product.claim_historical_data_visibility`),
+	/// and of the products of the companies of the user.
+	/// Note: Set by the list fns only.
+	#[serde(skip)]
+	#[modql(to_sea_condition_fn = "claim_readable_cond")]
+	pub readable_by: Option<OpValsValue>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+/// The time window of the claim history (as presented to the user).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimHistoryResolution {
+	AllTime,
+	OneYear,
+	ThreeYear,
+}
+
+impl ClaimHistoryResolution {
+	pub const ALL: [ClaimHistoryResolution; 3] = [
+		ClaimHistoryResolution::AllTime,
+		ClaimHistoryResolution::OneYear,
+		ClaimHistoryResolution::ThreeYear,
+	];
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			ClaimHistoryResolution::AllTime => "All time",
+			ClaimHistoryResolution::OneYear => "1 year",
+			ClaimHistoryResolution::ThreeYear => "3 year",
+		}
+	}
+
+	pub fn from_label(label: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|res| res.label() == label)
+	}
+
+	/// Number of calendar months (including the current one) of the window.
+	/// `None` means since the first claim.
+	pub fn months(&self) -> Option<i32> {
+		match self {
+			ClaimHistoryResolution::AllTime => None,
+			ClaimHistoryResolution::OneYear => Some(12),
+			ClaimHistoryResolution::ThreeYear => Some(36),
+		}
+	}
+}
+
+/// The number of claims submitted in a given calendar month (UTC).
+#[serde_as]
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ClaimMonthlyVolume {
+	#[serde_as(as = "Rfc3339")]
+	pub month: OffsetDateTime,
+	pub volume: i64,
+}
+
+/// The claim handling metrics of a product (or company) over a resolution window
+/// (claims submitted in the window).
+/// Note: The ratios are over the decided claims, and the medians in days.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ClaimMetrics {
+	pub count_claim: i64,
+	pub count_decided: i64,
+	pub count_settled: i64,
+	pub count_rejected: i64,
+	pub median_days_to_decision: Option<f64>,
+	pub median_days_to_payment: Option<f64>,
+}
+
+impl ClaimMetrics {
+	/// `None` when no claim was decided yet.
+	pub fn settlement_ratio(&self) -> Option<f64> {
+		self.ratio(self.count_settled)
+	}
+
+	/// `None` when no claim was decided yet.
+	pub fn rejection_ratio(&self) -> Option<f64> {
+		self.ratio(self.count_rejected)
+	}
+
+	fn ratio(&self, count: i64) -> Option<f64> {
+		(self.count_decided > 0).then(|| count as f64 / self.count_decided as f64)
+	}
+}
+
+/// The claim metrics of a product (see `ClaimBmc::metrics_by_products`).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ProductClaimMetrics {
+	pub product_id: i64,
+	#[sqlx(flatten)]
+	#[serde(flatten)]
+	pub metrics: ClaimMetrics,
+}
+
+/// Claim count of a product.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ProductClaimCount {
+	pub product_id: i64,
+	pub count_claim: i64,
+}
+
+#[derive(Iden)]
+enum ClaimIden {
+	ProductId,
+}
+
+// endregion: --- Claim Types
+
+// region:    --- ClaimBmc
+
+pub struct ClaimBmc;
+
+impl DbBmc for ClaimBmc {
+	const TABLE: &'static str = "claim";
+
+	fn has_soft_delete() -> bool {
+		true
+	}
+}
+
+/// Monthly claim volume of a product, one row per calendar month (UTC),
+/// from the window start up to the current month, empty months included.
+///
+/// - `$1` product_id
+/// - `$2` number of months of the window (NULL for since the first claim)
+const SQL_MONTHLY_VOLUME: &str = r#"
+SELECT month, count(claim.id) AS volume
+FROM generate_series(
+		COALESCE(
+			date_trunc('month', now(), 'UTC') - make_interval(months => $2 - 1),
+			(SELECT date_trunc('month', min(submit_time), 'UTC') FROM claim
+				WHERE product_id = $1 AND NOT deleted),
+			date_trunc('month', now(), 'UTC')
+		),
+		date_trunc('month', now(), 'UTC'),
+		interval '1 month'
+	) AS month
+	LEFT JOIN claim ON claim.product_id = $1
+		AND NOT claim.deleted
+		AND claim.submit_time >= month
+		AND claim.submit_time < month + interval '1 month'
+GROUP BY month
+ORDER BY month
+"#;
+
+/// Claim metrics of a product, or of the (claim data visible) products of a company.
+///
+/// - `$1` product_id (NULL for all the products of the company)
+/// - `$2` company_id (NULL for the product only)
+/// - `$3` number of months of the window (NULL for since the first claim)
+const SQL_METRICS: &str = r#"
+SELECT
+	count(claim.id) AS count_claim,
+	count(claim.id) FILTER (WHERE status <> 'Submitted') AS count_decided,
+	count(claim.id) FILTER (WHERE status IN ('Approved', 'Paid')) AS count_settled,
+	count(claim.id) FILTER (WHERE status = 'Rejected') AS count_rejected,
+	percentile_cont(0.5) WITHIN GROUP (
+		ORDER BY extract(epoch FROM decision_time - submit_time)::float8 / 86400
+	) AS median_days_to_decision,
+	percentile_cont(0.5) WITHIN GROUP (
+		ORDER BY extract(epoch FROM payment_time - submit_time)::float8 / 86400
+	) AS median_days_to_payment
+FROM claim
+	JOIN product ON product.id = claim.product_id
+WHERE NOT claim.deleted
+	AND ($1::bigint IS NULL OR claim.product_id = $1)
+	AND ($2::bigint IS NULL OR (product.company_id = $2
+		AND NOT product.deleted
+		AND product.claim_historical_data_visibility))
+	AND ($3::int IS NULL OR claim.submit_time >=
+		date_trunc('month', now(), 'UTC') - make_interval(months => $3 - 1))
+"#;
+
+/// Same as `SQL_METRICS`, for each of the products.
+///
+/// - `$1` product_ids
+/// - `$2` number of months of the window (NULL for since the first claim)
+const SQL_METRICS_BY_PRODUCTS: &str = r#"
+SELECT
+	claim.product_id,
+	count(claim.id) AS count_claim,
+	count(claim.id) FILTER (WHERE status <> 'Submitted') AS count_decided,
+	count(claim.id) FILTER (WHERE status IN ('Approved', 'Paid')) AS count_settled,
+	count(claim.id) FILTER (WHERE status = 'Rejected') AS count_rejected,
+	percentile_cont(0.5) WITHIN GROUP (
+		ORDER BY extract(epoch FROM decision_time - submit_time)::float8 / 86400
+	) AS median_days_to_decision,
+	percentile_cont(0.5) WITHIN GROUP (
+		ORDER BY extract(epoch FROM payment_time - submit_time)::float8 / 86400
+	) AS median_days_to_payment
+FROM claim
+WHERE NOT claim.deleted
+	AND claim.product_id = ANY($1)
+	AND ($2::int IS NULL OR claim.submit_time >=
+		date_trunc('month', now(), 'UTC') - make_interval(months => $2 - 1))
+GROUP BY claim.product_id
+"#;
+
+impl ClaimBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		claim_c: ClaimForCreate,
+	) -> Result<i64> {
+		base::create::<Self, _>(ctx, mm, claim_c).await
+	}
+
+	pub async fn create_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		claims_c: Vec<ClaimForCreate>,
+	) -> Result<BulkAffected> {
+		base::create_many::<Self, _>(ctx, mm, claims_c).await
+	}
+
+	/// Fails with `Error::ProductClaimDataHidden` if the product claim data is
+	/// hidden (see `check_claim_data_readable`).
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Claim> {
+		let claim: Claim = base::get::<Self, _>(ctx, mm, id).await?;
+		let product = ProductBmc::get(ctx, mm, claim.product_id).await?;
+		check_claim_data_readable(ctx, mm, &product).await?;
+
+		Ok(claim)
+	}
+
+	/// Note: Only the claims of the products with visible claim data (as all
+	///       the list fns below), unless the ctx user is an admin (or `Sys`),
+	///       or a member of the product company (see `check_claim_data_readable`).
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ClaimFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Claim>> {
+		let filter = visible_only(ctx, mm, filter).await?;
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ClaimFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<Claim>, ListMeta)> {
+		let filter = visible_only(ctx, mm, filter).await?;
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	pub async fn list_by_cursor(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ClaimFilter>>,
+		cursor_options: Option<ListCursorOptions>,
+	) -> Result<(Vec<Claim>, ListCursorMeta)> {
+		let filter = visible_only(ctx, mm, filter).await?;
+		base::list_by_cursor::<Self, _, _>(ctx, mm, filter, cursor_options).await
+	}
+
+	pub async fn count(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ClaimFilter>>,
+	) -> Result<i64> {
+		let filter = visible_only(ctx, mm, filter).await?;
+		base::count::<Self, _>(ctx, mm, filter).await
+	}
+
+	pub async fn update(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		claim_u: ClaimForUpdate,
+	) -> Result<()> {
+		base::update::<Self, _>(ctx, mm, id, claim_u).await
+	}
+
+	pub async fn update_if_unchanged(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		claim_u: ClaimForUpdate,
+		expected_mtime: OffsetDateTime,
+	) -> Result<()> {
+		base::update_if_unchanged::<Self, _>(ctx, mm, id, claim_u, expected_mtime)
+			.await
+	}
+
+	pub async fn update_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Vec<ClaimFilter>,
+		claim_u: ClaimForUpdate,
+	) -> Result<BulkAffected> {
+		base::update_many::<Self, _, _>(ctx, mm, filter, claim_u).await
+	}
+
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		base::delete::<Self>(ctx, mm, id).await
+	}
+
+	pub async fn delete_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Vec<ClaimFilter>,
+	) -> Result<BulkAffected> {
+		base::delete_many::<Self, _>(ctx, mm, filter).await
+	}
+
+	/// Only an admin (or `Sys`) user, or a member of the product company,
+	/// can create a claim of the product.
+	pub async fn check_create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		claim_c: &ClaimForCreate,
+	) -> Result<()> {
+		let product = ProductBmc::get(ctx, mm, claim_c.product_id).await?;
+		CompanyMemberBmc::check_admin_or_member(ctx, mm, product.company_id).await
+	}
+
+	/// Only an admin (or `Sys`) user, or a member of the product company,
+	/// can update or delete the claim.
+	pub async fn check_write(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		let claim = Self::get(ctx, mm, id).await?;
+		let product = ProductBmc::get(ctx, mm, claim.product_id).await?;
+		CompanyMemberBmc::check_admin_or_member(ctx, mm, product.company_id).await
+	}
+
+	/// Fails with `Error::ProductClaimDataHidden` if the product claim data is
+	/// hidden (see `check_claim_data_readable`).
+	pub async fn monthly_volume(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+		resolution: ClaimHistoryResolution,
+	) -> Result<Vec<ClaimMonthlyVolume>> {
+		let product = ProductBmc::get(ctx, mm, product_id).await?;
+		check_claim_data_readable(ctx, mm, &product).await?;
+
+		let sqlx_query = sqlx::query_as::<_, ClaimMonthlyVolume>(SQL_MONTHLY_VOLUME)
+			.bind(product_id)
+			.bind(resolution.months());
+		let volumes = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(volumes)
+	}
+
+	/// Fails with `Error::ProductClaimDataHidden` if the product claim data is
+	/// hidden (see `check_claim_data_readable`).
+	pub async fn metrics_by_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+		resolution: ClaimHistoryResolution,
+	) -> Result<ClaimMetrics> {
+		let product = ProductBmc::get(ctx, mm, product_id).await?;
+		check_claim_data_readable(ctx, mm, &product).await?;
+
+		Self::metrics(mm, Some(product_id), None, resolution).await
+	}
+
+	/// Note: The products with hidden claim data are not part of the company metrics.
+	pub async fn metrics_by_company(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+		resolution: ClaimHistoryResolution,
+	) -> Result<ClaimMetrics> {
+		// Fails if the company does not exist.
+		CompanyBmc::get(ctx, mm, company_id).await?;
+
+		Self::metrics(mm, None, Some(company_id), resolution).await
+	}
+
+	/// Returns the claim metrics of each of the given products.
+	/// Notes:
+	///   - The products without claims (in the window) are not part of the result.
+	///   - The claim data visibility is not checked (to be filtered by the caller).
+	pub async fn metrics_by_products(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		product_ids: &[i64],
+		resolution: ClaimHistoryResolution,
+	) -> Result<Vec<ProductClaimMetrics>> {
+		let sqlx_query =
+			sqlx::query_as::<_, ProductClaimMetrics>(SQL_METRICS_BY_PRODUCTS)
+				.bind(product_ids)
+				.bind(resolution.months());
+		let metrics = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(metrics)
+	}
+
+	async fn metrics(
+		mm: &ModelManager,
+		product_id: Option<i64>,
+		company_id: Option<i64>,
+		resolution: ClaimHistoryResolution,
+	) -> Result<ClaimMetrics> {
+		let sqlx_query = sqlx::query_as::<_, ClaimMetrics>(SQL_METRICS)
+			.bind(product_id)
+			.bind(company_id)
+			.bind(resolution.months());
+		let metrics = mm.dbx().fetch_one(sqlx_query).await?;
+
+		Ok(metrics)
+	}
+
+	/// Returns the claim count for each of the given products.
+	/// Products without claims are not part of the result.
+	pub async fn counts_by_products(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		product_ids: &[i64],
+	) -> Result<Vec<ProductClaimCount>> {
+		// -- Build query
+		let mut query = Query::select();
+		query
+			.from(Self::table_ref())
+			.column(ClaimIden::ProductId)
+			.expr_as(
+				Func::count(Expr::col(CommonIden::Id)),
+				Alias::new("count_claim"),
+			)
+			.and_where(Expr::col(ClaimIden::ProductId).is_in(product_ids.to_vec()))
+			.and_where(Expr::col(CommonIden::Deleted).eq(false))
+			.group_by_col(ClaimIden::ProductId);
+
+		// -- Exec query
+		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+		let sqlx_query =
+			sqlx::query_as_with::<_, ProductClaimCount, _>(&sql, values);
+		let counts = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(counts)
+	}
+}
+
+/// Fails with `Error::ProductClaimDataHidden` if the product claim data is
+/// hidden, unless the ctx user is an admin (or `Sys`) or a member of the
+/// product company.
+async fn check_claim_data_readable(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	product: &Product,
+) -> Result<()> {
+	if product.claim_historical_data_visibility {
+		return Ok(());
+	}
+
+	match CompanyMemberBmc::check_admin_or_member(ctx, mm, product.company_id).await
+	{
+		Err(Error::EntityAccessDenied { .. }) => {
+			Err(Error::ProductClaimDataHidden {
+				product_id: product.id,
+			})
+		}
+		res => res,
+	}
+}
+
+/// Restricts the filter to the claims the ctx user can read,
+/// unless the ctx user is an admin (or `Sys`).
+/// Note: Same rule as `check_claim_data_readable`.
+async fn visible_only(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	filter: Option<Vec<ClaimFilter>>,
+) -> Result<Option<Vec<ClaimFilter>>> {
+	match UserBmc::check_typ(ctx, mm, &[UserTyp::Sys, UserTyp::Admin]).await {
+		Ok(()) => Ok(filter),
+		Err(Error::UserNotPrivileged { .. }) => {
+			Ok(base::restrict_filters(filter, |filter| ClaimFilter {
+				readable_by: Some(OpValValue::Eq(ctx.user_id().into()).into()),
+				..filter
+			}))
+		}
+		Err(ex) => Err(ex),
+	}
+}
+
+/// The `ClaimFilter.readable_by` condition: the claims of the products with
+/// visible claim data, or of the companies the user (`$eq` a user id) is a member of.
+fn claim_readable_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let user_id = match op_value {
+		OpValValue::Eq(user_id) => user_id.as_i64(),
+		_ => None,
+	};
+	let user_id = user_id.ok_or_else(|| {
+		IntoSeaError::Custom("readable_by only supports $eq a user id".to_string())
+	})?;
+
+	Ok(Expr::cust_with_values(
+		r#""claim"."product_id" IN (
+			SELECT p.id FROM product p
+			WHERE p.claim_historical_data_visibility OR p.company_id IN (
+				SELECT m.company_id FROM company_member m WHERE m.user_id = $1
+			)
+		)"#,
+		[user_id],
+	)
+	.into())
+}
+
+// endregion: --- ClaimBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::product::{ProductBmc, ProductVisibilityForUpdate};
+	use lib_utils::time::now_utc;
+	use modql::filter::OpValInt64;
+	use serial_test::serial;
+	use time::Duration;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_monthly_volume_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_monthly_volume_ok company 01")
+				.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&["test_monthly_volume_ok product 01"],
+		)
+		.await?[0];
+		let now = now_utc();
+		let fx_submit_times = [now, now, now - Duration::days(400)];
+		for submit_time in fx_submit_times {
+			ClaimBmc::create(
+				&ctx,
+				&mm,
+				ClaimForCreate {
+					product_id: fx_product_id,
+					status: None,
+					submit_time,
+					decision_time: None,
+					payment_time: None,
+				},
+			)
+			.await?;
+		}
+
+		// -- Exec
+		let one_year = ClaimBmc::monthly_volume(
+			&ctx,
+			&mm,
+			fx_product_id,
+			ClaimHistoryResolution::OneYear,
+		)
+		.await?;
+		let three_year = ClaimBmc::monthly_volume(
+			&ctx,
+			&mm,
+			fx_product_id,
+			ClaimHistoryResolution::ThreeYear,
+		)
+		.await?;
+		let all_time = ClaimBmc::monthly_volume(
+			&ctx,
+			&mm,
+			fx_product_id,
+			ClaimHistoryResolution::AllTime,
+		)
+		.await?;
+
+		// -- Check
+		let total = |volumes: &[ClaimMonthlyVolume]| -> i64 {
+			volumes.iter().map(|v| v.volume).sum()
+		};
+		assert_eq!(one_year.len(), 12);
+		assert_eq!(total(&one_year), 2);
+		assert_eq!(one_year.last().map(|v| v.volume), Some(2));
+		assert_eq!(three_year.len(), 36);
+		assert_eq!(total(&three_year), 3);
+		assert!(all_time.len() >= 14);
+		assert_eq!(all_time.first().map(|v| v.volume), Some(1));
+		assert_eq!(total(&all_time), 3);
+
+		// -- Clean
+		let filter = ClaimFilter {
+			product_id: Some(OpValInt64::Eq(fx_product_id).into()),
+			..Default::default()
+		};
+		ClaimBmc::delete_many(&ctx, &mm, vec![filter]).await?;
+		ProductBmc::delete(&ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_metrics_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_metrics_ok company 01")
+				.await?;
+		let fx_product_ids = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&["test_metrics_ok product 01", "test_metrics_ok product 02"],
+		)
+		.await?;
+		let fx_user_id =
+			_dev_utils::seed_user(&ctx, &mm, "test_metrics_ok-user-01").await?;
+		let user_ctx = Ctx::new(fx_user_id)?;
+		let now = now_utc();
+		let days = |d: i64| now - Duration::days(d);
+		// (status, submit, decision, payment) in days ago.
+		let fx_claims = [
+			(ClaimStatus::Submitted, 0, None, None),
+			(ClaimStatus::Approved, 10, Some(6), None),
+			(ClaimStatus::Paid, 20, Some(18), Some(10)),
+			(ClaimStatus::Rejected, 30, Some(24), None),
+			(ClaimStatus::Rejected, 400, Some(399), None),
+		];
+		for product_id in &fx_product_ids {
+			for (status, submit, decision, payment) in fx_claims {
+				ClaimBmc::create(
+					&ctx,
+					&mm,
+					ClaimForCreate {
+						product_id: *product_id,
+						status: Some(status),
+						submit_time: days(submit),
+						decision_time: decision.map(days),
+						payment_time: payment.map(days),
+					},
+				)
+				.await?;
+			}
+		}
+		ProductBmc::update_visibility(
+			&ctx,
+			&mm,
+			fx_product_ids[1],
+			ProductVisibilityForUpdate {
+				review_visibility: None,
+				claim_historical_data_visibility: Some(false),
+			},
+		)
+		.await?;
+
+		// -- Exec
+		let one_year = ClaimBmc::metrics_by_product(
+			&ctx,
+			&mm,
+			fx_product_ids[0],
+			ClaimHistoryResolution::OneYear,
+		)
+		.await?;
+		let all_time = ClaimBmc::metrics_by_product(
+			&ctx,
+			&mm,
+			fx_product_ids[0],
+			ClaimHistoryResolution::AllTime,
+		)
+		.await?;
+		let company = ClaimBmc::metrics_by_company(
+			&ctx,
+			&mm,
+			fx_company_id,
+			ClaimHistoryResolution::AllTime,
+		)
+		.await?;
+		let res_hidden = ClaimBmc::metrics_by_product(
+			&user_ctx,
+			&mm,
+			fx_product_ids[1],
+			ClaimHistoryResolution::AllTime,
+		)
+		.await;
+		let hidden = ClaimBmc::metrics_by_product(
+			&ctx,
+			&mm,
+			fx_product_ids[1],
+			ClaimHistoryResolution::AllTime,
+		)
+		.await?;
+		// Inconsistent status and times (rejected by the table checks).
+		let fx_claims_invalid = [
+			(ClaimStatus::Approved, None, None),
+			(ClaimStatus::Paid, Some(days(1)), None),
+			(ClaimStatus::Rejected, Some(days(2)), None),
+		];
+		let mut res_invalid = Vec::new();
+		for (status, decision_time, payment_time) in fx_claims_invalid {
+			let res = ClaimBmc::create(
+				&ctx,
+				&mm,
+				ClaimForCreate {
+					product_id: fx_product_ids[0],
+					status: Some(status),
+					submit_time: days(1),
+					decision_time,
+					payment_time,
+				},
+			)
+			.await;
+			res_invalid.push(res);
+		}
+
+		// -- Check
+		let approx = |actual: Option<f64>, expected: f64| {
+			actual.is_some_and(|actual| (actual - expected).abs() < 0.001)
+		};
+		assert_eq!(one_year.count_claim, 4);
+		assert_eq!(one_year.count_decided, 3);
+		assert!(approx(one_year.settlement_ratio(), 2. / 3.));
+		assert!(approx(one_year.rejection_ratio(), 1. / 3.));
+		assert!(approx(one_year.median_days_to_decision, 4.));
+		assert!(approx(one_year.median_days_to_payment, 10.));
+		assert_eq!(all_time.count_claim, 5);
+		assert!(approx(all_time.settlement_ratio(), 0.5));
+		assert!(approx(all_time.median_days_to_decision, 3.));
+		assert_eq!(company.count_claim, all_time.count_claim);
+		assert!(
+			matches!(res_hidden, Err(crate::model::Error::ProductClaimDataHidden { product_id }) if product_id == fx_product_ids[1]),
+			"ProductClaimDataHidden not matching"
+		);
+		assert_eq!(hidden.count_claim, all_time.count_claim);
+		assert!(res_invalid.iter().all(|res| res.is_err()));
+
+		// -- Clean
+		let filter = ClaimFilter {
+			product_id: Some(OpValInt64::In(fx_product_ids.clone()).into()),
+			..Default::default()
+		};
+		ClaimBmc::delete_many(&ctx, &mm, vec![filter]).await?;
+		for product_id in fx_product_ids {
+			ProductBmc::delete(&ctx, &mm, product_id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+		UserBmc::delete(&ctx, &mm, fx_user_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -0,0 +1,374 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::company_member::CompanyMemberBmc;
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::product::{Product, ProductBmc, ProductFilter};
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::Result;
+use crate::model::{BulkAffected, ListCursorMeta, ListCursorOptions, ListMeta};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, ListOptions, OpValInt64, OpValsInt64, OpValsString, OpValsValue,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- Company Types
+
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct Company {
+	pub id: i64,
+
+	pub name: String,
+	pub description: String,
+	pub logo: Option<String>,
+	pub website_url: Option<String>,
+	pub location: Option<String>,
+	pub founded_year: Option<i32>,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize)]
+pub struct CompanyForCreate {
+	pub name: String,
+	pub description: Option<String>,
+	pub logo: Option<String>,
+	pub website_url: Option<String>,
+	pub location: Option<String>,
+	pub founded_year: Option<i32>,
+}
+
+#[derive(Fields, Default, Deserialize)]
+pub struct CompanyForUpdate {
+	pub name: Option<String>,
+	pub description: Option<String>,
+	pub logo: Option<String>,
+	pub website_url: Option<String>,
+	pub location: Option<String>,
+	pub founded_year: Option<i32>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct CompanyFilter {
+	pub id: Option<OpValsInt64>,
+
+	pub name: Option<OpValsString>,
+	pub location: Option<OpValsString>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+/// A company together with the products it offers.
+#[derive(Debug, Serialize)]
+pub struct CompanyWithProducts {
+	#[serde(flatten)]
+	pub company: Company,
+	pub products: Vec<Product>,
+}
+
+// endregion: --- Company Types
+
+// region:    --- CompanyBmc
+
+pub struct CompanyBmc;
+
+impl DbBmc for CompanyBmc {
+	const TABLE: &'static str = "company";
+
+	fn has_soft_delete() -> bool {
+		true
+	}
+
+	fn audit_excluded_fields() -> &'static [&'static str] {
+		&["search"]
+	}
+}
+
+impl CompanyBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_c: CompanyForCreate,
+	) -> Result<i64> {
+		base::create::<Self, _>(ctx, mm, company_c).await
+	}
+
+	pub async fn create_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		companies_c: Vec<CompanyForCreate>,
+	) -> Result<BulkAffected> {
+		base::create_many::<Self, _>(ctx, mm, companies_c).await
+	}
+
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Company> {
+		base::get::<Self, _>(ctx, mm, id).await
+	}
+
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CompanyFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Company>> {
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CompanyFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<Company>, ListMeta)> {
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	pub async fn list_by_cursor(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CompanyFilter>>,
+		cursor_options: Option<ListCursorOptions>,
+	) -> Result<(Vec<Company>, ListCursorMeta)> {
+		base::list_by_cursor::<Self, _, _>(ctx, mm, filter, cursor_options).await
+	}
+
+	pub async fn count(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CompanyFilter>>,
+	) -> Result<i64> {
+		base::count::<Self, _>(ctx, mm, filter).await
+	}
+
+	pub async fn update(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		company_u: CompanyForUpdate,
+	) -> Result<()> {
+		base::update::<Self, _>(ctx, mm, id, company_u).await
+	}
+
+	pub async fn update_if_unchanged(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		company_u: CompanyForUpdate,
+		expected_mtime: OffsetDateTime,
+	) -> Result<()> {
+		base::update_if_unchanged::<Self, _>(ctx, mm, id, company_u, expected_mtime)
+			.await
+	}
+
+	pub async fn update_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Vec<CompanyFilter>,
+		company_u: CompanyForUpdate,
+	) -> Result<BulkAffected> {
+		base::update_many::<Self, _, _>(ctx, mm, filter, company_u).await
+	}
+
+	/// Soft deletes the company and its products, in one transaction
+	/// (each recorded in the `entity_change` audit log).
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		let mm = mm.new_with_txn()?;
+		mm.dbx().begin_txn().await?;
+
+		base::delete::<Self>(ctx, &mm, id).await?;
+		delete_products(ctx, &mm, vec![id]).await?;
+
+		mm.dbx().commit_txn().await?;
+
+		Ok(())
+	}
+
+	/// Same as `delete`, for all the companies matching the filter.
+	pub async fn delete_many(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Vec<CompanyFilter>,
+	) -> Result<BulkAffected> {
+		let mm = mm.new_with_txn()?;
+		mm.dbx().begin_txn().await?;
+
+		let affected = base::delete_many::<Self, _>(ctx, &mm, filter).await?;
+		delete_products(ctx, &mm, affected.ids.clone()).await?;
+
+		mm.dbx().commit_txn().await?;
+
+		Ok(affected)
+	}
+
+	/// Only an admin (or `Sys`) user can create a company.
+	pub async fn check_create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		_company_c: &CompanyForCreate,
+	) -> Result<()> {
+		UserBmc::check_typ(ctx, mm, &[UserTyp::Sys, UserTyp::Admin]).await
+	}
+
+	/// Only an admin (or `Sys`) user, or a member of the company,
+	/// can update or delete the company.
+	pub async fn check_write(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		Self::get(ctx, mm, id).await?;
+		CompanyMemberBmc::check_admin_or_member(ctx, mm, id).await
+	}
+
+	pub async fn get_with_products(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+	) -> Result<CompanyWithProducts> {
+		let company = Self::get(ctx, mm, id).await?;
+
+		let filter = ProductFilter {
+			company_id: Some(id.into()),
+			..Default::default()
+		};
+		let products = ProductBmc::list(ctx, mm, Some(vec![filter]), None).await?;
+
+		Ok(CompanyWithProducts { company, products })
+	}
+}
+
+/// Soft deletes the products of the companies.
+/// Note: Must be called within the transaction of the company delete.
+async fn delete_products(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	company_ids: Vec<i64>,
+) -> Result<()> {
+	if company_ids.is_empty() {
+		return Ok(());
+	}
+	let filter = ProductFilter {
+		company_id: Some(OpValInt64::In(company_ids).into()),
+		..Default::default()
+	};
+	ProductBmc::delete_many(ctx, mm, vec![filter]).await?;
+
+	Ok(())
+}
+
+// endregion: --- CompanyBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::entity_change::EntityChangeBmc;
+	use crate::model::Error as ModelError;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_get_with_products_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_name = "test_get_with_products_ok company 01";
+		let fx_product_names = &[
+			"test_get_with_products_ok product 01",
+			"test_get_with_products_ok product 02",
+		];
+		let company_id =
+			_dev_utils::seed_company(&ctx, &mm, fx_company_name).await?;
+		let product_ids =
+			_dev_utils::seed_products(&ctx, &mm, company_id, fx_product_names)
+				.await?;
+
+		// -- Exec
+		let company = CompanyBmc::get_with_products(&ctx, &mm, company_id).await?;
+
+		// -- Check
+		assert_eq!(company.company.name, fx_company_name);
+		let names: Vec<&str> =
+			company.products.iter().map(|p| p.name.as_str()).collect();
+		assert_eq!(names, fx_product_names);
+
+		// -- Clean
+		for id in product_ids {
+			ProductBmc::delete(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_delete_ok_soft_with_products() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_delete_ok_soft_with_products company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&["test_delete_ok_soft_with_products product 01"],
+		)
+		.await?[0];
+
+		// -- Exec
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		// -- Check
+		let res = CompanyBmc::get(&ctx, &mm, fx_company_id).await;
+		assert!(
+			matches!(res, Err(ModelError::EntityNotFound { entity: "company", id }) if id == fx_company_id),
+			"EntityNotFound not matching"
+		);
+		let res = ProductBmc::get(&ctx, &mm, fx_product_id).await;
+		assert!(
+			matches!(res, Err(ModelError::EntityNotFound { entity: "product", id }) if id == fx_product_id),
+			"EntityNotFound not matching"
+		);
+		let product =
+			ProductBmc::get_including_deleted(&ctx, &mm, fx_product_id).await?;
+		assert_eq!(product.company_id, fx_company_id);
+		for (entity, id) in [("company", fx_company_id), ("product", fx_product_id)]
+		{
+			let changes =
+				EntityChangeBmc::list_by_entity(&ctx, &mm, entity, id).await?;
+			let ops: Vec<String> =
+				changes.iter().map(|c| c.op.to_string()).collect();
+			assert_eq!(ops, ["Create", "Delete"]);
+		}
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -0,0 +1,352 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::company::CompanyBmc;
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::{Error, ListMeta, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, IntoSeaError, ListOptions, OpValValue, OpValsInt64, OpValsValue,
+	SeaResult,
+};
+use sea_query::{ColumnRef, ConditionExpression, Expr};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- CompanyMember Types
+
+/// A user acting for the company (e.g., the insurer staff).
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct CompanyMember {
+	pub id: i64,
+	pub company_id: i64,
+	pub user_id: i64,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize)]
+pub struct CompanyMemberForCreate {
+	pub company_id: i64,
+	pub user_id: i64,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct CompanyMemberFilter {
+	pub id: Option<OpValsInt64>,
+	pub company_id: Option<OpValsInt64>,
+	pub user_id: Option<OpValsInt64>,
+
+	/// The members of the companies of the user (`$eq` a user id).
+	/// Note: Set by the list fns only.
+	#[serde(skip)]
+	#[modql(to_sea_condition_fn = "company_member_colleague_cond")]
+	pub colleague_of: Option<OpValsValue>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+// endregion: --- CompanyMember Types
+
+// region:    --- CompanyMemberBmc
+
+pub struct CompanyMemberBmc;
+
+impl DbBmc for CompanyMemberBmc {
+	const TABLE: &'static str = "company_member";
+}
+
+impl CompanyMemberBmc {
+	/// Only a `Sys` user (or the root ctx) can add a member.
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_member_c: CompanyMemberForCreate,
+	) -> Result<i64> {
+		UserBmc::check_typ(ctx, mm, &[UserTyp::Sys]).await?;
+
+		base::create::<Self, _>(ctx, mm, company_member_c).await
+	}
+
+	/// Only an admin (or `Sys`) user, or a member of the same company can get it.
+	pub async fn get(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+	) -> Result<CompanyMember> {
+		let member: CompanyMember = base::get::<Self, _>(ctx, mm, id).await?;
+		Self::check_admin_or_member(ctx, mm, member.company_id).await?;
+
+		Ok(member)
+	}
+
+	/// Note: All the members for the admin (or `Sys`) users, only the members
+	///       of the companies of the ctx user for the other users.
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CompanyMemberFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<CompanyMember>> {
+		let filter = Self::colleagues_only(ctx, mm, filter).await?;
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	/// Same as `list`, with the list metadata (see `base::list_with_meta`).
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CompanyMemberFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<CompanyMember>, ListMeta)> {
+		let filter = Self::colleagues_only(ctx, mm, filter).await?;
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	/// Only a `Sys` user (or the root ctx) can remove a member.
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		UserBmc::check_typ(ctx, mm, &[UserTyp::Sys]).await?;
+
+		base::delete::<Self>(ctx, mm, id).await
+	}
+
+	/// Returns the ids of the companies the ctx user is a member of.
+	pub async fn company_ids_by_ctx(
+		ctx: &Ctx,
+		mm: &ModelManager,
+	) -> Result<Vec<i64>> {
+		let filter = CompanyMemberFilter {
+			user_id: Some(ctx.user_id().into()),
+			..Default::default()
+		};
+		// Note: The ctx user own memberships, so without the `Self::list` restriction.
+		let members: Vec<CompanyMember> =
+			base::list::<Self, _, _>(ctx, mm, Some(vec![filter]), None).await?;
+		let company_ids = members
+			.into_iter()
+			.map(|member| member.company_id)
+			.collect();
+
+		Ok(company_ids)
+	}
+
+	/// Fails with `Error::EntityAccessDenied` (on the company) if the ctx user
+	/// is not a member of the company.
+	/// Note: The root ctx (system) is always allowed.
+	pub async fn check_member(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+	) -> Result<()> {
+		if ctx.user_id() == 0
+			|| Self::company_ids_by_ctx(ctx, mm)
+				.await?
+				.contains(&company_id)
+		{
+			Ok(())
+		} else {
+			Err(Error::EntityAccessDenied {
+				entity: CompanyBmc::TABLE,
+				id: company_id,
+			})
+		}
+	}
+
+	/// Same as `check_member`, but an admin (or `Sys`) user is always allowed.
+	pub async fn check_admin_or_member(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+	) -> Result<()> {
+		match UserBmc::check_typ(ctx, mm, &[UserTyp::Sys, UserTyp::Admin]).await {
+			Err(Error::UserNotPrivileged { .. }) => {
+				Self::check_member(ctx, mm, company_id).await
+			}
+			res => res,
+		}
+	}
+
+	/// Restricts the filter to the members of the companies of the ctx user,
+	/// unless the ctx user is an admin (or `Sys`) user.
+	async fn colleagues_only(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<CompanyMemberFilter>>,
+	) -> Result<Option<Vec<CompanyMemberFilter>>> {
+		match UserBmc::check_typ(ctx, mm, &[UserTyp::Sys, UserTyp::Admin]).await {
+			Ok(()) => Ok(filter),
+			Err(Error::UserNotPrivileged { .. }) => {
+				Ok(base::restrict_filters(filter, |filter| {
+					CompanyMemberFilter {
+						colleague_of: Some(
+							OpValValue::Eq(ctx.user_id().into()).into(),
+						),
+						..filter
+					}
+				}))
+			}
+			Err(ex) => Err(ex),
+		}
+	}
+}
+
+/// The `CompanyMemberFilter.colleague_of` condition: the members of the
+/// companies the user (`$eq` a user id) is a member of.
+fn company_member_colleague_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let user_id = match op_value {
+		OpValValue::Eq(user_id) => user_id.as_i64(),
+		_ => None,
+	};
+	let user_id = user_id.ok_or_else(|| {
+		IntoSeaError::Custom("colleague_of only supports $eq a user id".to_string())
+	})?;
+
+	Ok(Expr::cust_with_values(
+		r#""company_member"."company_id" IN (
+			SELECT m.company_id FROM company_member m WHERE m.user_id = $1
+		)"#,
+		[user_id],
+	)
+	.into())
+}
+
+// endregion: --- CompanyMemberBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::Error as ModelError;
+	use modql::filter::OpValInt64;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_ok_colleagues_only() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let mut fx_company_ids = Vec::new();
+		for name in ["01", "02"] {
+			let company_id = _dev_utils::seed_company(
+				&root_ctx,
+				&mm,
+				&format!("test_list_ok_colleagues_only company {name}"),
+			)
+			.await?;
+			fx_company_ids.push(company_id);
+		}
+		let fx_user_ids = _dev_utils::seed_users(
+			&root_ctx,
+			&mm,
+			&[
+				"test_list_ok_colleagues_only-staff-01",
+				"test_list_ok_colleagues_only-staff-02",
+				"test_list_ok_colleagues_only-user",
+				"test_list_ok_colleagues_only-admin",
+			],
+		)
+		.await?;
+		UserBmc::update_typ(&root_ctx, &mm, fx_user_ids[3], UserTyp::Admin).await?;
+		let mut fx_member_ids = Vec::new();
+		for (company_id, user_id) in fx_company_ids.iter().zip(&fx_user_ids[..2]) {
+			let id = CompanyMemberBmc::create(
+				&root_ctx,
+				&mm,
+				CompanyMemberForCreate {
+					company_id: *company_id,
+					user_id: *user_id,
+				},
+			)
+			.await?;
+			fx_member_ids.push(id);
+		}
+		let staff_ctx = Ctx::new(fx_user_ids[0])?;
+		let user_ctx = Ctx::new(fx_user_ids[2])?;
+		let admin_ctx = Ctx::new(fx_user_ids[3])?;
+		let filter = |company_ids: &[i64]| CompanyMemberFilter {
+			company_id: Some(OpValInt64::In(company_ids.to_vec()).into()),
+			..Default::default()
+		};
+		let member_ids = |members: Vec<CompanyMember>| -> Vec<i64> {
+			members.into_iter().map(|m| m.id).collect()
+		};
+
+		// -- Exec
+		let staff_members = CompanyMemberBmc::list(
+			&staff_ctx,
+			&mm,
+			Some(vec![filter(&fx_company_ids)]),
+			None,
+		)
+		.await?;
+		let user_members = CompanyMemberBmc::list(
+			&user_ctx,
+			&mm,
+			Some(vec![filter(&fx_company_ids)]),
+			None,
+		)
+		.await?;
+		let admin_members = CompanyMemberBmc::list(
+			&admin_ctx,
+			&mm,
+			Some(vec![filter(&fx_company_ids)]),
+			None,
+		)
+		.await?;
+		let res_get = CompanyMemberBmc::get(&staff_ctx, &mm, fx_member_ids[1]).await;
+
+		// -- Check
+		assert_eq!(member_ids(staff_members), &fx_member_ids[..1]);
+		assert!(user_members.is_empty(), "non member should list no member");
+		assert_eq!(member_ids(admin_members), fx_member_ids);
+		assert!(
+			matches!(
+				res_get,
+				Err(ModelError::EntityAccessDenied { entity: "company", id })
+					if id == fx_company_ids[1]
+			),
+			"EntityAccessDenied not matching"
+		);
+
+		// -- Clean
+		for id in fx_member_ids {
+			CompanyMemberBmc::delete(&root_ctx, &mm, id).await?;
+		}
+		for company_id in fx_company_ids {
+			CompanyBmc::delete(&root_ctx, &mm, company_id).await?;
+		}
+		_dev_utils::clean_users(&root_ctx, &mm, "test_list_ok_colleagues_only")
+			.await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
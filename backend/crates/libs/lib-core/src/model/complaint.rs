@@ -0,0 +1,927 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::claim::{Claim, ClaimBmc};
+use crate::model::company::CompanyBmc;
+use crate::model::company_member::CompanyMemberBmc;
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::product::ProductBmc;
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::{Error, ListMeta, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, ListOptions, OpValsInt64, OpValsString, OpValsValue,
+};
+use sea_query::Nullable;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- Complaint Types
+
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	PartialEq,
+	Eq,
+	sqlx::Type,
+	derive_more::Display,
+	Deserialize,
+	Serialize,
+)]
+#[sqlx(type_name = "complaint_category")]
+pub enum ComplaintCategory {
+	ClaimDenial,
+	ClaimDelay,
+	PolicyTerms,
+	Premium,
+	Service,
+	Other,
+}
+impl From<ComplaintCategory> for sea_query::Value {
+	fn from(val: ComplaintCategory) -> Self {
+		val.to_string().into()
+	}
+}
+
+/// Open -> InsurerResponded -> Escalated -> Resolved
+/// (see `ComplaintBmc` for the allowed transitions).
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	PartialEq,
+	Eq,
+	sqlx::Type,
+	derive_more::Display,
+	Deserialize,
+	Serialize,
+)]
+#[sqlx(type_name = "complaint_status")]
+pub enum ComplaintStatus {
+	Open,
+	InsurerResponded,
+	Escalated,
+	Resolved,
+}
+impl From<ComplaintStatus> for sea_query::Value {
+	fn from(val: ComplaintStatus) -> Self {
+		val.to_string().into()
+	}
+}
+impl Nullable for ComplaintStatus {
+	fn null() -> sea_query::Value {
+		sea_query::Value::String(None)
+	}
+}
+
+/// A reference to a document of the complaint (e.g., the claim denial letter).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ComplaintAttachment {
+	pub name: String,
+	pub url: String,
+}
+
+/// The attachments of a complaint (stored as jsonb).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ComplaintAttachments(pub Vec<ComplaintAttachment>);
+
+impl From<ComplaintAttachments> for sea_query::Value {
+	fn from(val: ComplaintAttachments) -> Self {
+		serde_json::to_value(val).unwrap_or_default().into()
+	}
+}
+
+/// The complainant is the `cid` (the user that filed the complaint).
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct Complaint {
+	pub id: i64,
+	pub company_id: i64,
+	pub product_id: Option<i64>,
+	pub claim_id: Option<i64>,
+	pub policy_number: String,
+
+	pub category: ComplaintCategory,
+	pub narrative: String,
+	#[sqlx(json)]
+	pub attachments: ComplaintAttachments,
+
+	// -- Lifecycle
+	pub status: ComplaintStatus,
+	pub insurer_response: String,
+	pub escalation_reason: String,
+	pub resolution: String,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+/// Note: The product and claim (if any) must be of the company.
+///       Without a product, the product is the one of the claim (if any).
+#[derive(Deserialize)]
+pub struct ComplaintForCreate {
+	pub company_id: i64,
+	pub product_id: Option<i64>,
+	pub claim_id: Option<i64>,
+	pub policy_number: Option<String>,
+
+	pub category: ComplaintCategory,
+	pub narrative: String,
+	pub attachments: Option<ComplaintAttachments>,
+}
+
+#[derive(Fields)]
+struct ComplaintForInsert {
+	company_id: i64,
+	product_id: Option<i64>,
+	claim_id: Option<i64>,
+	policy_number: Option<String>,
+	#[field(cast_as = "complaint_category")]
+	category: ComplaintCategory,
+	narrative: String,
+	attachments: ComplaintAttachments,
+}
+
+/// A lifecycle transition, with the text of the transition
+/// (i.e., `insurer_response`, `escalation_reason`, or `resolution`).
+#[derive(Fields, Default)]
+struct ComplaintForTransition {
+	#[field(cast_as = "complaint_status")]
+	status: Option<ComplaintStatus>,
+	insurer_response: Option<String>,
+	escalation_reason: Option<String>,
+	resolution: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ComplaintFilter {
+	pub id: Option<OpValsInt64>,
+	pub company_id: Option<OpValsInt64>,
+	pub product_id: Option<OpValsInt64>,
+	pub claim_id: Option<OpValsInt64>,
+
+	#[modql(cast_as = "complaint_category")]
+	pub category: Option<OpValsString>,
+	#[modql(cast_as = "complaint_status")]
+	pub status: Option<OpValsString>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+/// The complaint counts of a company.
+#[derive(Debug, Clone, Default, FromRow, Serialize)]
+pub struct CompanyComplaintCount {
+	pub count_complaint: i64,
+	pub count_resolved: i64,
+}
+
+/// The counts of the complaint rate of a company or product, over the same products
+/// (the claims of all the products, whatever their claim data visibility).
+///
+/// Note: Only the triaged complaints count, i.e., responded by the insurer,
+///       or resolved by a moderator (not withdrawn by the complainant).
+#[derive(Debug, Clone, Default, FromRow, Serialize)]
+pub struct ComplaintRateCount {
+	pub count_complaint: i64,
+	pub count_claim: i64,
+}
+
+impl ComplaintRateCount {
+	/// Complaints per claim (`None` without claims).
+	pub fn rate(&self) -> Option<f64> {
+		(self.count_claim > 0)
+			.then(|| self.count_complaint as f64 / self.count_claim as f64)
+	}
+}
+
+// endregion: --- Complaint Types
+
+// region:    --- ComplaintBmc
+
+pub struct ComplaintBmc;
+
+impl DbBmc for ComplaintBmc {
+	const TABLE: &'static str = "complaint";
+}
+
+/// The complaints about the (not deleted) products of the company
+/// (with the complaints without product).
+///
+/// - `$1` company_id
+/// - `$2` product_id (NULL for all the complaints of the company)
+/// - `$3` number of months of the window (NULL for since the first complaint)
+const SQL_COUNT: &str = r#"
+SELECT
+	count(complaint.id) AS count_complaint,
+	count(complaint.id) FILTER (WHERE complaint.status = 'Resolved') AS count_resolved
+FROM complaint
+	LEFT JOIN product ON product.id = complaint.product_id
+WHERE complaint.company_id = $1
+	AND ($2::bigint IS NULL OR complaint.product_id = $2)
+	AND (product.id IS NULL OR NOT product.deleted)
+	AND ($3::int IS NULL OR complaint.ctime >=
+		date_trunc('month', now(), 'UTC') - make_interval(months => $3 - 1))
+"#;
+
+/// The triaged complaints and the claims of the (not deleted) products of the company
+/// (with the complaints without product for the company).
+///
+/// - `$1` company_id
+/// - `$2` product_id (NULL for all the products of the company)
+/// - `$3` number of months of the window (NULL for all time)
+const SQL_RATE_COUNT: &str = r#"
+SELECT
+	(SELECT count(complaint.id) FROM complaint
+		LEFT JOIN product ON product.id = complaint.product_id
+	WHERE complaint.company_id = $1
+		AND ($2::bigint IS NULL OR complaint.product_id = $2)
+		AND (product.id IS NULL OR NOT product.deleted)
+		AND (complaint.insurer_response <> ''
+			OR (complaint.status = 'Resolved' AND complaint.mid <> complaint.cid))
+		AND ($3::int IS NULL OR complaint.ctime >=
+			date_trunc('month', now(), 'UTC') - make_interval(months => $3 - 1))
+	) AS count_complaint,
+	(SELECT count(claim.id) FROM claim
+		JOIN product ON product.id = claim.product_id
+	WHERE product.company_id = $1
+		AND ($2::bigint IS NULL OR claim.product_id = $2)
+		AND NOT product.deleted
+		AND NOT claim.deleted
+		AND ($3::int IS NULL OR claim.submit_time >=
+			date_trunc('month', now(), 'UTC') - make_interval(months => $3 - 1))
+	) AS count_claim
+"#;
+
+impl ComplaintBmc {
+	/// Files a complaint (the ctx user is the complainant).
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		complaint_c: ComplaintForCreate,
+	) -> Result<i64> {
+		let ComplaintForCreate {
+			company_id,
+			mut product_id,
+			claim_id,
+			policy_number,
+			category,
+			narrative,
+			attachments,
+		} = complaint_c;
+		check_text("narrative", &narrative)?;
+		let attachments = attachments.unwrap_or_default();
+		check_attachments(&attachments)?;
+
+		// -- Check the links are of the company.
+		// Note: The claim is read without the claim data visibility check
+		//       (a complaint can be about a claim of a hidden product).
+		CompanyBmc::get(ctx, mm, company_id).await?;
+		if let Some(claim_id) = claim_id {
+			let claim: Claim = base::get::<ClaimBmc, _>(ctx, mm, claim_id).await?;
+			if product_id.is_some_and(|id| id != claim.product_id) {
+				return Err(Error::ComplaintLinkInvalid {
+					entity: ClaimBmc::TABLE,
+					id: claim_id,
+				});
+			}
+			product_id = Some(claim.product_id);
+		}
+		if let Some(product_id) = product_id {
+			let product = ProductBmc::get(ctx, mm, product_id).await?;
+			if product.company_id != company_id {
+				return Err(Error::ComplaintLinkInvalid {
+					entity: ProductBmc::TABLE,
+					id: product_id,
+				});
+			}
+		}
+
+		let complaint_fi = ComplaintForInsert {
+			company_id,
+			product_id,
+			claim_id,
+			policy_number,
+			category,
+			narrative,
+			attachments,
+		};
+		base::create::<Self, _>(ctx, mm, complaint_fi).await
+	}
+
+	/// Restricts the filter to the complaints of the ctx user,
+	/// unless the ctx user is a moderator.
+	async fn complainant_only(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ComplaintFilter>>,
+	) -> Result<Option<Vec<ComplaintFilter>>> {
+		match UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await {
+			Ok(()) => Ok(filter),
+			Err(Error::UserNotPrivileged { .. }) => {
+				Ok(base::restrict_filters(filter, |filter| ComplaintFilter {
+					cid: Some(ctx.user_id().into()),
+					..filter
+				}))
+			}
+			Err(ex) => Err(ex),
+		}
+	}
+
+	/// Only the complainant, a member of the company, or a moderator can get it.
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Complaint> {
+		let complaint: Complaint = base::get::<Self, _>(ctx, mm, id).await?;
+
+		let is_complainant = complaint.cid == ctx.user_id();
+		let is_member =
+			CompanyMemberBmc::check_member(ctx, mm, complaint.company_id)
+				.await
+				.is_ok();
+		let is_moderator = UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS)
+			.await
+			.is_ok();
+		if is_complainant || is_member || is_moderator {
+			Ok(complaint)
+		} else {
+			Err(Error::EntityAccessDenied {
+				entity: Self::TABLE,
+				id,
+			})
+		}
+	}
+
+	/// Returns the complaints of the ctx user (all the complaints for a moderator).
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ComplaintFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Complaint>> {
+		let filter = Self::complainant_only(ctx, mm, filter).await?;
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	/// Same as `list`, with the list metadata (see `base::list_with_meta`).
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ComplaintFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<Complaint>, ListMeta)> {
+		let filter = Self::complainant_only(ctx, mm, filter).await?;
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	/// Returns the complaints against the company (for the company members),
+	/// with the list metadata (see `base::list_with_meta`).
+	pub async fn list_by_company(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+		filter: Option<Vec<ComplaintFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<Complaint>, ListMeta)> {
+		CompanyMemberBmc::check_member(ctx, mm, company_id).await?;
+
+		let filter = base::restrict_filters(filter, |filter| ComplaintFilter {
+			company_id: Some(company_id.into()),
+			..filter
+		});
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	/// The complaint counts of the company, over the last `months`
+	/// (calendar months, including the current one), or all time if None.
+	pub async fn count_by_company(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+		months: Option<i32>,
+	) -> Result<CompanyComplaintCount> {
+		Self::count(mm, company_id, None, months).await
+	}
+
+	/// Same as `count_by_company`, for the complaints about the product.
+	pub async fn count_by_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+		months: Option<i32>,
+	) -> Result<CompanyComplaintCount> {
+		let product = ProductBmc::get(ctx, mm, product_id).await?;
+		Self::count(mm, product.company_id, Some(product.id), months).await
+	}
+
+	/// The counts of the complaint rate of the company, over the last `months`
+	/// (calendar months, including the current one), or all time if None.
+	/// Note: Only the rate is meant to be shown, as the claim count includes
+	///       the products with hidden claim data.
+	pub async fn rate_count_by_company(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+		months: Option<i32>,
+	) -> Result<ComplaintRateCount> {
+		Self::rate_count(mm, company_id, None, months).await
+	}
+
+	/// Same as `rate_count_by_company`, for the product.
+	pub async fn rate_count_by_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+		months: Option<i32>,
+	) -> Result<ComplaintRateCount> {
+		let product = ProductBmc::get(ctx, mm, product_id).await?;
+		Self::rate_count(mm, product.company_id, Some(product.id), months).await
+	}
+
+	// -- Lifecycle
+	// Note: A transition only applies to the complaint as read (status checked),
+	//       so a concurrent transition fails with `Error::ConcurrentModification`.
+
+	/// Open -> InsurerResponded, by a member of the company.
+	pub async fn respond(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		response: String,
+	) -> Result<()> {
+		check_text("insurer_response", &response)?;
+		let complaint = Self::get(ctx, mm, id).await?;
+		CompanyMemberBmc::check_member(ctx, mm, complaint.company_id).await?;
+		check_status(&complaint, &[ComplaintStatus::Open])?;
+
+		let complaint_ft = ComplaintForTransition {
+			status: Some(ComplaintStatus::InsurerResponded),
+			insurer_response: Some(response),
+			..Default::default()
+		};
+		base::update_if_unchanged::<Self, _>(
+			ctx,
+			mm,
+			id,
+			complaint_ft,
+			complaint.mtime,
+		)
+		.await
+	}
+
+	/// Open or InsurerResponded -> Escalated, by the complainant
+	/// (e.g., the response is not satisfying, or the insurer does not respond).
+	pub async fn escalate(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		reason: String,
+	) -> Result<()> {
+		check_text("escalation_reason", &reason)?;
+		let complaint = Self::get(ctx, mm, id).await?;
+		check_complainant(ctx, &complaint)?;
+		check_status(
+			&complaint,
+			&[ComplaintStatus::Open, ComplaintStatus::InsurerResponded],
+		)?;
+
+		let complaint_ft = ComplaintForTransition {
+			status: Some(ComplaintStatus::Escalated),
+			escalation_reason: Some(reason),
+			..Default::default()
+		};
+		base::update_if_unchanged::<Self, _>(
+			ctx,
+			mm,
+			id,
+			complaint_ft,
+			complaint.mtime,
+		)
+		.await
+	}
+
+	/// -> Resolved, by the complainant (e.g., accepting the insurer response),
+	/// or by a moderator once escalated.
+	pub async fn resolve(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		resolution: String,
+	) -> Result<()> {
+		check_text("resolution", &resolution)?;
+		let complaint = Self::get(ctx, mm, id).await?;
+		match check_complainant(ctx, &complaint) {
+			Ok(()) => check_status(
+				&complaint,
+				&[
+					ComplaintStatus::InsurerResponded,
+					ComplaintStatus::Escalated,
+				],
+			)?,
+			Err(_) => {
+				UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await?;
+				check_status(&complaint, &[ComplaintStatus::Escalated])?;
+			}
+		}
+
+		let complaint_ft = ComplaintForTransition {
+			status: Some(ComplaintStatus::Resolved),
+			resolution: Some(resolution),
+			..Default::default()
+		};
+		base::update_if_unchanged::<Self, _>(
+			ctx,
+			mm,
+			id,
+			complaint_ft,
+			complaint.mtime,
+		)
+		.await
+	}
+
+	async fn count(
+		mm: &ModelManager,
+		company_id: i64,
+		product_id: Option<i64>,
+		months: Option<i32>,
+	) -> Result<CompanyComplaintCount> {
+		let sqlx_query = sqlx::query_as::<_, CompanyComplaintCount>(SQL_COUNT)
+			.bind(company_id)
+			.bind(product_id)
+			.bind(months);
+		let count = mm.dbx().fetch_one(sqlx_query).await?;
+
+		Ok(count)
+	}
+
+	async fn rate_count(
+		mm: &ModelManager,
+		company_id: i64,
+		product_id: Option<i64>,
+		months: Option<i32>,
+	) -> Result<ComplaintRateCount> {
+		let sqlx_query = sqlx::query_as::<_, ComplaintRateCount>(SQL_RATE_COUNT)
+			.bind(company_id)
+			.bind(product_id)
+			.bind(months);
+		let count = mm.dbx().fetch_one(sqlx_query).await?;
+
+		Ok(count)
+	}
+}
+
+fn check_text(field: &'static str, text: &str) -> Result<()> {
+	if text.trim().is_empty() {
+		Err(Error::ComplaintTextEmpty { field })
+	} else {
+		Ok(())
+	}
+}
+
+/// Only the `https:` urls (e.g., no `javascript:` url rendered as a link).
+fn check_attachments(attachments: &ComplaintAttachments) -> Result<()> {
+	match attachments
+		.0
+		.iter()
+		.find(|attachment| !attachment.url.starts_with("https://"))
+	{
+		Some(attachment) => Err(Error::ComplaintAttachmentUrlInvalid {
+			url: attachment.url.clone(),
+		}),
+		None => Ok(()),
+	}
+}
+
+/// Note: The root ctx (system) is always allowed.
+fn check_complainant(ctx: &Ctx, complaint: &Complaint) -> Result<()> {
+	if ctx.user_id() == 0 || complaint.cid == ctx.user_id() {
+		Ok(())
+	} else {
+		Err(Error::EntityAccessDenied {
+			entity: ComplaintBmc::TABLE,
+			id: complaint.id,
+		})
+	}
+}
+
+fn check_status(complaint: &Complaint, allowed: &[ComplaintStatus]) -> Result<()> {
+	if allowed.contains(&complaint.status) {
+		Ok(())
+	} else {
+		Err(Error::ComplaintStatusInvalid {
+			id: complaint.id,
+			status: complaint.status,
+		})
+	}
+}
+
+// endregion: --- ComplaintBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::company_member::CompanyMemberForCreate;
+	use crate::model::entity_change::EntityChangeBmc;
+	use crate::model::Error as ModelError;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_lifecycle_ok_audited() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_lifecycle_ok_audited company 01",
+		)
+		.await?;
+		let fx_user_ids = _dev_utils::seed_users(
+			&root_ctx,
+			&mm,
+			&[
+				"test_lifecycle_ok_audited-complainant",
+				"test_lifecycle_ok_audited-staff",
+			],
+		)
+		.await?;
+		let fx_member_id = CompanyMemberBmc::create(
+			&root_ctx,
+			&mm,
+			CompanyMemberForCreate {
+				company_id: fx_company_id,
+				user_id: fx_user_ids[1],
+			},
+		)
+		.await?;
+		let complainant_ctx = Ctx::new(fx_user_ids[0])?;
+		let staff_ctx = Ctx::new(fx_user_ids[1])?;
+		let id = ComplaintBmc::create(
+			&complainant_ctx,
+			&mm,
+			ComplaintForCreate {
+				company_id: fx_company_id,
+				product_id: None,
+				claim_id: None,
+				policy_number: Some("POL-0001".to_string()),
+				category: ComplaintCategory::ClaimDenial,
+				narrative: "Claim denied without reason".to_string(),
+				attachments: Some(ComplaintAttachments(vec![ComplaintAttachment {
+					name: "denial-letter.pdf".to_string(),
+					url: "https://example.com/denial-letter.pdf".to_string(),
+				}])),
+			},
+		)
+		.await?;
+
+		// -- Exec
+		let res_attachment = ComplaintBmc::create(
+			&complainant_ctx,
+			&mm,
+			ComplaintForCreate {
+				company_id: fx_company_id,
+				product_id: None,
+				claim_id: None,
+				policy_number: None,
+				category: ComplaintCategory::Other,
+				narrative: "Link".to_string(),
+				attachments: Some(ComplaintAttachments(vec![ComplaintAttachment {
+					name: "link".to_string(),
+					url: "javascript:alert(1)".to_string(),
+				}])),
+			},
+		)
+		.await;
+		let res_escalate_staff =
+			ComplaintBmc::escalate(&staff_ctx, &mm, id, "No".to_string()).await;
+		let res_respond_complainant =
+			ComplaintBmc::respond(&complainant_ctx, &mm, id, "Myself".to_string())
+				.await;
+		ComplaintBmc::respond(&staff_ctx, &mm, id, "Under review".to_string())
+			.await?;
+		let res_respond_twice =
+			ComplaintBmc::respond(&staff_ctx, &mm, id, "Again".to_string()).await;
+		ComplaintBmc::escalate(&complainant_ctx, &mm, id, "No news".to_string())
+			.await?;
+		ComplaintBmc::resolve(&root_ctx, &mm, id, "Claim paid".to_string()).await?;
+
+		// -- Check
+		assert!(
+			matches!(
+				res_attachment,
+				Err(ModelError::ComplaintAttachmentUrlInvalid { .. })
+			),
+			"ComplaintAttachmentUrlInvalid not matching"
+		);
+		assert!(
+			matches!(
+				res_escalate_staff,
+				Err(ModelError::EntityAccessDenied {
+					entity: "complaint",
+					..
+				})
+			),
+			"staff should not escalate"
+		);
+		assert!(
+			matches!(
+				res_respond_complainant,
+				Err(ModelError::EntityAccessDenied {
+					entity: "company",
+					..
+				})
+			),
+			"complainant should not respond"
+		);
+		assert!(
+			matches!(
+				res_respond_twice,
+				Err(ModelError::ComplaintStatusInvalid {
+					status: ComplaintStatus::InsurerResponded,
+					..
+				})
+			),
+			"ComplaintStatusInvalid not matching"
+		);
+		let complaint = ComplaintBmc::get(&complainant_ctx, &mm, id).await?;
+		assert_eq!(complaint.status, ComplaintStatus::Resolved);
+		assert_eq!(complaint.insurer_response, "Under review");
+		assert_eq!(complaint.attachments.0.len(), 1);
+		let statuses: Vec<String> =
+			EntityChangeBmc::list_by_entity(&root_ctx, &mm, "complaint", id)
+				.await?
+				.into_iter()
+				.filter_map(|change| {
+					change.after?.get("status")?.as_str().map(String::from)
+				})
+				.collect();
+		assert_eq!(
+			statuses,
+			&["Open", "InsurerResponded", "Escalated", "Resolved"]
+		);
+		let count =
+			ComplaintBmc::count_by_company(&root_ctx, &mm, fx_company_id, None)
+				.await?;
+		assert_eq!((count.count_complaint, count.count_resolved), (1, 1));
+
+		// -- Clean
+		// Note: Complaints are records (no `ComplaintBmc::delete`).
+		base::delete::<ComplaintBmc>(&root_ctx, &mm, id).await?;
+		CompanyMemberBmc::delete(&root_ctx, &mm, fx_member_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+		_dev_utils::clean_users(&root_ctx, &mm, "test_lifecycle_ok_audited").await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_respond_err_concurrent() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_respond_err_concurrent company 01",
+		)
+		.await?;
+		let id = ComplaintBmc::create(
+			&ctx,
+			&mm,
+			ComplaintForCreate {
+				company_id: fx_company_id,
+				product_id: None,
+				claim_id: None,
+				policy_number: None,
+				category: ComplaintCategory::Service,
+				narrative: "No answer".to_string(),
+				attachments: None,
+			},
+		)
+		.await?;
+
+		let rate_count_open =
+			ComplaintBmc::rate_count_by_company(&ctx, &mm, fx_company_id, None)
+				.await?;
+
+		// -- Exec
+		let (res_01, res_02) = tokio::join!(
+			ComplaintBmc::respond(&ctx, &mm, id, "Response 01".to_string()),
+			ComplaintBmc::respond(&ctx, &mm, id, "Response 02".to_string()),
+		);
+
+		// -- Check
+		// Only one of the two responses applies.
+		let res_err = match (res_01, res_02) {
+			(Ok(()), Err(ex)) | (Err(ex), Ok(())) => ex,
+			res => return Err(format!("Should have one error: {res:?}").into()),
+		};
+		assert!(
+			matches!(
+				res_err,
+				ModelError::ConcurrentModification { .. }
+					| ModelError::ComplaintStatusInvalid { .. }
+			),
+			"ConcurrentModification not matching"
+		);
+		let changes =
+			EntityChangeBmc::list_by_entity(&ctx, &mm, "complaint", id).await?;
+		assert_eq!(changes.len(), 2, "create and one response");
+		let rate_count =
+			ComplaintBmc::rate_count_by_company(&ctx, &mm, fx_company_id, None)
+				.await?;
+		assert_eq!(rate_count_open.count_complaint, 0, "not triaged");
+		assert_eq!(rate_count.count_complaint, 1);
+
+		// -- Clean
+		base::delete::<ComplaintBmc>(&ctx, &mm, id).await?;
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_count_by_company_ok_deleted_product() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_count_by_company_ok_deleted_product company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&["test_count_by_company_ok_deleted_product product 01"],
+		)
+		.await?[0];
+		let mut fx_complaint_ids = Vec::new();
+		for product_id in [Some(fx_product_id), None] {
+			let id = ComplaintBmc::create(
+				&ctx,
+				&mm,
+				ComplaintForCreate {
+					company_id: fx_company_id,
+					product_id,
+					claim_id: None,
+					policy_number: None,
+					category: ComplaintCategory::Service,
+					narrative: "No answer".to_string(),
+					attachments: None,
+				},
+			)
+			.await?;
+			fx_complaint_ids.push(id);
+		}
+
+		// -- Exec
+		let count =
+			ComplaintBmc::count_by_company(&ctx, &mm, fx_company_id, None).await?;
+		ProductBmc::delete(&ctx, &mm, fx_product_id).await?;
+		let count_deleted =
+			ComplaintBmc::count_by_company(&ctx, &mm, fx_company_id, None).await?;
+
+		// -- Check
+		assert_eq!(count.count_complaint, 2);
+		// Only the complaint without product is left.
+		assert_eq!(count_deleted.count_complaint, 1);
+
+		// -- Clean
+		for id in fx_complaint_ids {
+			base::delete::<ComplaintBmc>(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -0,0 +1,544 @@
+use crate::ctx::Ctx;
+use crate::generate_common_bmc_fns;
+use crate::model::base::{self, CommonIden, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::product::ProductBmc;
+use crate::model::ListCursorOptions;
+use crate::model::ModelManager;
+use crate::model::Result;
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, IntoSeaError, ListOptions, OpValInt64, OpValValue, OpValsInt64,
+	OpValsValue, SeaResult,
+};
+use sea_query::{
+	Alias, ColumnRef, ConditionExpression, Expr, Iden, Nullable, Query, SimpleExpr,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- CoverageItem Types
+
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	PartialEq,
+	Eq,
+	Hash,
+	sqlx::Type,
+	derive_more::Display,
+	Deserialize,
+	Serialize,
+)]
+#[sqlx(type_name = "coverage_benefit_typ")]
+pub enum CoverageBenefitTyp {
+	Hospitalization,
+	Outpatient,
+	Surgery,
+	CriticalIllness,
+	Maternity,
+	Dental,
+	Death,
+	Disability,
+	Other,
+}
+impl CoverageBenefitTyp {
+	pub const ALL: [CoverageBenefitTyp; 9] = [
+		CoverageBenefitTyp::Hospitalization,
+		CoverageBenefitTyp::Outpatient,
+		CoverageBenefitTyp::Surgery,
+		CoverageBenefitTyp::CriticalIllness,
+		CoverageBenefitTyp::Maternity,
+		CoverageBenefitTyp::Dental,
+		CoverageBenefitTyp::Death,
+		CoverageBenefitTyp::Disability,
+		CoverageBenefitTyp::Other,
+	];
+}
+impl From<CoverageBenefitTyp> for sea_query::Value {
+	fn from(val: CoverageBenefitTyp) -> Self {
+		val.to_string().into()
+	}
+}
+impl Nullable for CoverageBenefitTyp {
+	fn null() -> sea_query::Value {
+		sea_query::Value::String(None)
+	}
+}
+
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	PartialEq,
+	Eq,
+	sqlx::Type,
+	derive_more::Display,
+	Deserialize,
+	Serialize,
+)]
+#[sqlx(type_name = "coverage_limit_period")]
+pub enum CoverageLimitPeriod {
+	Day,
+	Month,
+	Year,
+	Lifetime,
+}
+impl From<CoverageLimitPeriod> for sea_query::Value {
+	fn from(val: CoverageLimitPeriod) -> Self {
+		val.to_string().into()
+	}
+}
+impl Nullable for CoverageLimitPeriod {
+	fn null() -> sea_query::Value {
+		sea_query::Value::String(None)
+	}
+}
+
+/// A benefit of the coverage of a product (e.g., Hospitalization "Room & Board").
+///
+/// Notes:
+///   - The amounts are in the product currency (IDR), None meaning no limit.
+///   - The `period_limit_amount` is the limit per `limit_period` (e.g., per Year).
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct CoverageItem {
+	pub id: i64,
+	pub product_id: i64,
+
+	pub benefit_typ: CoverageBenefitTyp,
+	pub name: String,
+
+	pub limit_amount: Option<i64>,
+	pub period_limit_amount: Option<i64>,
+	pub limit_period: Option<CoverageLimitPeriod>,
+	pub waiting_days: i32,
+	pub exclusions: String,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize)]
+pub struct CoverageItemForCreate {
+	pub product_id: i64,
+
+	#[field(cast_as = "coverage_benefit_typ")]
+	pub benefit_typ: CoverageBenefitTyp,
+	pub name: String,
+
+	pub limit_amount: Option<i64>,
+	pub period_limit_amount: Option<i64>,
+	#[field(cast_as = "coverage_limit_period")]
+	pub limit_period: Option<CoverageLimitPeriod>,
+	pub waiting_days: Option<i32>,
+	pub exclusions: Option<String>,
+}
+
+#[derive(Fields, Default, Deserialize)]
+pub struct CoverageItemForUpdate {
+	#[field(cast_as = "coverage_benefit_typ")]
+	pub benefit_typ: Option<CoverageBenefitTyp>,
+	pub name: Option<String>,
+
+	pub limit_amount: Option<i64>,
+	pub period_limit_amount: Option<i64>,
+	#[field(cast_as = "coverage_limit_period")]
+	pub limit_period: Option<CoverageLimitPeriod>,
+	pub waiting_days: Option<i32>,
+	pub exclusions: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct CoverageItemFilter {
+	pub id: Option<OpValsInt64>,
+	pub product_id: Option<OpValsInt64>,
+
+	pub limit_amount: Option<OpValsInt64>,
+	pub period_limit_amount: Option<OpValsInt64>,
+	pub waiting_days: Option<OpValsInt64>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+#[derive(Iden)]
+enum CoverageItemIden {
+	ProductId,
+	BenefitTyp,
+	LimitAmount,
+	PeriodLimitAmount,
+	LimitPeriod,
+	WaitingDays,
+}
+
+// endregion: --- CoverageItem Types
+
+// region:    --- CoverageItemBmc
+
+pub struct CoverageItemBmc;
+
+impl DbBmc for CoverageItemBmc {
+	const TABLE: &'static str = "coverage_item";
+}
+
+generate_common_bmc_fns!(
+	Bmc: CoverageItemBmc,
+	Entity: CoverageItem,
+	ForCreate: CoverageItemForCreate,
+	ForUpdate: CoverageItemForUpdate,
+	Filter: CoverageItemFilter,
+);
+
+impl CoverageItemBmc {
+	/// Only the users who can write the product (see `ProductBmc::check_write`)
+	/// can create a coverage item of the product.
+	pub async fn check_create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		coverage_item_c: &CoverageItemForCreate,
+	) -> Result<()> {
+		ProductBmc::check_write(ctx, mm, coverage_item_c.product_id).await
+	}
+
+	/// Only the users who can write the product (see `ProductBmc::check_write`)
+	/// can update or delete its coverage items.
+	pub async fn check_write(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		let coverage_item = Self::get(ctx, mm, id).await?;
+		ProductBmc::check_write(ctx, mm, coverage_item.product_id).await
+	}
+
+	/// Returns all the coverage items of the given products (by product id, then id),
+	/// not limited to a list page.
+	pub async fn list_by_products(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_ids: &[i64],
+	) -> Result<Vec<CoverageItem>> {
+		let filter = || CoverageItemFilter {
+			product_id: Some(OpValInt64::In(product_ids.to_vec()).into()),
+			..Default::default()
+		};
+
+		let mut items = Vec::new();
+		let mut cursor = None;
+		loop {
+			let cursor_options = ListCursorOptions {
+				cursor,
+				order_bys: Some(vec!["product_id", "id"].into()),
+				..Default::default()
+			};
+			let (page, meta) = Self::list_by_cursor(
+				ctx,
+				mm,
+				Some(vec![filter()]),
+				Some(cursor_options),
+			)
+			.await?;
+			items.extend(page);
+
+			match meta.next_cursor {
+				Some(next_cursor) => cursor = Some(next_cursor),
+				None => break,
+			}
+		}
+
+		Ok(items)
+	}
+}
+
+// endregion: --- CoverageItemBmc
+
+// region:    --- ProductFilter Conditions
+
+// The `ProductFilter.coverage_...` conditions: the products having (at least)
+// one coverage item matching the condition.
+
+pub fn product_coverage_benefit_typ_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let to_expr = |value: Value| -> SeaResult<SimpleExpr> {
+		let benefit_typ: CoverageBenefitTyp = serde_json::from_value(value.clone())
+			.map_err(|_| {
+				IntoSeaError::Custom(format!(
+					"'{value}' is not a coverage benefit type"
+				))
+			})?;
+		Ok(Expr::val(benefit_typ).as_enum(Alias::new("coverage_benefit_typ")))
+	};
+	product_coverage_cond(
+		CoverageItemIden::BenefitTyp,
+		op_value,
+		to_expr,
+		None,
+		false,
+	)
+}
+
+/// Note: A NULL `limit_amount` (no limit) is above any amount.
+pub fn product_coverage_limit_amount_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let to_expr = |value: Value| Ok(Expr::val(json_to_sea_value(value)?).into());
+	product_coverage_cond(
+		CoverageItemIden::LimitAmount,
+		op_value,
+		to_expr,
+		None,
+		true,
+	)
+}
+
+/// Note: Only the yearly `period_limit_amount` (e.g., "annual limit ≥ X"),
+///       or no period limit at all (above any amount).
+pub fn product_coverage_annual_limit_amount_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let to_expr = |value: Value| Ok(Expr::val(json_to_sea_value(value)?).into());
+	let mut item_cond = Expr::col(CoverageItemIden::LimitPeriod)
+		.eq(Expr::val(CoverageLimitPeriod::Year)
+			.as_enum(Alias::new("coverage_limit_period")));
+	if matches!(op_value, OpValValue::Gt(_) | OpValValue::Gte(_)) {
+		item_cond = item_cond.or(Expr::col(CoverageItemIden::LimitPeriod).is_null());
+	}
+	product_coverage_cond(
+		CoverageItemIden::PeriodLimitAmount,
+		op_value,
+		to_expr,
+		Some(item_cond),
+		true,
+	)
+}
+
+pub fn product_coverage_waiting_days_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let to_expr = |value: Value| Ok(Expr::val(json_to_sea_value(value)?).into());
+	product_coverage_cond(
+		CoverageItemIden::WaitingDays,
+		op_value,
+		to_expr,
+		None,
+		false,
+	)
+}
+
+/// `EXISTS (SELECT ... FROM coverage_item WHERE product_id = "product"."id"
+/// AND <col op value> [AND <item_cond>])`
+///
+/// Note: For a `null_unlimited` column (NULL meaning no limit), `$gt` and `$gte`
+///       also match NULL (i.e., `(<col op value> OR <col> IS NULL)`).
+fn product_coverage_cond(
+	col: CoverageItemIden,
+	op_value: OpValValue,
+	to_expr: impl Fn(Value) -> SeaResult<SimpleExpr>,
+	item_cond: Option<SimpleExpr>,
+	null_unlimited: bool,
+) -> SeaResult<ConditionExpression> {
+	let to_exprs = |values: Vec<Value>| -> SeaResult<Vec<SimpleExpr>> {
+		values.into_iter().map(&to_expr).collect()
+	};
+	let col = Expr::col(col);
+	let col_null = col.clone().is_null();
+	let col_cond = match op_value {
+		OpValValue::Eq(value) => col.eq(to_expr(value)?),
+		OpValValue::Not(value) => col.ne(to_expr(value)?),
+		OpValValue::In(values) => col.is_in(to_exprs(values)?),
+		OpValValue::NotIn(values) => col.is_not_in(to_exprs(values)?),
+		OpValValue::Lt(value) => col.lt(to_expr(value)?),
+		OpValValue::Lte(value) => col.lte(to_expr(value)?),
+		OpValValue::Gt(value) if null_unlimited => {
+			col.gt(to_expr(value)?).or(col_null)
+		}
+		OpValValue::Gt(value) => col.gt(to_expr(value)?),
+		OpValValue::Gte(value) if null_unlimited => {
+			col.gte(to_expr(value)?).or(col_null)
+		}
+		OpValValue::Gte(value) => col.gte(to_expr(value)?),
+		OpValValue::Null(_) => {
+			return Err(IntoSeaError::Custom(
+				"coverage filters do not support $null".to_string(),
+			))
+		}
+	};
+
+	let mut sub_query = Query::select();
+	sub_query
+		.expr(Expr::val(1))
+		.from(CoverageItemBmc::table_ref())
+		.and_where(
+			Expr::col(CoverageItemIden::ProductId)
+				.equals((Alias::new("product"), CommonIden::Id)),
+		)
+		.and_where(col_cond);
+	if let Some(item_cond) = item_cond {
+		sub_query.and_where(item_cond);
+	}
+
+	Ok(Expr::exists(sub_query).into())
+}
+
+fn json_to_sea_value(value: Value) -> SeaResult<sea_query::Value> {
+	match value {
+		Value::Number(num) => num
+			.as_i64()
+			.map(sea_query::Value::from)
+			.ok_or_else(|| IntoSeaError::Custom(format!("'{num}' is not an i64"))),
+		Value::String(string) => Ok(string.into()),
+		other => Err(IntoSeaError::Custom(format!(
+			"'{other}' is not a number or string"
+		))),
+	}
+}
+
+// endregion: --- ProductFilter Conditions
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::company::CompanyBmc;
+	use crate::model::product::{ProductBmc, ProductFilter};
+	use crate::model::Error as ModelError;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_products_by_coverage_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_list_products_by_coverage_ok company 01",
+		)
+		.await?;
+		let fx_product_ids = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&[
+				"test_list_products_by_coverage_ok product 01",
+				"test_list_products_by_coverage_ok product 02",
+			],
+		)
+		.await?;
+		let fx_items = [
+			(
+				fx_product_ids[0],
+				CoverageBenefitTyp::Hospitalization,
+				500_000_000,
+			),
+			(
+				fx_product_ids[1],
+				CoverageBenefitTyp::Hospitalization,
+				100_000_000,
+			),
+			(fx_product_ids[1], CoverageBenefitTyp::Dental, 5_000_000),
+		];
+		let mut fx_item_ids = Vec::new();
+		for (product_id, benefit_typ, annual_limit) in fx_items {
+			let id = CoverageItemBmc::create(
+				&ctx,
+				&mm,
+				CoverageItemForCreate {
+					product_id,
+					benefit_typ,
+					name: benefit_typ.to_string(),
+					limit_amount: None,
+					period_limit_amount: Some(annual_limit),
+					limit_period: Some(CoverageLimitPeriod::Year),
+					waiting_days: Some(30),
+					exclusions: None,
+				},
+			)
+			.await?;
+			fx_item_ids.push(id);
+		}
+
+		// -- Exec
+		let list = |filter: ProductFilter| {
+			let filter = ProductFilter {
+				company_id: Some(fx_company_id.into()),
+				..filter
+			};
+			ProductBmc::list(&ctx, &mm, Some(vec![filter]), None)
+		};
+		let annual_limit_products = list(ProductFilter {
+			coverage_annual_limit_amount: Some(
+				OpValValue::Gte(200_000_000.into()).into(),
+			),
+			..Default::default()
+		})
+		.await?;
+		let dental_products = list(ProductFilter {
+			coverage_benefit_typ: Some(OpValValue::Eq("Dental".into()).into()),
+			..Default::default()
+		})
+		.await?;
+		// No limit amount (None) is above any amount.
+		let limit_products = list(ProductFilter {
+			coverage_limit_amount: Some(OpValValue::Gte(10_000_000.into()).into()),
+			..Default::default()
+		})
+		.await?;
+		let res_benefit_typ = list(ProductFilter {
+			coverage_benefit_typ: Some(OpValValue::Eq("Dentl".into()).into()),
+			..Default::default()
+		})
+		.await;
+		let items =
+			CoverageItemBmc::list_by_products(&ctx, &mm, &fx_product_ids).await?;
+
+		// -- Check
+		let ids: Vec<i64> = annual_limit_products.iter().map(|p| p.id).collect();
+		assert_eq!(ids, [fx_product_ids[0]]);
+		let ids: Vec<i64> = dental_products.iter().map(|p| p.id).collect();
+		assert_eq!(ids, [fx_product_ids[1]]);
+		let ids: Vec<i64> = limit_products.iter().map(|p| p.id).collect();
+		assert_eq!(ids, fx_product_ids);
+		assert!(
+			matches!(res_benefit_typ, Err(ModelError::ModqlIntoSea(_))),
+			"ModqlIntoSea not matching"
+		);
+		assert_eq!(items.len(), 3);
+		assert!(items.iter().all(|item| item.waiting_days == 30));
+
+		// -- Clean
+		for id in fx_item_ids {
+			CoverageItemBmc::delete(&ctx, &mm, id).await?;
+		}
+		for product_id in fx_product_ids {
+			ProductBmc::delete(&ctx, &mm, product_id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
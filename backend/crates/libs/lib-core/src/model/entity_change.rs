@@ -0,0 +1,348 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, prep_fields_for_create, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::ModelManager;
+use crate::model::Result;
+use lib_utils::time::Rfc3339;
+use modql::field::{Fields, HasSeaFields};
+use modql::filter::{
+	FilterNodes, ListOptions, OpValsInt64, OpValsString, OpValsValue,
+};
+use sea_query::{PostgresQueryBuilder, Query};
+use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+use std::collections::HashMap;
+
+// region:    --- EntityChange Types
+
+#[derive(Clone, Debug, sqlx::Type, derive_more::Display, Deserialize, Serialize)]
+#[sqlx(type_name = "entity_change_op")]
+pub enum EntityChangeOp {
+	Create,
+	Update,
+	Delete,
+}
+impl From<EntityChangeOp> for sea_query::Value {
+	fn from(val: EntityChangeOp) -> Self {
+		val.to_string().into()
+	}
+}
+
+/// A create/update/delete of the entity `entity_id` of the table `entity`.
+/// The `cid` is the user who performed the change.
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct EntityChange {
+	pub id: i64,
+	pub entity: String,
+	pub entity_id: i64,
+	pub op: EntityChangeOp,
+
+	/// The changed fields, before the change (None for a create).
+	pub before: Option<Value>,
+	/// The changed fields, after the change (None for a hard delete).
+	pub after: Option<Value>,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields)]
+struct EntityChangeForInsert {
+	entity: String,
+	entity_id: i64,
+	#[field(cast_as = "entity_change_op")]
+	op: EntityChangeOp,
+	before: Option<Value>,
+	after: Option<Value>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct EntityChangeFilter {
+	pub id: Option<OpValsInt64>,
+
+	pub entity: Option<OpValsString>,
+	pub entity_id: Option<OpValsInt64>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+}
+
+// endregion: --- EntityChange Types
+
+// region:    --- EntityChangeBmc
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Note: The log is append only, and only written by the base crud fns.
+pub struct EntityChangeBmc;
+
+impl DbBmc for EntityChangeBmc {
+	const TABLE: &'static str = "entity_change";
+}
+
+impl EntityChangeBmc {
+	/// Returns the history of an entity (oldest change first).
+	pub async fn list_by_entity(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		entity: &str,
+		entity_id: i64,
+	) -> Result<Vec<EntityChange>> {
+		let filter = EntityChangeFilter {
+			entity: Some(entity.into()),
+			entity_id: Some(entity_id.into()),
+			..Default::default()
+		};
+		let list_options = ListOptions {
+			order_bys: Some("id".into()),
+			..Default::default()
+		};
+
+		base::list::<Self, _, _>(ctx, mm, Some(vec![filter]), Some(list_options))
+			.await
+	}
+
+	/// Returns the JSON object of the `MC` row `id` (None if not found),
+	/// without the `MC::audit_excluded_fields()`.
+	pub(in crate::model) async fn snapshot<MC>(
+		mm: &ModelManager,
+		id: i64,
+	) -> Result<Option<Value>>
+	where
+		MC: DbBmc,
+	{
+		// Note: MC::TABLE is a static name, never user input.
+		let sql = format!(
+			r#"SELECT to_jsonb(t) - $2::text[] FROM "{}" t WHERE t.id = $1"#,
+			MC::TABLE
+		);
+		let sqlx_query = sqlx::query_as::<_, (Value,)>(&sql)
+			.bind(id)
+			.bind(MC::audit_excluded_fields());
+		let snapshot = mm.dbx().fetch_optional(sqlx_query).await?;
+
+		Ok(snapshot.map(|(value,)| value))
+	}
+
+	/// Returns the JSON objects of the `MC` rows `ids`, by id (the not found ones are absent),
+	/// without the `MC::audit_excluded_fields()`.
+	pub(in crate::model) async fn snapshots<MC>(
+		mm: &ModelManager,
+		ids: &[i64],
+	) -> Result<HashMap<i64, Value>>
+	where
+		MC: DbBmc,
+	{
+		// Note: MC::TABLE is a static name, never user input.
+		let sql = format!(
+			r#"SELECT t.id, to_jsonb(t) - $2::text[] FROM "{}" t WHERE t.id = ANY($1)"#,
+			MC::TABLE
+		);
+		let sqlx_query = sqlx::query_as::<_, (i64, Value)>(&sql)
+			.bind(ids)
+			.bind(MC::audit_excluded_fields());
+		let snapshots = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(snapshots.into_iter().collect())
+	}
+
+	/// Records the change of the `MC` row `id` from its before/after snapshots.
+	/// Only the changed fields are recorded, and the `MC::audit_redacted_fields()` are redacted.
+	///
+	/// Note: Must be called within the transaction of the change.
+	pub(in crate::model) async fn record<MC>(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		op: EntityChangeOp,
+		before: Option<Value>,
+		after: Option<Value>,
+	) -> Result<()>
+	where
+		MC: DbBmc,
+	{
+		Self::record_many::<MC>(ctx, mm, op, vec![(id, before, after)]).await
+	}
+
+	/// Same as `record`, for the changes `(id, before, after)` of a bulk operation
+	/// (one multi-row insert).
+	pub(in crate::model) async fn record_many<MC>(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		op: EntityChangeOp,
+		changes: Vec<(i64, Option<Value>, Option<Value>)>,
+	) -> Result<()>
+	where
+		MC: DbBmc,
+	{
+		if changes.is_empty() {
+			return Ok(());
+		}
+		let redacted = MC::audit_redacted_fields();
+
+		// -- Build query
+		// Note: Not `base::create`, which would record this change as well.
+		let mut query = Query::insert();
+		query.into_table(Self::table_ref());
+		for (id, before, after) in changes {
+			let (before, after) = diff_snapshots(before, after);
+			let change_fi = EntityChangeForInsert {
+				entity: MC::TABLE.to_string(),
+				entity_id: id,
+				op: op.clone(),
+				before: before.map(|v| redact(v, redacted)),
+				after: after.map(|v| redact(v, redacted)),
+			};
+
+			// -- Prep the data
+			// Note: All the fields (even the None ones), so that all rows have the same columns.
+			let mut fields = change_fi.all_sea_fields();
+			prep_fields_for_create::<Self>(&mut fields, ctx.user_id());
+
+			let (columns, sea_values) = fields.for_sea_insert();
+			query.columns(columns).values(sea_values)?;
+		}
+
+		// -- Exec query
+		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+		let sqlx_query = sqlx::query_with(&sql, values);
+		mm.dbx().execute(sqlx_query).await?;
+
+		Ok(())
+	}
+}
+
+/// When both snapshots are present (update), keeps only the fields that differ.
+fn diff_snapshots(
+	before: Option<Value>,
+	after: Option<Value>,
+) -> (Option<Value>, Option<Value>) {
+	match (before, after) {
+		(Some(Value::Object(mut before)), Some(Value::Object(after))) => {
+			let mut before_diff = Map::new();
+			let mut after_diff = Map::new();
+			for (name, after_val) in after {
+				let before_val = before.remove(&name).unwrap_or(Value::Null);
+				if before_val != after_val {
+					before_diff.insert(name.clone(), before_val);
+					after_diff.insert(name, after_val);
+				}
+			}
+			(
+				Some(Value::Object(before_diff)),
+				Some(Value::Object(after_diff)),
+			)
+		}
+		other => other,
+	}
+}
+
+fn redact(mut value: Value, redacted: &[&str]) -> Value {
+	if let Value::Object(obj) = &mut value {
+		for name in redacted {
+			if let Some(val) = obj.get_mut(*name) {
+				*val = Value::String(REDACTED.to_string());
+			}
+		}
+	}
+	value
+}
+
+// endregion: --- EntityChangeBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::company::{CompanyBmc, CompanyForUpdate};
+	use serde_json::json;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_by_entity_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_name = "test_list_by_entity_ok company 01";
+		let fx_name_new = "test_list_by_entity_ok company 01 new";
+		let fx_user_id =
+			_dev_utils::seed_user(&root_ctx, &mm, "test_list_by_entity_ok-user-01")
+				.await?;
+		let ctx = Ctx::new(fx_user_id)?;
+		let company_id = _dev_utils::seed_company(&ctx, &mm, fx_name).await?;
+		CompanyBmc::update(
+			&ctx,
+			&mm,
+			company_id,
+			CompanyForUpdate {
+				name: Some(fx_name_new.to_string()),
+				..Default::default()
+			},
+		)
+		.await?;
+		CompanyBmc::delete(&ctx, &mm, company_id).await?;
+
+		// -- Exec
+		let changes =
+			EntityChangeBmc::list_by_entity(&ctx, &mm, "company", company_id)
+				.await?;
+
+		// -- Check
+		let ops: Vec<String> = changes.iter().map(|c| c.op.to_string()).collect();
+		assert_eq!(ops, ["Create", "Update", "Delete"]);
+		assert!(changes.iter().all(|c| c.cid == fx_user_id));
+		let [create, update, delete] = &changes[..] else {
+			return Err("Should have 3 changes".into());
+		};
+		assert!(create.before.is_none());
+		assert_eq!(
+			create.after.as_ref().map(|v| &v["name"]),
+			Some(&json!(fx_name))
+		);
+		assert_eq!(
+			update.before.as_ref().map(|v| &v["name"]),
+			Some(&json!(fx_name))
+		);
+		assert_eq!(
+			update.after.as_ref().map(|v| &v["name"]),
+			Some(&json!(fx_name_new))
+		);
+		assert!(
+			update
+				.after
+				.as_ref()
+				.and_then(|v| v.get("description"))
+				.is_none(),
+			"unchanged fields should not be recorded"
+		);
+		// Note: The company is soft-deleted.
+		assert_eq!(
+			delete.after.as_ref().map(|v| &v["deleted"]),
+			Some(&json!(true))
+		);
+
+		// -- Clean
+		_dev_utils::clean_users(&root_ctx, &mm, "test_list_by_entity_ok").await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -1,3 +1,4 @@
+use crate::model::complaint::ComplaintStatus;
 use crate::model::store::dbx;
 use derive_more::From;
 use lib_auth::pwd;
@@ -19,11 +20,111 @@ pub enum Error {
 		max: i64,
 		actual: i64,
 	},
+	/// The list limit is negative.
+	ListLimitInvalid {
+		actual: i64,
+	},
+	/// The cursor is malformed, or was not issued for the requested order.
+	ListCursorInvalid,
+	/// The bulk update/delete filter has no condition (would match all the rows).
+	BulkFilterEmpty,
+	EntityAccessDenied {
+		entity: &'static str,
+		id: i64,
+	},
+	/// The entity was modified since the expected `mtime` (optimistic concurrency).
+	ConcurrentModification {
+		entity: &'static str,
+		id: i64,
+	},
+
+	// -- User
+	/// The ctx user type is not allowed (e.g., not a moderator).
+	UserNotPrivileged {
+		user_id: i64,
+	},
+
+	// -- Product
+	/// The product reviews are hidden (see `Product.review_visibility`).
+	ProductReviewsHidden {
+		product_id: i64,
+	},
+	/// The product claim data is hidden (see `Product.claim_historical_data_visibility`).
+	ProductClaimDataHidden {
+		product_id: i64,
+	},
+
+	// -- Review
+	ReviewRatingOutOfRange {
+		min: i16,
+		max: i16,
+		actual: i16,
+	},
+	ReviewModerationReasonEmpty,
+
+	// -- Complaint
+	ComplaintTextEmpty {
+		field: &'static str,
+	},
+	/// The linked product or claim is not of the complaint company.
+	ComplaintLinkInvalid {
+		entity: &'static str,
+		id: i64,
+	},
+	/// The attachment url is not an `https:` url.
+	ComplaintAttachmentUrlInvalid {
+		url: String,
+	},
+	/// The lifecycle transition is not allowed from the current status.
+	ComplaintStatusInvalid {
+		id: i64,
+		status: ComplaintStatus,
+	},
+
+	// -- Product Comparison
+	ProductComparisonCountOutOfRange {
+		min: usize,
+		max: usize,
+		actual: usize,
+	},
+
+	// -- Trust Score
+	TrustScoreHistoryDaysOutOfRange {
+		min: i64,
+		max: i64,
+		actual: i64,
+	},
+
+	// -- Quote
+	RatingTableInvalid {
+		reason: String,
+	},
+	QuoteUserProfileNotFound {
+		user_id: i64,
+	},
+	QuoteRatingTableNotFound {
+		product_id: i64,
+		version: Option<i32>,
+	},
+	/// The age is not in any of the age bands of the product rating table.
+	QuoteAgeNotRated {
+		product_id: i64,
+		age: i32,
+	},
 
 	// -- DB
 	UserAlreadyExists {
 		username: String,
 	},
+	ReviewAlreadyExists {
+		product_id: i64,
+	},
+	ReviewFlagAlreadyExists {
+		review_id: i64,
+	},
+	ReviewResponseAlreadyExists {
+		review_id: i64,
+	},
 	UniqueViolation {
 		table: String,
 		constraint: String,
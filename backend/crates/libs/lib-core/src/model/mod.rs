@@ -24,9 +24,29 @@ mod base;
 mod error;
 mod store;
 
+pub mod category;
+pub mod claim;
+pub mod company;
+pub mod company_member;
+pub mod complaint;
+pub mod coverage_item;
+pub mod entity_change;
 pub mod modql_utils;
+pub mod product;
+pub mod product_comparison;
+pub mod quote;
+pub mod rating_table;
+pub mod recommendation;
+pub mod review;
+pub mod review_flag;
+pub mod review_response;
+pub mod review_vote;
+pub mod trust_score;
+pub mod um_change_log;
 pub mod user;
+pub mod user_profile;
 
+pub use self::base::{BulkAffected, ListCursorMeta, ListCursorOptions, ListMeta};
 pub use self::error::{Error, Result};
 
 use crate::model::store::dbx::Dbx;
@@ -52,7 +72,14 @@ impl ModelManager {
 		Ok(ModelManager { dbx })
 	}
 
+	/// Returns a ModelManager with a transactional Dbx.
+	/// If this ModelManager is already transactional, it is returned as is (cloned)
+	/// so that nested `begin_txn`/`commit_txn` join the ongoing transaction.
 	pub fn new_with_txn(&self) -> Result<ModelManager> {
+		if self.dbx.with_txn() {
+			return Ok(self.clone());
+		}
+
 		let dbx = Dbx::new(self.dbx.db().clone(), true)?;
 		Ok(ModelManager { dbx })
 	}
@@ -0,0 +1,1111 @@
+use crate::ctx::Ctx;
+use crate::generate_common_bmc_fns;
+use crate::model::base::{self, CommonIden, DbBmc};
+use crate::model::category::product_category_subtree_cond;
+use crate::model::company_member::CompanyMemberBmc;
+use crate::model::coverage_item::{
+	product_coverage_annual_limit_amount_cond, product_coverage_benefit_typ_cond,
+	product_coverage_limit_amount_cond, product_coverage_waiting_days_cond,
+};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::{Fields, HasSeaFields};
+use modql::filter::{
+	FilterNodes, ListOptions, OpValsInt64, OpValsString, OpValsValue,
+};
+use sea_query::{Alias, Expr, Order, PostgresQueryBuilder, Query};
+use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- Product Types
+
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct Product {
+	pub id: i64,
+	pub company_id: i64,
+
+	pub name: String,
+	pub description: String,
+	pub banner: Option<String>,
+
+	// -- Visibility (see `ProductBmc::update_visibility`)
+	pub review_visibility: bool,
+	pub claim_historical_data_visibility: bool,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize)]
+pub struct ProductForCreate {
+	pub company_id: i64,
+	pub name: String,
+	pub description: Option<String>,
+	pub banner: Option<String>,
+}
+
+#[derive(Fields, Default, Deserialize)]
+pub struct ProductForUpdate {
+	pub name: Option<String>,
+	pub description: Option<String>,
+	pub banner: Option<String>,
+}
+
+#[derive(Fields, Default, Deserialize)]
+pub struct ProductVisibilityForUpdate {
+	pub review_visibility: Option<bool>,
+	pub claim_historical_data_visibility: Option<bool>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ProductFilter {
+	pub id: Option<OpValsInt64>,
+	pub company_id: Option<OpValsInt64>,
+
+	pub name: Option<OpValsString>,
+	pub description: Option<OpValsString>,
+
+	/// The products in the subtree of the category (see `CategoryBmc`).
+	#[modql(to_sea_condition_fn = "product_category_subtree_cond")]
+	pub category_id: Option<OpValsValue>,
+
+	// -- Coverage (the products with a coverage item matching, see `CoverageItemBmc`)
+	#[modql(to_sea_condition_fn = "product_coverage_benefit_typ_cond")]
+	pub coverage_benefit_typ: Option<OpValsValue>,
+	#[modql(to_sea_condition_fn = "product_coverage_limit_amount_cond")]
+	pub coverage_limit_amount: Option<OpValsValue>,
+	#[modql(to_sea_condition_fn = "product_coverage_annual_limit_amount_cond")]
+	pub coverage_annual_limit_amount: Option<OpValsValue>,
+	#[modql(to_sea_condition_fn = "product_coverage_waiting_days_cond")]
+	pub coverage_waiting_days: Option<OpValsValue>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+/// A product matching a search, with its rank and highlighted snippet
+/// (HTML escaped, with the matching words between `<b>` and `</b>`).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ProductSearchHit {
+	#[sqlx(flatten)]
+	#[serde(flatten)]
+	pub product: Product,
+	pub rank: f64,
+	pub snippet: String,
+}
+
+// endregion: --- Product Types
+
+// region:    --- ProductBmc
+
+pub struct ProductBmc;
+
+impl DbBmc for ProductBmc {
+	const TABLE: &'static str = "product";
+
+	fn has_soft_delete() -> bool {
+		true
+	}
+
+	fn audit_excluded_fields() -> &'static [&'static str] {
+		&["search"]
+	}
+}
+
+generate_common_bmc_fns!(
+	Bmc: ProductBmc,
+	Entity: Product,
+	ForCreate: ProductForCreate,
+	ForUpdate: ProductForUpdate,
+	Filter: ProductFilter,
+);
+
+const SEARCH_LIMIT_DEFAULT: i64 = 100;
+const SEARCH_LIMIT_MAX: i64 = 1000;
+/// The max number of search terms (the extra ones are ignored).
+const SEARCH_TERMS_MAX: usize = 10;
+/// The weight of the company rank in the product rank (e.g., a search on the insurer name).
+const SEARCH_COMPANY_RANK_WEIGHT: f64 = 0.5;
+
+impl ProductBmc {
+	/// Only an admin (or `Sys`) user, or a member of the product company,
+	/// can change the visibility of the product reviews and claim data.
+	pub async fn update_visibility(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		product_u: ProductVisibilityForUpdate,
+	) -> Result<()> {
+		Self::check_write(ctx, mm, id).await?;
+
+		base::update::<Self, _>(ctx, mm, id, product_u).await
+	}
+
+	/// Same as `update_visibility`, but fails with `Error::ConcurrentModification`
+	/// if the product was modified since `expected_mtime`.
+	pub async fn update_visibility_if_unchanged(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		product_u: ProductVisibilityForUpdate,
+		expected_mtime: OffsetDateTime,
+	) -> Result<()> {
+		Self::check_write(ctx, mm, id).await?;
+
+		base::update_if_unchanged::<Self, _>(ctx, mm, id, product_u, expected_mtime)
+			.await
+	}
+
+	/// Only an admin (or `Sys`) user, or a member of the company,
+	/// can create a product for the company.
+	pub async fn check_create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_c: &ProductForCreate,
+	) -> Result<()> {
+		CompanyMemberBmc::check_admin_or_member(ctx, mm, product_c.company_id).await
+	}
+
+	/// Only an admin (or `Sys`) user, or a member of the product company,
+	/// can update or delete the product.
+	pub async fn check_write(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		let product = Self::get(ctx, mm, id).await?;
+		CompanyMemberBmc::check_admin_or_member(ctx, mm, product.company_id).await
+	}
+
+	/// Full-text search of the products (and of their companies), best rank first.
+	///
+	/// Each word of the query matches its stem (e.g., "kesehatan" matches "sehat")
+	/// or, as a prefix, the words being typed (e.g., "kese"). The products matching
+	/// more words, or in their name, rank first (see the `search` columns).
+	pub async fn search(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		query: &str,
+		filter: Option<Vec<ProductFilter>>,
+		limit: Option<i64>,
+	) -> Result<Vec<ProductSearchHit>> {
+		let limit = limit.unwrap_or(SEARCH_LIMIT_DEFAULT);
+		if limit < 0 {
+			return Err(Error::ListLimitInvalid { actual: limit });
+		}
+		if limit > SEARCH_LIMIT_MAX {
+			return Err(Error::ListLimitOverMax {
+				max: SEARCH_LIMIT_MAX,
+				actual: limit,
+			});
+		}
+
+		// -- Build the tsquery texts
+		// Note: Only alphanumeric terms, so no tsquery syntax injection.
+		let mut terms: Vec<String> = Vec::new();
+		for term in query.split(|c: char| !c.is_alphanumeric()) {
+			let term = term.to_lowercase();
+			if !term.is_empty() && !terms.contains(&term) {
+				terms.push(term);
+			}
+		}
+		terms.truncate(SEARCH_TERMS_MAX);
+		if terms.is_empty() {
+			return Ok(Vec::new());
+		}
+		let stem_query = terms.join(" | ");
+		let prefix_query = terms
+			.iter()
+			.map(|term| format!("{term}:*"))
+			.collect::<Vec<_>>()
+			.join(" | ");
+		let tsquery = "(to_tsquery('search_id', $1) || to_tsquery('simple', $2))";
+		let tsquery_expr = |sql: String| {
+			Expr::cust_with_values(
+				sql.replace("$TSQUERY", tsquery),
+				[stem_query.clone(), prefix_query.clone()],
+			)
+		};
+
+		// -- Build query
+		let mut sea_query = Query::select();
+		sea_query
+			.from(Self::table_ref())
+			.columns(Product::sea_column_refs())
+			.expr_as(
+				tsquery_expr(format!(
+					r#"ts_rank("product"."search", $TSQUERY) + {SEARCH_COMPANY_RANK_WEIGHT} * COALESCE(
+						(SELECT ts_rank(c.search, $TSQUERY) FROM company c WHERE c.id = "product"."company_id"),
+						0
+					)"#
+				)),
+				Alias::new("rank"),
+			)
+			// Note: The text is HTML escaped, as the snippet is HTML (`<b>` highlights).
+			.expr_as(
+				tsquery_expr(
+					r#"ts_headline('search_id',
+						replace(replace(replace(
+							"product"."name" || ' ' || "product"."description",
+							'&', '&amp;'), '<', '&lt;'), '>', '&gt;'),
+						$TSQUERY, 'MaxFragments=2, MaxWords=20, MinWords=5')"#
+						.to_string(),
+				),
+				Alias::new("snippet"),
+			)
+			.cond_where(base::list_cond::<Self, _>(filter, false)?)
+			.and_where(tsquery_expr(
+				r#"("product"."search" @@ $TSQUERY OR EXISTS (
+					SELECT 1 FROM company c WHERE c.id = "product"."company_id" AND c.search @@ $TSQUERY
+				))"#
+					.to_string(),
+			))
+			.order_by(Alias::new("rank"), Order::Desc)
+			.order_by(CommonIden::Id, Order::Asc)
+			.limit(limit as u64);
+
+		// -- Exec query
+		let (sql, values) = sea_query.build_sqlx(PostgresQueryBuilder);
+		let sqlx_query = sqlx::query_as_with::<_, ProductSearchHit, _>(&sql, values);
+		let hits = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(hits)
+	}
+}
+
+// endregion: --- ProductBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::claim::{
+		ClaimBmc, ClaimFilter, ClaimForCreate, ClaimHistoryResolution,
+	};
+	use crate::model::company::CompanyBmc;
+	use crate::model::company_member::CompanyMemberForCreate;
+	use crate::model::entity_change::EntityChangeBmc;
+	use crate::model::review::ReviewBmc;
+	use crate::model::Error as ModelError;
+	use crate::model::ListCursorOptions;
+	use lib_utils::time::now_utc;
+	use modql::filter::OpValString;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_name = "test_create_ok product 01";
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_create_ok company 01").await?;
+
+		// -- Exec
+		let product_id = ProductBmc::create(
+			&ctx,
+			&mm,
+			ProductForCreate {
+				company_id: fx_company_id,
+				name: fx_name.to_string(),
+				description: None,
+				banner: None,
+			},
+		)
+		.await?;
+
+		// -- Check
+		let product = ProductBmc::get(&ctx, &mm, product_id).await?;
+		assert_eq!(product.name, fx_name);
+		assert_eq!(product.company_id, fx_company_id);
+		assert_eq!(product.description, "");
+
+		// -- Clean
+		ProductBmc::delete(&ctx, &mm, product_id).await?;
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_by_name_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_names = &[
+			"test_list_by_name_ok product 01",
+			"test_list_by_name_ok product 02",
+		];
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_list_by_name_ok company 01")
+				.await?;
+		let fx_ids =
+			_dev_utils::seed_products(&ctx, &mm, fx_company_id, fx_names).await?;
+
+		// -- Exec
+		let filter = ProductFilter {
+			name: Some(OpValString::Contains("test_list_by_name_ok".into()).into()),
+			..Default::default()
+		};
+		let products = ProductBmc::list(&ctx, &mm, Some(vec![filter]), None).await?;
+
+		// -- Check
+		let names: Vec<&str> = products.iter().map(|p| p.name.as_str()).collect();
+		assert_eq!(names, fx_names);
+
+		// -- Clean
+		for id in fx_ids {
+			ProductBmc::delete(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_with_meta_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_names = &[
+			"test_list_with_meta_ok product 01",
+			"test_list_with_meta_ok product 02",
+			"test_list_with_meta_ok product 03",
+		];
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_list_with_meta_ok company 01")
+				.await?;
+		let fx_ids =
+			_dev_utils::seed_products(&ctx, &mm, fx_company_id, fx_names).await?;
+		let filter = || ProductFilter {
+			company_id: Some(fx_company_id.into()),
+			..Default::default()
+		};
+		let list_options = |offset: i64| ListOptions {
+			limit: Some(2),
+			offset: Some(offset),
+			order_bys: Some("id".into()),
+		};
+
+		// -- Exec
+		let (first_page, first_meta) = ProductBmc::list_with_meta(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			Some(list_options(0)),
+			true,
+		)
+		.await?;
+		let (last_page, last_meta) = ProductBmc::list_with_meta(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			Some(list_options(2)),
+			false,
+		)
+		.await?;
+		let res_limit = ProductBmc::list_with_meta(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			Some(ListOptions {
+				limit: Some(-1),
+				..list_options(0)
+			}),
+			false,
+		)
+		.await;
+
+		// -- Check
+		let names: Vec<&str> = first_page.iter().map(|p| p.name.as_str()).collect();
+		assert_eq!(names, &fx_names[..2]);
+		assert!(first_meta.has_more);
+		assert_eq!(first_meta.total, Some(3));
+		assert_eq!(last_page.len(), 1);
+		assert!(!last_meta.has_more);
+		assert_eq!(last_meta.offset, 2);
+		assert_eq!(last_meta.total, None);
+		assert!(
+			matches!(res_limit, Err(ModelError::ListLimitInvalid { actual: -1 })),
+			"ListLimitInvalid not matching"
+		);
+
+		// -- Clean
+		for id in fx_ids {
+			ProductBmc::delete(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_list_by_cursor_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_names = &[
+			"test_list_by_cursor_ok product 01",
+			"test_list_by_cursor_ok product 02",
+			"test_list_by_cursor_ok product 03",
+		];
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_list_by_cursor_ok company 01")
+				.await?;
+		let fx_product_ids =
+			_dev_utils::seed_products(&ctx, &mm, fx_company_id, fx_names).await?;
+		let filter = || ProductFilter {
+			company_id: Some(fx_company_id.into()),
+			..Default::default()
+		};
+		let cursor_options = |cursor: Option<String>, order_bys: &str| {
+			Some(ListCursorOptions {
+				cursor,
+				limit: Some(2),
+				order_bys: Some(order_bys.into()),
+			})
+		};
+
+		// -- Exec
+		let (first_page, first_meta) = ProductBmc::list_by_cursor(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			cursor_options(None, "!name"),
+		)
+		.await?;
+		// The last entity of the first page changes (the cursor keeps its values).
+		ProductBmc::update(
+			&ctx,
+			&mm,
+			fx_product_ids[1],
+			ProductForUpdate {
+				name: Some("test_list_by_cursor_ok product 04".to_string()),
+				..Default::default()
+			},
+		)
+		.await?;
+		let (last_page, last_meta) = ProductBmc::list_by_cursor(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			cursor_options(first_meta.next_cursor.clone(), "!name"),
+		)
+		.await?;
+		let res = ProductBmc::list_by_cursor(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			cursor_options(first_meta.next_cursor.clone(), "name"),
+		)
+		.await;
+		let res_limit = ProductBmc::list_by_cursor(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			Some(ListCursorOptions {
+				limit: Some(-1),
+				..Default::default()
+			}),
+		)
+		.await;
+		let res_limit_max = ProductBmc::list_by_cursor(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			Some(ListCursorOptions {
+				limit: Some(5001),
+				..Default::default()
+			}),
+		)
+		.await;
+
+		// -- Check
+		let names = |page: &[Product]| -> Vec<String> {
+			page.iter().map(|p| p.name.clone()).collect()
+		};
+		assert_eq!(names(&first_page), [fx_names[2], fx_names[1]]);
+		assert!(first_meta.next_cursor.is_some());
+		assert_eq!(names(&last_page), [fx_names[0]]);
+		assert!(last_meta.next_cursor.is_none());
+		assert!(
+			matches!(res, Err(ModelError::ListCursorInvalid)),
+			"ListCursorInvalid not matching"
+		);
+		assert!(
+			matches!(res_limit, Err(ModelError::ListLimitInvalid { actual: -1 })),
+			"ListLimitInvalid not matching"
+		);
+		assert!(
+			matches!(
+				res_limit_max,
+				Err(ModelError::ListLimitOverMax { actual: 5001, .. })
+			),
+			"ListLimitOverMax not matching"
+		);
+
+		// -- Clean
+		for id in fx_product_ids {
+			ProductBmc::delete(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_many_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_create_many_ok company 01")
+				.await?;
+		let fx_description = "test_create_many_ok description 01";
+		let fx_products_c = vec![
+			ProductForCreate {
+				company_id: fx_company_id,
+				name: "test_create_many_ok product 01".to_string(),
+				description: Some(fx_description.to_string()),
+				banner: None,
+			},
+			ProductForCreate {
+				company_id: fx_company_id,
+				name: "test_create_many_ok product 02".to_string(),
+				description: None,
+				banner: None,
+			},
+		];
+
+		// -- Exec
+		let affected = ProductBmc::create_many(&ctx, &mm, fx_products_c).await?;
+
+		// -- Check
+		assert_eq!(affected.count, 2);
+		let products = ProductBmc::list(
+			&ctx,
+			&mm,
+			Some(vec![ProductFilter {
+				company_id: Some(fx_company_id.into()),
+				..Default::default()
+			}]),
+			None,
+		)
+		.await?;
+		let ids: Vec<i64> = products.iter().map(|p| p.id).collect();
+		assert_eq!(ids, affected.ids);
+		assert_eq!(products[0].description, fx_description);
+		assert_eq!(products[1].description, "", "should be the column default");
+		for &id in &affected.ids {
+			let changes =
+				EntityChangeBmc::list_by_entity(&ctx, &mm, "product", id).await?;
+			assert_eq!(changes.len(), 1);
+		}
+
+		// -- Clean
+		for id in affected.ids {
+			ProductBmc::delete(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_update_many_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_names = &[
+			"test_update_many_ok product 01",
+			"test_update_many_ok product 02",
+			"test_update_many_ok product 03",
+		];
+		let fx_description = "test_update_many_ok description";
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_update_many_ok company 01")
+				.await?;
+		let fx_ids =
+			_dev_utils::seed_products(&ctx, &mm, fx_company_id, fx_names).await?;
+
+		// -- Exec
+		let affected = ProductBmc::update_many(
+			&ctx,
+			&mm,
+			vec![ProductFilter {
+				company_id: Some(fx_company_id.into()),
+				name: Some(
+					OpValString::In(
+						fx_names[..2].iter().map(|n| n.to_string()).collect(),
+					)
+					.into(),
+				),
+				..Default::default()
+			}],
+			ProductForUpdate {
+				description: Some(fx_description.to_string()),
+				..Default::default()
+			},
+		)
+		.await?;
+
+		// -- Check
+		assert_eq!(affected.count, 2);
+		assert_eq!(affected.ids, &fx_ids[..2]);
+		for (i, id) in fx_ids.iter().enumerate() {
+			let product = ProductBmc::get(&ctx, &mm, *id).await?;
+			let expected = if i < 2 { fx_description } else { "" };
+			assert_eq!(product.description, expected);
+		}
+
+		// -- Clean
+		for id in fx_ids {
+			ProductBmc::delete(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_delete_many_ok_soft() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_names = &[
+			"test_delete_many_ok_soft product 01",
+			"test_delete_many_ok_soft product 02",
+		];
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_delete_many_ok_soft company 01",
+		)
+		.await?;
+		let fx_ids =
+			_dev_utils::seed_products(&ctx, &mm, fx_company_id, fx_names).await?;
+		let filter = || {
+			vec![ProductFilter {
+				company_id: Some(fx_company_id.into()),
+				..Default::default()
+			}]
+		};
+
+		// -- Exec
+		let affected = ProductBmc::delete_many(&ctx, &mm, filter()).await?;
+		let affected_again = ProductBmc::delete_many(&ctx, &mm, filter()).await?;
+
+		// -- Check
+		assert_eq!(affected.ids, fx_ids);
+		assert_eq!(affected.count, 2);
+		assert_eq!(affected_again.count, 0);
+		let products = ProductBmc::list(&ctx, &mm, Some(filter()), None).await?;
+		assert!(products.is_empty());
+		let products_all =
+			ProductBmc::list_including_deleted(&ctx, &mm, Some(filter()), None)
+				.await?;
+		assert_eq!(products_all.len(), 2);
+
+		// -- Clean
+		// Note: The products are already (soft) deleted.
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_delete_many_err_filter_empty() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_filters = [vec![], vec![ProductFilter::default()]];
+
+		for fx_filter in fx_filters {
+			// -- Exec
+			let res = ProductBmc::delete_many(&ctx, &mm, fx_filter).await;
+
+			// -- Check
+			assert!(
+				matches!(res, Err(ModelError::BulkFilterEmpty)),
+				"BulkFilterEmpty not matching"
+			);
+		}
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_delete_ok_soft() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_names = &[
+			"test_delete_ok_soft product 01",
+			"test_delete_ok_soft product 02",
+		];
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_delete_ok_soft company 01")
+				.await?;
+		let fx_ids =
+			_dev_utils::seed_products(&ctx, &mm, fx_company_id, fx_names).await?;
+
+		// -- Exec
+		ProductBmc::delete(&ctx, &mm, fx_ids[0]).await?;
+
+		// -- Check
+		let res = ProductBmc::get(&ctx, &mm, fx_ids[0]).await;
+		assert!(
+			matches!(res, Err(ModelError::EntityNotFound { entity: "product", id }) if id == fx_ids[0]),
+			"EntityNotFound not matching"
+		);
+		let product =
+			ProductBmc::get_including_deleted(&ctx, &mm, fx_ids[0]).await?;
+		assert_eq!(product.name, fx_names[0]);
+		let filter = || ProductFilter {
+			company_id: Some(fx_company_id.into()),
+			..Default::default()
+		};
+		let products =
+			ProductBmc::list(&ctx, &mm, Some(vec![filter()]), None).await?;
+		assert_eq!(products.len(), 1);
+		assert_eq!(products[0].id, fx_ids[1]);
+		let products = ProductBmc::list_including_deleted(
+			&ctx,
+			&mm,
+			Some(vec![filter()]),
+			None,
+		)
+		.await?;
+		assert_eq!(products.len(), 2);
+
+		// -- Clean
+		ProductBmc::delete(&ctx, &mm, fx_ids[1]).await?;
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_update_if_unchanged_err_concurrent() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_update_if_unchanged_err_concurrent company 01",
+		)
+		.await?;
+		let fx_id = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&["test_update_if_unchanged_err_concurrent product 01"],
+		)
+		.await?[0];
+		let product_u = |name: &str| ProductForUpdate {
+			name: Some(name.to_string()),
+			..Default::default()
+		};
+		let read_mtime = ProductBmc::get(&ctx, &mm, fx_id).await?.mtime;
+		// first writer (with the read mtime)
+		ProductBmc::update_if_unchanged(
+			&ctx,
+			&mm,
+			fx_id,
+			product_u("name 01"),
+			read_mtime,
+		)
+		.await?;
+
+		// -- Exec
+		// second writer (with the same, now stale, read mtime)
+		let res = ProductBmc::update_if_unchanged(
+			&ctx,
+			&mm,
+			fx_id,
+			product_u("name 02"),
+			read_mtime,
+		)
+		.await;
+
+		// -- Check
+		assert!(
+			matches!(
+				res,
+				Err(ModelError::ConcurrentModification { entity: "product", id }) if id == fx_id
+			),
+			"ConcurrentModification not matching"
+		);
+		let product = ProductBmc::get(&ctx, &mm, fx_id).await?;
+		assert_eq!(product.name, "name 01");
+
+		// -- Clean
+		ProductBmc::delete(&ctx, &mm, fx_id).await?;
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_search_ok_ranked() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id =
+			_dev_utils::seed_company(&ctx, &mm, "test_search_ok_ranked company 01")
+				.await?;
+		let fx_products = [
+			("Asuransi Jiwa Prima", "Santunan meninggal dunia"),
+			(
+				"Asuransi Kesehatan Keluarga",
+				"Perlindungan kesehatan <i>untuk</i> seluruh keluarga",
+			),
+		];
+		let mut fx_product_ids = Vec::new();
+		for (name, description) in fx_products {
+			let product_c = ProductForCreate {
+				company_id: fx_company_id,
+				name: name.to_string(),
+				description: Some(description.to_string()),
+				banner: None,
+			};
+			fx_product_ids.push(ProductBmc::create(&ctx, &mm, product_c).await?);
+		}
+		let filter = || {
+			Some(vec![ProductFilter {
+				company_id: Some(fx_company_id.into()),
+				..Default::default()
+			}])
+		};
+
+		// -- Exec
+		let search = |query: &'static str| {
+			ProductBmc::search(&ctx, &mm, query, filter(), None)
+		};
+		let family_hits = search("asuransi kesehatan keluarga").await?;
+		let stem_hits = search("sehat").await?;
+		let prefix_hits = search("kelu").await?;
+		let empty_hits = search(" ?! ").await?;
+		let res_limit =
+			ProductBmc::search(&ctx, &mm, "sehat", filter(), Some(-1)).await;
+
+		// -- Check
+		let ids: Vec<i64> = family_hits.iter().map(|h| h.product.id).collect();
+		assert_eq!(ids, [fx_product_ids[1], fx_product_ids[0]]);
+		assert!(family_hits[0].rank > family_hits[1].rank);
+		assert!(family_hits[0].snippet.contains("<b>Kesehatan</b>"));
+		assert!(family_hits[0].snippet.contains("&lt;i&gt;untuk&lt;/i&gt;"));
+		let ids: Vec<i64> = stem_hits.iter().map(|h| h.product.id).collect();
+		assert_eq!(ids, [fx_product_ids[1]]);
+		let ids: Vec<i64> = prefix_hits.iter().map(|h| h.product.id).collect();
+		assert_eq!(ids, [fx_product_ids[1]]);
+		assert!(empty_hits.is_empty());
+		assert!(
+			matches!(res_limit, Err(ModelError::ListLimitInvalid { actual: -1 })),
+			"ListLimitInvalid not matching"
+		);
+
+		// -- Clean
+		for id in fx_product_ids {
+			ProductBmc::delete(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_get_err_not_found() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_id = 100;
+
+		// -- Exec
+		let res = ProductBmc::get(&ctx, &mm, fx_id).await;
+
+		// -- Check
+		assert!(
+			matches!(
+				res,
+				Err(ModelError::EntityNotFound {
+					entity: "product",
+					id: 100
+				})
+			),
+			"EntityNotFound not matching"
+		);
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_update_visibility_ok_enforced() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_update_visibility_ok_enforced company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_update_visibility_ok_enforced product 01"],
+		)
+		.await?[0];
+		let fx_user_ids = _dev_utils::seed_users(
+			&root_ctx,
+			&mm,
+			&[
+				"test_update_visibility_ok_enforced-staff",
+				"test_update_visibility_ok_enforced-user",
+			],
+		)
+		.await?;
+		let fx_member_id = CompanyMemberBmc::create(
+			&root_ctx,
+			&mm,
+			CompanyMemberForCreate {
+				company_id: fx_company_id,
+				user_id: fx_user_ids[0],
+			},
+		)
+		.await?;
+		let staff_ctx = Ctx::new(fx_user_ids[0])?;
+		let user_ctx = Ctx::new(fx_user_ids[1])?;
+		let fx_claim_id = ClaimBmc::create(
+			&root_ctx,
+			&mm,
+			ClaimForCreate {
+				product_id: fx_product_id,
+				status: None,
+				submit_time: now_utc(),
+				decision_time: None,
+				payment_time: None,
+			},
+		)
+		.await?;
+		let hide_all = || ProductVisibilityForUpdate {
+			review_visibility: Some(false),
+			claim_historical_data_visibility: Some(false),
+		};
+
+		// -- Exec
+		let res_user =
+			ProductBmc::update_visibility(&user_ctx, &mm, fx_product_id, hide_all())
+				.await;
+		ProductBmc::update_visibility(&staff_ctx, &mm, fx_product_id, hide_all())
+			.await?;
+
+		// -- Check
+		assert!(
+			matches!(
+				res_user,
+				Err(ModelError::EntityAccessDenied {
+					entity: "company",
+					..
+				})
+			),
+			"EntityAccessDenied not matching"
+		);
+		let product = ProductBmc::get(&user_ctx, &mm, fx_product_id).await?;
+		assert!(!product.review_visibility);
+		assert!(!product.claim_historical_data_visibility);
+		let res_reviews =
+			ReviewBmc::list_by_product(&user_ctx, &mm, fx_product_id, None).await;
+		assert!(
+			matches!(res_reviews, Err(ModelError::ProductReviewsHidden { product_id }) if product_id == fx_product_id),
+			"ProductReviewsHidden not matching"
+		);
+		let res_claims = ClaimBmc::monthly_volume(
+			&user_ctx,
+			&mm,
+			fx_product_id,
+			ClaimHistoryResolution::AllTime,
+		)
+		.await;
+		assert!(
+			matches!(res_claims, Err(ModelError::ProductClaimDataHidden { product_id }) if product_id == fx_product_id),
+			"ProductClaimDataHidden not matching"
+		);
+		let res_claim = ClaimBmc::get(&user_ctx, &mm, fx_claim_id).await;
+		assert!(
+			matches!(res_claim, Err(ModelError::ProductClaimDataHidden { product_id }) if product_id == fx_product_id),
+			"ProductClaimDataHidden not matching"
+		);
+		let claim = ClaimBmc::get(&staff_ctx, &mm, fx_claim_id).await?;
+		assert_eq!(claim.product_id, fx_product_id);
+		let volumes = ClaimBmc::monthly_volume(
+			&staff_ctx,
+			&mm,
+			fx_product_id,
+			ClaimHistoryResolution::AllTime,
+		)
+		.await?;
+		assert_eq!(volumes.iter().map(|v| v.volume).sum::<i64>(), 1);
+		let claim_filter = || ClaimFilter {
+			product_id: Some(fx_product_id.into()),
+			..Default::default()
+		};
+		let claims =
+			ClaimBmc::list(&user_ctx, &mm, Some(vec![claim_filter()]), None).await?;
+		assert!(claims.is_empty());
+		let claims =
+			ClaimBmc::list(&staff_ctx, &mm, Some(vec![claim_filter()]), None)
+				.await?;
+		assert_eq!(claims.len(), 1);
+		let claims =
+			ClaimBmc::list(&root_ctx, &mm, Some(vec![claim_filter()]), None).await?;
+		assert_eq!(claims.len(), 1);
+
+		// -- Clean
+		ClaimBmc::delete(&root_ctx, &mm, fx_claim_id).await?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		CompanyMemberBmc::delete(&root_ctx, &mm, fx_member_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+		_dev_utils::clean_users(
+			&root_ctx,
+			&mm,
+			"test_update_visibility_ok_enforced",
+		)
+		.await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
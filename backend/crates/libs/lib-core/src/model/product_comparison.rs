@@ -0,0 +1,532 @@
+//! Side-by-side comparison of 2 to 5 products.
+//!
+//! The comparison is a matrix with one column per product (in the requested order)
+//! and one row per attribute (company, rating, claim count, and for each benefit type
+//! covered by at least one of the products, its limits, waiting days, and exclusions).
+//!
+//! Notes:
+//!   - A benefit type not covered by a product is an explicit `NotCovered` cell.
+//!   - For the numeric rows with a direction (e.g., higher rating is better),
+//!     `best_product_ids` flags the best product(s) (several on a tie), and is empty
+//!     when all the products are on par.
+
+use crate::ctx::Ctx;
+use crate::model::base::DbBmc;
+use crate::model::claim::ClaimBmc;
+use crate::model::company::{Company, CompanyBmc, CompanyFilter};
+use crate::model::coverage_item::{
+	CoverageBenefitTyp, CoverageItem, CoverageItemBmc, CoverageLimitPeriod,
+};
+use crate::model::product::{Product, ProductBmc, ProductFilter};
+use crate::model::review::ReviewBmc;
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use modql::filter::OpValInt64;
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub const PRODUCT_COMPARISON_MIN: usize = 2;
+pub const PRODUCT_COMPARISON_MAX: usize = 5;
+
+// region:    --- ProductComparison Types
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductComparison {
+	/// The compared products (the columns), in the requested order.
+	pub products: Vec<ComparedProduct>,
+	pub rows: Vec<ComparisonRow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparedProduct {
+	pub product_id: i64,
+	pub name: String,
+	pub company_id: i64,
+	pub company_name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonRow {
+	pub attribute: ComparisonAttribute,
+	/// Set for the coverage rows.
+	pub benefit_typ: Option<CoverageBenefitTyp>,
+	/// None for the rows without a better direction (e.g., text, claim count).
+	pub better: Option<ComparisonBetter>,
+	/// One cell per product, aligned with `ProductComparison.products`.
+	pub cells: Vec<ComparisonCell>,
+	pub best_product_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ComparisonAttribute {
+	Company,
+	Rating,
+	CountReview,
+	CountClaim,
+	/// The highest per-item limit of the benefit type.
+	LimitAmount,
+	/// The highest yearly `period_limit_amount` of the benefit type.
+	AnnualLimitAmount,
+	/// The shortest waiting period of the benefit type.
+	WaitingDays,
+	Exclusions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ComparisonBetter {
+	Higher,
+	Lower,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "value")]
+pub enum ComparisonCell {
+	Text(String),
+	Integer(i64),
+	Float(f64),
+	/// The benefit is covered without limit.
+	Unlimited,
+	/// The product does not cover the benefit type of the row.
+	NotCovered,
+	/// The product has no value for the attribute (e.g., no reviews yet).
+	NotSpecified,
+	/// The data is hidden by the insurer (see `Product.review_visibility`).
+	Hidden,
+}
+
+impl ComparisonCell {
+	/// The value used to find the best product(s). None if not comparable.
+	fn score(&self) -> Option<f64> {
+		match self {
+			ComparisonCell::Integer(val) => Some(*val as f64),
+			ComparisonCell::Float(val) => Some(*val),
+			ComparisonCell::Unlimited => Some(f64::INFINITY),
+			ComparisonCell::Text(_)
+			| ComparisonCell::NotCovered
+			| ComparisonCell::NotSpecified
+			| ComparisonCell::Hidden => None,
+		}
+	}
+}
+
+// endregion: --- ProductComparison Types
+
+// region:    --- Compare
+
+/// Compares the given products (2 to 5 distinct ids, duplicates are ignored).
+pub async fn compare_products(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	product_ids: &[i64],
+) -> Result<ProductComparison> {
+	// -- Validate the product ids.
+	let mut ids: Vec<i64> = Vec::with_capacity(product_ids.len());
+	for id in product_ids {
+		if !ids.contains(id) {
+			ids.push(*id);
+		}
+	}
+	if !(PRODUCT_COMPARISON_MIN..=PRODUCT_COMPARISON_MAX).contains(&ids.len()) {
+		return Err(Error::ProductComparisonCountOutOfRange {
+			min: PRODUCT_COMPARISON_MIN,
+			max: PRODUCT_COMPARISON_MAX,
+			actual: ids.len(),
+		});
+	}
+
+	// -- Load the products (in the requested order) and their companies.
+	let product_filter = ProductFilter {
+		id: Some(OpValInt64::In(ids.clone()).into()),
+		..Default::default()
+	};
+	let mut products: HashMap<i64, Product> =
+		ProductBmc::list(ctx, mm, Some(vec![product_filter]), None)
+			.await?
+			.into_iter()
+			.map(|product| (product.id, product))
+			.collect();
+	let products = ids
+		.iter()
+		.map(|id| {
+			products.remove(id).ok_or(Error::EntityNotFound {
+				entity: ProductBmc::TABLE,
+				id: *id,
+			})
+		})
+		.collect::<Result<Vec<Product>>>()?;
+	let company_filter = CompanyFilter {
+		id: Some(
+			OpValInt64::In(products.iter().map(|p| p.company_id).collect()).into(),
+		),
+		..Default::default()
+	};
+	let companies: HashMap<i64, Company> =
+		CompanyBmc::list(ctx, mm, Some(vec![company_filter]), None)
+			.await?
+			.into_iter()
+			.map(|company| (company.id, company))
+			.collect();
+
+	// -- Load the review, claim, and coverage data.
+	let ratings: HashMap<i64, _> = ReviewBmc::ratings_by_products(ctx, mm, &ids)
+		.await?
+		.into_iter()
+		.map(|rating| (rating.product_id, rating))
+		.collect();
+	let claim_counts: HashMap<i64, i64> =
+		ClaimBmc::counts_by_products(ctx, mm, &ids)
+			.await?
+			.into_iter()
+			.map(|count| (count.product_id, count.count_claim))
+			.collect();
+	let mut coverage: HashMap<(i64, CoverageBenefitTyp), Vec<CoverageItem>> =
+		HashMap::new();
+	for item in CoverageItemBmc::list_by_products(ctx, mm, &ids).await? {
+		coverage
+			.entry((item.product_id, item.benefit_typ))
+			.or_default()
+			.push(item);
+	}
+
+	// -- Build the rows.
+	let reviews_visible =
+		|id: i64| products.iter().any(|p| p.id == id && p.review_visibility);
+	let claims_visible = |id: i64| {
+		products
+			.iter()
+			.any(|p| p.id == id && p.claim_historical_data_visibility)
+	};
+	let mut rows = vec![
+		row(&ids, ComparisonAttribute::Company, None, None, |id| {
+			let name = products
+				.iter()
+				.find(|p| p.id == id)
+				.and_then(|p| companies.get(&p.company_id))
+				.map(|c| c.name.clone());
+			name.map(ComparisonCell::Text)
+				.unwrap_or(ComparisonCell::NotSpecified)
+		}),
+		row(
+			&ids,
+			ComparisonAttribute::Rating,
+			None,
+			Some(ComparisonBetter::Higher),
+			|id| {
+				if !reviews_visible(id) {
+					return ComparisonCell::Hidden;
+				}
+				ratings
+					.get(&id)
+					.map(|r| ComparisonCell::Float(r.rating))
+					.unwrap_or(ComparisonCell::NotSpecified)
+			},
+		),
+		row(
+			&ids,
+			ComparisonAttribute::CountReview,
+			None,
+			Some(ComparisonBetter::Higher),
+			|id| {
+				if !reviews_visible(id) {
+					return ComparisonCell::Hidden;
+				}
+				let count = ratings.get(&id).map(|r| r.count_review);
+				ComparisonCell::Integer(count.unwrap_or_default())
+			},
+		),
+		row(&ids, ComparisonAttribute::CountClaim, None, None, |id| {
+			if !claims_visible(id) {
+				return ComparisonCell::Hidden;
+			}
+			ComparisonCell::Integer(
+				claim_counts.get(&id).copied().unwrap_or_default(),
+			)
+		}),
+	];
+
+	for benefit_typ in CoverageBenefitTyp::ALL {
+		if !ids
+			.iter()
+			.any(|id| coverage.contains_key(&(*id, benefit_typ)))
+		{
+			continue;
+		}
+		let coverage_row =
+			|attribute: ComparisonAttribute,
+			 better: Option<ComparisonBetter>,
+			 to_cell: fn(&[CoverageItem]) -> ComparisonCell| {
+				row(&ids, attribute, Some(benefit_typ), better, |id| {
+					coverage
+						.get(&(id, benefit_typ))
+						.map(|items| to_cell(items))
+						.unwrap_or(ComparisonCell::NotCovered)
+				})
+			};
+		rows.push(coverage_row(
+			ComparisonAttribute::LimitAmount,
+			Some(ComparisonBetter::Higher),
+			limit_amount_cell,
+		));
+		rows.push(coverage_row(
+			ComparisonAttribute::AnnualLimitAmount,
+			Some(ComparisonBetter::Higher),
+			annual_limit_amount_cell,
+		));
+		rows.push(coverage_row(
+			ComparisonAttribute::WaitingDays,
+			Some(ComparisonBetter::Lower),
+			waiting_days_cell,
+		));
+		rows.push(coverage_row(
+			ComparisonAttribute::Exclusions,
+			None,
+			exclusions_cell,
+		));
+	}
+
+	let products = products
+		.into_iter()
+		.map(|product| ComparedProduct {
+			product_id: product.id,
+			company_name: companies
+				.get(&product.company_id)
+				.map(|c| c.name.clone())
+				.unwrap_or_default(),
+			company_id: product.company_id,
+			name: product.name,
+		})
+		.collect();
+
+	Ok(ProductComparison { products, rows })
+}
+
+/// Builds a row from the cell of each product, and flags its best product(s).
+fn row(
+	product_ids: &[i64],
+	attribute: ComparisonAttribute,
+	benefit_typ: Option<CoverageBenefitTyp>,
+	better: Option<ComparisonBetter>,
+	to_cell: impl Fn(i64) -> ComparisonCell,
+) -> ComparisonRow {
+	let cells: Vec<ComparisonCell> =
+		product_ids.iter().map(|id| to_cell(*id)).collect();
+	let best_product_ids = better
+		.map(|better| best_product_ids(product_ids, &cells, better))
+		.unwrap_or_default();
+
+	ComparisonRow {
+		attribute,
+		benefit_typ,
+		better,
+		cells,
+		best_product_ids,
+	}
+}
+
+fn best_product_ids(
+	product_ids: &[i64],
+	cells: &[ComparisonCell],
+	better: ComparisonBetter,
+) -> Vec<i64> {
+	// Normalize the scores so that the highest is always the best.
+	let scores: Vec<Option<f64>> = cells
+		.iter()
+		.map(|cell| {
+			cell.score().map(|score| match better {
+				ComparisonBetter::Higher => score,
+				ComparisonBetter::Lower => -score,
+			})
+		})
+		.collect();
+	let Some(best) = scores.iter().flatten().copied().reduce(f64::max) else {
+		return Vec::new();
+	};
+
+	let best_ids: Vec<i64> = product_ids
+		.iter()
+		.zip(scores)
+		.filter(|(_, score)| *score == Some(best))
+		.map(|(id, _)| *id)
+		.collect();
+
+	// All the products on par, nothing to flag.
+	if best_ids.len() == product_ids.len() {
+		return Vec::new();
+	}
+
+	best_ids
+}
+
+// -- Coverage cells (from the items of a product for a given benefit type)
+
+fn limit_amount_cell(items: &[CoverageItem]) -> ComparisonCell {
+	let limits: Option<Vec<i64>> = items.iter().map(|i| i.limit_amount).collect();
+	match limits.and_then(|limits| limits.into_iter().max()) {
+		Some(limit) => ComparisonCell::Integer(limit),
+		// At least one item without limit.
+		None => ComparisonCell::Unlimited,
+	}
+}
+
+fn annual_limit_amount_cell(items: &[CoverageItem]) -> ComparisonCell {
+	items
+		.iter()
+		.filter(|i| i.limit_period == Some(CoverageLimitPeriod::Year))
+		.filter_map(|i| i.period_limit_amount)
+		.max()
+		.map(ComparisonCell::Integer)
+		.unwrap_or(ComparisonCell::NotSpecified)
+}
+
+fn waiting_days_cell(items: &[CoverageItem]) -> ComparisonCell {
+	items
+		.iter()
+		.map(|i| i.waiting_days as i64)
+		.min()
+		.map(ComparisonCell::Integer)
+		.unwrap_or(ComparisonCell::NotSpecified)
+}
+
+fn exclusions_cell(items: &[CoverageItem]) -> ComparisonCell {
+	let exclusions: Vec<&str> = items
+		.iter()
+		.map(|i| i.exclusions.as_str())
+		.filter(|exclusions| !exclusions.is_empty())
+		.collect();
+	if exclusions.is_empty() {
+		ComparisonCell::NotSpecified
+	} else {
+		ComparisonCell::Text(exclusions.join("\n"))
+	}
+}
+
+// endregion: --- Compare
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::coverage_item::CoverageItemForCreate;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_compare_products_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_compare_products_ok company 01",
+		)
+		.await?;
+		let fx_product_ids = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&[
+				"test_compare_products_ok product 01",
+				"test_compare_products_ok product 02",
+			],
+		)
+		.await?;
+		let fx_items = [
+			(fx_product_ids[0], CoverageBenefitTyp::Hospitalization, 30),
+			(fx_product_ids[1], CoverageBenefitTyp::Hospitalization, 90),
+			(fx_product_ids[1], CoverageBenefitTyp::Dental, 0),
+		];
+		let mut fx_item_ids = Vec::new();
+		for (product_id, benefit_typ, waiting_days) in fx_items {
+			let id = CoverageItemBmc::create(
+				&ctx,
+				&mm,
+				CoverageItemForCreate {
+					product_id,
+					benefit_typ,
+					name: benefit_typ.to_string(),
+					limit_amount: None,
+					period_limit_amount: None,
+					limit_period: None,
+					waiting_days: Some(waiting_days),
+					exclusions: None,
+				},
+			)
+			.await?;
+			fx_item_ids.push(id);
+		}
+
+		// -- Exec
+		let comparison = compare_products(&ctx, &mm, &fx_product_ids).await?;
+		let res_too_few =
+			compare_products(&ctx, &mm, &[fx_product_ids[0], fx_product_ids[0]])
+				.await;
+
+		// -- Check
+		let ids: Vec<i64> =
+			comparison.products.iter().map(|p| p.product_id).collect();
+		assert_eq!(ids, fx_product_ids);
+		let find_row = |attribute, benefit_typ| {
+			comparison
+				.rows
+				.iter()
+				.find(|r| r.attribute == attribute && r.benefit_typ == benefit_typ)
+		};
+		let row = find_row(
+			ComparisonAttribute::WaitingDays,
+			Some(CoverageBenefitTyp::Hospitalization),
+		)
+		.ok_or("Should have hospitalization waiting days row")?;
+		assert_eq!(
+			row.cells,
+			[ComparisonCell::Integer(30), ComparisonCell::Integer(90)]
+		);
+		assert_eq!(row.best_product_ids, [fx_product_ids[0]]);
+		let row = find_row(
+			ComparisonAttribute::LimitAmount,
+			Some(CoverageBenefitTyp::Dental),
+		)
+		.ok_or("Should have dental limit amount row")?;
+		assert_eq!(
+			row.cells,
+			[ComparisonCell::NotCovered, ComparisonCell::Unlimited]
+		);
+		assert_eq!(row.best_product_ids, [fx_product_ids[1]]);
+		let row = find_row(ComparisonAttribute::CountClaim, None)
+			.ok_or("Should have claim count row")?;
+		assert!(row.best_product_ids.is_empty());
+		assert!(find_row(
+			ComparisonAttribute::LimitAmount,
+			Some(CoverageBenefitTyp::Surgery)
+		)
+		.is_none());
+		assert!(
+			matches!(
+				res_too_few,
+				Err(crate::model::Error::ProductComparisonCountOutOfRange {
+					actual: 1,
+					..
+				})
+			),
+			"Should be ProductComparisonCountOutOfRange"
+		);
+
+		// -- Clean
+		for id in fx_item_ids {
+			CoverageItemBmc::delete(&ctx, &mm, id).await?;
+		}
+		for product_id in fx_product_ids {
+			ProductBmc::delete(&ctx, &mm, product_id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -0,0 +1,333 @@
+//! Premium quote engine.
+//!
+//! Computes the estimated premium of a product for the user of the `Ctx`,
+//! from the product rating table (see `RatingTableBmc`) and the user profile
+//! collected at registration (birth date, region, occupation, dependents).
+//!
+//! Notes:
+//!   - The amounts are in IDR, the annual premium being the rounded product of the
+//!     `base_premium` and the age band, region, occupation class and dependent factors.
+//!   - Each line of the breakdown is the amount added by its factor, so that the lines
+//!     always sum up to the annual premium.
+
+use crate::ctx::Ctx;
+use crate::model::product::ProductBmc;
+use crate::model::rating_table::{RatingTable, RatingTableBmc};
+use crate::model::user_profile::{UserProfile, UserProfileBmc};
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use lib_utils::time::now_utc;
+use serde::Serialize;
+use sqlx::types::time::Date;
+
+// region:    --- Quote Types
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PremiumQuote {
+	pub product_id: i64,
+	pub rating_table_id: i64,
+	pub rating_version: i32,
+
+	/// The age (in full years) used for the age band.
+	pub age: i32,
+	pub lines: Vec<PremiumQuoteLine>,
+	pub annual_premium: i64,
+	pub monthly_premium: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PremiumQuoteLine {
+	pub item: PremiumQuoteItem,
+	pub label: String,
+	/// The factor applied to the premium (1 for the base premium).
+	pub factor: f64,
+	/// The amount added to the premium by this line (can be negative for a discount).
+	pub amount: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PremiumQuoteItem {
+	Base,
+	Age,
+	Region,
+	Occupation,
+	Dependents,
+}
+
+// endregion: --- Quote Types
+
+// region:    --- Quote
+
+/// Quotes the product for the user of the ctx (which must have a profile),
+/// with the given rating table version, or the latest if None.
+/// Fails with `Error::EntityNotFound` if the product is not found (or deleted).
+pub async fn quote_product(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	product_id: i64,
+	version: Option<i32>,
+) -> Result<PremiumQuote> {
+	// Note: The rating tables are kept when the product is (soft) deleted.
+	ProductBmc::get(ctx, mm, product_id).await?;
+
+	let user_id = ctx.user_id();
+	let profile = UserProfileBmc::first_by_user(ctx, mm, user_id)
+		.await?
+		.ok_or(Error::QuoteUserProfileNotFound { user_id })?;
+	let rating_table =
+		RatingTableBmc::first_by_product(ctx, mm, product_id, version)
+			.await?
+			.ok_or(Error::QuoteRatingTableNotFound {
+				product_id,
+				version,
+			})?;
+
+	compute_quote(&rating_table, &profile, now_utc().date())
+}
+
+fn compute_quote(
+	rating_table: &RatingTable,
+	profile: &UserProfile,
+	today: Date,
+) -> Result<PremiumQuote> {
+	let schedule = &rating_table.schedule;
+	let age = age_on(profile.birth_date, today);
+
+	// -- Collect the factors.
+	let band = schedule.age_band(age).ok_or(Error::QuoteAgeNotRated {
+		product_id: rating_table.product_id,
+		age,
+	})?;
+	// Note: The schedule is validated on create, so the default class exists.
+	let occupation_class = schedule.occupation_class(&profile.occupation);
+	let dependents = profile.dependents.max(0);
+	let factors = [
+		(
+			PremiumQuoteItem::Age,
+			format!("Age {age} ({}-{})", band.min_age, band.max_age),
+			band.factor,
+		),
+		(
+			PremiumQuoteItem::Region,
+			format!("Region {}", profile.region),
+			schedule.region_factor(&profile.region),
+		),
+		(
+			PremiumQuoteItem::Occupation,
+			format!(
+				"Occupation class {}",
+				occupation_class.map(|c| c.class).unwrap_or_default()
+			),
+			occupation_class.map(|c| c.factor).unwrap_or(1.),
+		),
+		(
+			PremiumQuoteItem::Dependents,
+			format!("{dependents} dependent(s)"),
+			1. + dependents as f64 * schedule.dependent_loading,
+		),
+	];
+
+	// -- Build the lines (the rounded running premium deltas).
+	let base_premium = rating_table.base_premium;
+	let mut lines = vec![PremiumQuoteLine {
+		item: PremiumQuoteItem::Base,
+		label: "Base premium".to_string(),
+		factor: 1.,
+		amount: base_premium,
+	}];
+	let mut premium = base_premium as f64;
+	let mut annual_premium = base_premium;
+	for (item, label, factor) in factors {
+		premium *= factor;
+		let rounded = premium.round() as i64;
+		lines.push(PremiumQuoteLine {
+			item,
+			label,
+			factor,
+			amount: rounded - annual_premium,
+		});
+		annual_premium = rounded;
+	}
+
+	Ok(PremiumQuote {
+		product_id: rating_table.product_id,
+		rating_table_id: rating_table.id,
+		rating_version: rating_table.version,
+		age,
+		lines,
+		annual_premium,
+		monthly_premium: (annual_premium as f64 / 12.).round() as i64,
+	})
+}
+
+/// The age in full years on the given date.
+fn age_on(birth_date: Date, date: Date) -> i32 {
+	let age = date.year() - birth_date.year();
+	if (date.month() as u8, date.day())
+		< (birth_date.month() as u8, birth_date.day())
+	{
+		age - 1
+	} else {
+		age
+	}
+}
+
+// endregion: --- Quote
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::base;
+	use crate::model::company::CompanyBmc;
+	use crate::model::rating_table::{
+		RatingAgeBand, RatingOccupationClass, RatingRegionFactor, RatingSchedule,
+		RatingTableForCreate,
+	};
+	use crate::model::user::{UserBmc, UserForCreate};
+	use crate::model::user_profile::UserProfileForCreate;
+	use serial_test::serial;
+
+	fn fx_schedule(age_factor: f64) -> RatingSchedule {
+		RatingSchedule {
+			age_bands: vec![RatingAgeBand {
+				min_age: 18,
+				max_age: 150,
+				factor: age_factor,
+			}],
+			region_factors: vec![RatingRegionFactor {
+				region: "DKI Jakarta".to_string(),
+				factor: 1.2,
+			}],
+			default_region_factor: 1.,
+			occupation_classes: vec![
+				RatingOccupationClass {
+					class: 1,
+					factor: 1.,
+					occupations: vec!["Engineer".to_string()],
+				},
+				RatingOccupationClass {
+					class: 3,
+					factor: 1.5,
+					occupations: vec!["Miner".to_string()],
+				},
+			],
+			default_occupation_class: 1,
+			dependent_loading: 0.25,
+		}
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_quote_product_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_username = "test_quote_product_ok@example.com";
+		let fx_user_id = UserProfileBmc::create_with_user(
+			&root_ctx,
+			&mm,
+			UserForCreate {
+				username: fx_username.to_string(),
+				pwd_clear: "test_quote_product_ok pwd 01".to_string(),
+			},
+			UserProfileForCreate {
+				email: fx_username.to_string(),
+				phone: "+62 812 0000 0000".to_string(),
+				full_name: "Test User".to_string(),
+				gender: "Male".to_string(),
+				birth_date: Date::from_calendar_date(1990, time::Month::January, 1)?,
+				address: "Jakarta".to_string(),
+				marital_status: "Married".to_string(),
+				occupation: "miner".to_string(),
+				income: 10_000_000.,
+				dependents: 2,
+				region: "DKI Jakarta".to_string(),
+				familiarity: "Beginner".to_string(),
+				interests: "Health".to_string(),
+			},
+		)
+		.await?;
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_quote_product_ok company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_quote_product_ok product 01"],
+		)
+		.await?[0];
+		let mut fx_rating_table_ids = Vec::new();
+		for age_factor in [1., 2.] {
+			let id = RatingTableBmc::create(
+				&root_ctx,
+				&mm,
+				RatingTableForCreate {
+					product_id: fx_product_id,
+					base_premium: 1_000_000,
+					schedule: fx_schedule(age_factor),
+				},
+			)
+			.await?;
+			fx_rating_table_ids.push(id);
+		}
+		let ctx = Ctx::new(fx_user_id)?;
+
+		// -- Exec
+		let latest = quote_product(&ctx, &mm, fx_product_id, None).await?;
+		let first = quote_product(&ctx, &mm, fx_product_id, Some(1)).await?;
+		let res_no_profile =
+			quote_product(&root_ctx, &mm, fx_product_id, None).await;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		let res_deleted = quote_product(&ctx, &mm, fx_product_id, None).await;
+
+		// -- Check
+		// 1_000_000 x 2 (age) x 1.2 (region) x 1.5 (class 3) x 1.5 (2 dependents)
+		assert_eq!(latest.rating_version, 2);
+		assert_eq!(latest.annual_premium, 5_400_000);
+		assert_eq!(latest.monthly_premium, 450_000);
+		let amounts: Vec<i64> = latest.lines.iter().map(|l| l.amount).collect();
+		assert_eq!(
+			amounts,
+			[1_000_000, 1_000_000, 400_000, 1_200_000, 1_800_000]
+		);
+		assert_eq!(first.rating_version, 1);
+		assert_eq!(first.annual_premium, 2_700_000);
+		assert!(
+			matches!(
+				res_no_profile,
+				Err(crate::model::Error::QuoteUserProfileNotFound { user_id: 0 })
+			),
+			"Should be QuoteUserProfileNotFound"
+		);
+		assert!(
+			matches!(
+				res_deleted,
+				Err(crate::model::Error::EntityNotFound { entity: "product", id })
+					if id == fx_product_id
+			),
+			"Should be EntityNotFound"
+		);
+
+		// -- Clean
+		// Note: The rating table versions are immutable (no `RatingTableBmc::delete`).
+		for id in fx_rating_table_ids {
+			base::delete::<RatingTableBmc>(&root_ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+		UserBmc::delete(&root_ctx, &mm, fx_user_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -0,0 +1,336 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::product::ProductBmc;
+use crate::model::ModelManager;
+use crate::model::{Error, ListMeta, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsValue};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- RatingTable Types
+
+/// A version of the premium rating table of a product.
+/// Note: The versions are immutable (a new rating is a new version),
+///       so that a quote can always be reproduced from its version.
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct RatingTable {
+	pub id: i64,
+	pub product_id: i64,
+	pub version: i32,
+
+	/// The yearly premium (IDR) before the schedule factors.
+	pub base_premium: i64,
+	#[sqlx(json)]
+	pub schedule: RatingSchedule,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+/// The factors applied to the `base_premium` (stored as jsonb).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RatingSchedule {
+	/// Must not overlap. An age outside of the bands is not rated (no quote).
+	pub age_bands: Vec<RatingAgeBand>,
+
+	pub region_factors: Vec<RatingRegionFactor>,
+	/// For the regions not in `region_factors`.
+	pub default_region_factor: f64,
+
+	pub occupation_classes: Vec<RatingOccupationClass>,
+	/// For the occupations not in any of the `occupation_classes`.
+	pub default_occupation_class: i32,
+
+	/// The loading per dependent (e.g., 0.25 for +25% per dependent).
+	pub dependent_loading: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RatingAgeBand {
+	pub min_age: i32,
+	pub max_age: i32,
+	pub factor: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RatingRegionFactor {
+	pub region: String,
+	pub factor: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RatingOccupationClass {
+	pub class: i32,
+	pub factor: f64,
+	pub occupations: Vec<String>,
+}
+
+impl From<RatingSchedule> for sea_query::Value {
+	fn from(val: RatingSchedule) -> Self {
+		serde_json::to_value(val).unwrap_or_default().into()
+	}
+}
+
+impl RatingSchedule {
+	pub fn age_band(&self, age: i32) -> Option<&RatingAgeBand> {
+		self.age_bands
+			.iter()
+			.find(|band| (band.min_age..=band.max_age).contains(&age))
+	}
+
+	/// Note: The region match is case insensitive.
+	pub fn region_factor(&self, region: &str) -> f64 {
+		self.region_factors
+			.iter()
+			.find(|r| r.region.trim().eq_ignore_ascii_case(region.trim()))
+			.map(|r| r.factor)
+			.unwrap_or(self.default_region_factor)
+	}
+
+	/// Note: The occupation match is case insensitive.
+	pub fn occupation_class(
+		&self,
+		occupation: &str,
+	) -> Option<&RatingOccupationClass> {
+		let occupation = occupation.trim();
+		self.occupation_classes
+			.iter()
+			.find(|class| {
+				class
+					.occupations
+					.iter()
+					.any(|o| o.trim().eq_ignore_ascii_case(occupation))
+			})
+			.or_else(|| {
+				self.occupation_classes
+					.iter()
+					.find(|class| class.class == self.default_occupation_class)
+			})
+	}
+
+	fn validate(&self) -> Result<()> {
+		let invalid = |reason: String| Err(Error::RatingTableInvalid { reason });
+
+		if self.age_bands.is_empty() {
+			return invalid("age_bands is empty".to_string());
+		}
+		for (i, band) in self.age_bands.iter().enumerate() {
+			if band.min_age < 0 || band.min_age > band.max_age {
+				return invalid(format!(
+					"age band {}-{} is not a valid range",
+					band.min_age, band.max_age
+				));
+			}
+			let overlapping = self.age_bands[i + 1..].iter().find(|other| {
+				band.min_age <= other.max_age && other.min_age <= band.max_age
+			});
+			if let Some(other) = overlapping {
+				return invalid(format!(
+					"age bands {}-{} and {}-{} overlap",
+					band.min_age, band.max_age, other.min_age, other.max_age
+				));
+			}
+		}
+
+		if !self
+			.occupation_classes
+			.iter()
+			.any(|class| class.class == self.default_occupation_class)
+		{
+			return invalid(format!(
+				"default_occupation_class {} is not in occupation_classes",
+				self.default_occupation_class
+			));
+		}
+
+		let factors = self
+			.age_bands
+			.iter()
+			.map(|band| band.factor)
+			.chain(self.region_factors.iter().map(|r| r.factor))
+			.chain([self.default_region_factor])
+			.chain(self.occupation_classes.iter().map(|class| class.factor));
+		for factor in factors {
+			if !factor.is_finite() || factor <= 0. {
+				return invalid(format!("factor {factor} is not a positive number"));
+			}
+		}
+		if !self.dependent_loading.is_finite() || self.dependent_loading < 0. {
+			return invalid(format!(
+				"dependent_loading {} is not a number >= 0",
+				self.dependent_loading
+			));
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Deserialize)]
+pub struct RatingTableForCreate {
+	pub product_id: i64,
+	pub base_premium: i64,
+	pub schedule: RatingSchedule,
+}
+
+#[derive(Fields)]
+struct RatingTableForInsert {
+	product_id: i64,
+	version: i32,
+	base_premium: i64,
+	schedule: RatingSchedule,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct RatingTableFilter {
+	pub id: Option<OpValsInt64>,
+	pub product_id: Option<OpValsInt64>,
+	pub version: Option<OpValsInt64>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+// endregion: --- RatingTable Types
+
+// region:    --- RatingTableBmc
+
+pub struct RatingTableBmc;
+
+impl DbBmc for RatingTableBmc {
+	const TABLE: &'static str = "rating_table";
+}
+
+impl RatingTableBmc {
+	/// Only the users who can write the product (see `ProductBmc::check_write`)
+	/// can create its rating tables.
+	pub async fn check_create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		rating_table_c: &RatingTableForCreate,
+	) -> Result<()> {
+		ProductBmc::check_write(ctx, mm, rating_table_c.product_id).await
+	}
+
+	/// Creates the next version of the rating table of the product.
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		rating_table_c: RatingTableForCreate,
+	) -> Result<i64> {
+		let RatingTableForCreate {
+			product_id,
+			base_premium,
+			schedule,
+		} = rating_table_c;
+		if base_premium <= 0 {
+			return Err(Error::RatingTableInvalid {
+				reason: format!("base_premium {base_premium} is not positive"),
+			});
+		}
+		schedule.validate()?;
+
+		// Start the transaction
+		let mm = mm.new_with_txn()?;
+
+		mm.dbx().begin_txn().await?;
+
+		// Lock the product so that concurrent creates get distinct versions.
+		let sqlx_query = sqlx::query_as::<_, (i64,)>(
+			"SELECT id FROM product WHERE id = $1 AND NOT deleted FOR UPDATE",
+		)
+		.bind(product_id);
+		if mm.dbx().fetch_optional(sqlx_query).await?.is_none() {
+			return Err(Error::EntityNotFound {
+				entity: ProductBmc::TABLE,
+				id: product_id,
+			});
+		}
+		let sqlx_query = sqlx::query_as::<_, (i32,)>(
+			"SELECT COALESCE(MAX(version), 0) + 1 FROM rating_table WHERE product_id = $1",
+		)
+		.bind(product_id);
+		let (version,) = mm.dbx().fetch_one(sqlx_query).await?;
+
+		let rating_table_fi = RatingTableForInsert {
+			product_id,
+			version,
+			base_premium,
+			schedule,
+		};
+		let id = base::create::<Self, _>(ctx, &mm, rating_table_fi).await?;
+
+		// Commit the transaction
+		mm.dbx().commit_txn().await?;
+
+		Ok(id)
+	}
+
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<RatingTable> {
+		base::get::<Self, _>(ctx, mm, id).await
+	}
+
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<RatingTableFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<RatingTable>> {
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<RatingTableFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<RatingTable>, ListMeta)> {
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	/// Returns the given version of the rating table of the product,
+	/// or the latest version if `version` is None.
+	pub async fn first_by_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+		version: Option<i32>,
+	) -> Result<Option<RatingTable>> {
+		let filter = RatingTableFilter {
+			product_id: Some(product_id.into()),
+			version: version.map(|version| i64::from(version).into()),
+			..Default::default()
+		};
+		let list_options = ListOptions {
+			limit: Some(1),
+			order_bys: Some(vec!["!version"].into()),
+			..Default::default()
+		};
+		let rating_table =
+			Self::list(ctx, mm, Some(vec![filter]), Some(list_options))
+				.await?
+				.into_iter()
+				.next();
+
+		Ok(rating_table)
+	}
+}
+
+// endregion: --- RatingTableBmc
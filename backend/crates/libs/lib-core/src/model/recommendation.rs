@@ -0,0 +1,477 @@
+//! Personalized product recommendations.
+//!
+//! Ranks the products for the user of the `Ctx` from its profile (collected at
+//! registration) with the following scoring rules, each adding a reason:
+//!
+//!   - Interests: each interest matching a product category (or category ancestor)
+//!     or a coverage benefit type (e.g., "Health", "Dental").
+//!   - Dependents: a death benefit when the user has dependents.
+//!   - Familiarity: a coverage without exclusions for the beginners.
+//!   - Region: an insurer located in the user region.
+//!   - Rating: the Bayesian average rating (same prior as the trust score, see
+//!     `TrustScoreWeights::bayesian_rating`), a reason only for the well rated products.
+//!   - Settlement: the claim settlement ratio over one year (see `ClaimMetrics`),
+//!     a reason only for the well settling products.
+//!
+//! Notes:
+//!   - Without a profile (e.g., root ctx), only the rating and settlement rules apply.
+//!   - The candidates are all the products.
+
+use crate::ctx::Ctx;
+use crate::model::category::CategoryBmc;
+use crate::model::claim::{ClaimBmc, ClaimHistoryResolution, ClaimMetrics};
+use crate::model::company::{Company, CompanyBmc, CompanyFilter};
+use crate::model::coverage_item::{CoverageBenefitTyp, CoverageItemBmc};
+use crate::model::product::{Product, ProductBmc};
+use crate::model::review::ReviewBmc;
+use crate::model::trust_score::TRUST_SCORE_WEIGHTS;
+use crate::model::user_profile::{UserProfile, UserProfileBmc};
+use crate::model::ModelManager;
+use crate::model::{Error, ListCursorOptions, Result};
+use modql::filter::OpValInt64;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+pub const RECOMMENDATION_LIMIT_DEFAULT: i64 = 10;
+/// Note: Below the product list default limit (the recommended products are
+///       then listed in one page).
+pub const RECOMMENDATION_LIMIT_MAX: i64 = 100;
+
+const WEIGHT_INTEREST: f64 = 3.;
+const WEIGHT_DEPENDENTS: f64 = 2.;
+const WEIGHT_FAMILIARITY: f64 = 1.;
+const WEIGHT_REGION: f64 = 1.;
+const WEIGHT_RATING: f64 = 2.;
+const WEIGHT_SETTLEMENT: f64 = 1.;
+
+/// The minimum (average) rating for the rating to be a reason.
+const WELL_RATED_MIN: f64 = 4.;
+/// The minimum settlement ratio for the settlement to be a reason.
+const WELL_SETTLED_MIN: f64 = 0.8;
+
+/// The reason of the products not matching any rule with a reason.
+const REASON_DEFAULT: &str = "Ranked on the overall ratings and claim settlement";
+
+// region:    --- Recommendation Types
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductRecommendation {
+	pub product_id: i64,
+	pub score: f64,
+	/// The main reason (of the rule with the highest score).
+	pub reason: String,
+	/// All the reasons, by decreasing score.
+	pub reasons: Vec<String>,
+}
+
+// endregion: --- Recommendation Types
+
+// region:    --- Recommend
+
+/// Returns the recommended products for the user of the ctx, best first
+/// (`RECOMMENDATION_LIMIT_DEFAULT` if no limit, at most `RECOMMENDATION_LIMIT_MAX`).
+/// Note: All the products are candidates (scored page by page).
+pub async fn recommend_products(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	limit: Option<i64>,
+) -> Result<Vec<ProductRecommendation>> {
+	let limit = limit.unwrap_or(RECOMMENDATION_LIMIT_DEFAULT);
+	if limit < 0 {
+		return Err(Error::ListLimitInvalid { actual: limit });
+	}
+	if limit > RECOMMENDATION_LIMIT_MAX {
+		return Err(Error::ListLimitOverMax {
+			max: RECOMMENDATION_LIMIT_MAX,
+			actual: limit,
+		});
+	}
+	let limit = limit as usize;
+
+	let profile = UserProfileBmc::first_by_user(ctx, mm, ctx.user_id()).await?;
+	let profile = profile.as_ref().map(ProfileSignals::from);
+
+	let mut recommendations: Vec<ProductRecommendation> = Vec::new();
+	let mut cursor = None;
+	loop {
+		let cursor_options = ListCursorOptions {
+			cursor,
+			..Default::default()
+		};
+		let (products, meta) =
+			ProductBmc::list_by_cursor(ctx, mm, None, Some(cursor_options)).await?;
+		recommendations
+			.extend(score_products(ctx, mm, profile.as_ref(), &products).await?);
+
+		// Only keep the best recommendations so far.
+		recommendations.sort_by(|a, b| {
+			b.score
+				.total_cmp(&a.score)
+				.then(a.product_id.cmp(&b.product_id))
+		});
+		recommendations.truncate(limit);
+
+		match meta.next_cursor {
+			Some(next_cursor) => cursor = Some(next_cursor),
+			None => break,
+		}
+	}
+
+	Ok(recommendations)
+}
+
+/// Scores the products (a page of the candidates) for the profile (if any).
+async fn score_products(
+	ctx: &Ctx,
+	mm: &ModelManager,
+	profile: Option<&ProfileSignals>,
+	products: &[Product],
+) -> Result<Vec<ProductRecommendation>> {
+	// -- Load the data of the products.
+	let product_ids: Vec<i64> = products.iter().map(|p| p.id).collect();
+	// Note: The hidden review/claim data is left out (see `ProductBmc::update_visibility`).
+	let review_product_ids: Vec<i64> = products
+		.iter()
+		.filter(|p| p.review_visibility)
+		.map(|p| p.id)
+		.collect();
+	let claim_product_ids: Vec<i64> = products
+		.iter()
+		.filter(|p| p.claim_historical_data_visibility)
+		.map(|p| p.id)
+		.collect();
+	let company_filter = CompanyFilter {
+		id: Some(
+			OpValInt64::In(products.iter().map(|p| p.company_id).collect()).into(),
+		),
+		..Default::default()
+	};
+	let companies: HashMap<i64, Company> =
+		CompanyBmc::list(ctx, mm, Some(vec![company_filter]), None)
+			.await?
+			.into_iter()
+			.map(|company| (company.id, company))
+			.collect();
+	let ratings: HashMap<i64, _> =
+		ReviewBmc::ratings_by_products(ctx, mm, &review_product_ids)
+			.await?
+			.into_iter()
+			.map(|rating| (rating.product_id, rating))
+			.collect();
+	let claim_metrics: HashMap<i64, ClaimMetrics> = ClaimBmc::metrics_by_products(
+		ctx,
+		mm,
+		&claim_product_ids,
+		ClaimHistoryResolution::OneYear,
+	)
+	.await?
+	.into_iter()
+	.map(|metrics| (metrics.product_id, metrics.metrics))
+	.collect();
+	let mut coverage: HashMap<i64, Vec<_>> = HashMap::new();
+	for item in CoverageItemBmc::list_by_products(ctx, mm, &product_ids).await? {
+		coverage.entry(item.product_id).or_default().push(item);
+	}
+	let mut category_names: HashMap<i64, Vec<String>> = HashMap::new();
+	for name in CategoryBmc::names_by_products(ctx, mm, &product_ids).await? {
+		category_names
+			.entry(name.product_id)
+			.or_default()
+			.push(name.name);
+	}
+
+	// -- Score the products.
+	let recommendations = products
+		.iter()
+		.map(|product| {
+			let mut scored: Vec<(f64, String)> = Vec::new();
+			let items = coverage.get(&product.id).map(Vec::as_slice).unwrap_or(&[]);
+			let benefit_typs: HashSet<CoverageBenefitTyp> =
+				items.iter().map(|item| item.benefit_typ).collect();
+
+			if let Some(profile) = profile {
+				// -- Interests
+				let names = category_names.get(&product.id);
+				let topics = names
+					.into_iter()
+					.flatten()
+					.cloned()
+					.chain(benefit_typs.iter().map(|typ| typ.to_string()));
+				let mut matched: Vec<String> = Vec::new();
+				for topic in topics {
+					let key = normalize(&topic);
+					let is_new = !matched.iter().any(|m| normalize(m) == key);
+					if profile.interests.contains(&key) && is_new {
+						matched.push(topic);
+					}
+				}
+				for topic in matched {
+					scored.push((
+						WEIGHT_INTEREST,
+						format!("Matches your interest in {topic}"),
+					));
+				}
+
+				// -- Dependents
+				if profile.dependents > 0
+					&& benefit_typs.contains(&CoverageBenefitTyp::Death)
+				{
+					scored.push((
+						WEIGHT_DEPENDENTS,
+						format!(
+							"Death benefit protecting your {} dependent(s)",
+							profile.dependents
+						),
+					));
+				}
+
+				// -- Familiarity
+				if profile.is_beginner
+					&& !items.is_empty()
+					&& items.iter().all(|item| item.exclusions.is_empty())
+				{
+					scored.push((
+						WEIGHT_FAMILIARITY,
+						"Simple coverage without exclusions for first-time buyers"
+							.to_string(),
+					));
+				}
+
+				// -- Region
+				let location = companies
+					.get(&product.company_id)
+					.and_then(|company| company.location.as_deref())
+					.map(normalize)
+					.unwrap_or_default();
+				if !profile.region.is_empty() && location.contains(&profile.region) {
+					scored.push((
+						WEIGHT_REGION,
+						"Insurer located in your region".to_string(),
+					));
+				}
+			}
+
+			// Note: The rating and settlement are always scored, but only a reason
+			//       for the well rated (or settling) products.
+			let mut unreasoned_score = 0.;
+
+			// -- Rating
+			let (count_review, rating) = ratings
+				.get(&product.id)
+				.map(|r| (r.count_review, r.rating))
+				.unwrap_or_default();
+			let bayesian_rating =
+				TRUST_SCORE_WEIGHTS.bayesian_rating(rating, count_review);
+			let rating_score = WEIGHT_RATING * (bayesian_rating - 1.) / 4.;
+			if count_review > 0 && rating >= WELL_RATED_MIN {
+				scored.push((
+					rating_score,
+					format!("Rated {rating:.1}/5 from {count_review} review(s)"),
+				));
+			} else {
+				unreasoned_score += rating_score;
+			}
+
+			// -- Settlement
+			let settlement = claim_metrics.get(&product.id).and_then(|metrics| {
+				Some((metrics.settlement_ratio()?, metrics.count_decided))
+			});
+			if let Some((ratio, count_decided)) = settlement {
+				let settlement_score = WEIGHT_SETTLEMENT * ratio;
+				if ratio >= WELL_SETTLED_MIN {
+					scored.push((
+						settlement_score,
+						format!(
+							"Settled {:.0}% of {count_decided} decided claim(s)",
+							ratio * 100.
+						),
+					));
+				} else {
+					unreasoned_score += settlement_score;
+				}
+			}
+
+			scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+			let score = unreasoned_score
+				+ scored.iter().map(|(score, _)| score).sum::<f64>();
+			let reasons: Vec<String> =
+				scored.into_iter().map(|(_, reason)| reason).collect();
+
+			ProductRecommendation {
+				product_id: product.id,
+				score,
+				reason: reasons
+					.first()
+					.cloned()
+					.unwrap_or_else(|| REASON_DEFAULT.to_string()),
+				reasons,
+			}
+		})
+		.collect();
+
+	Ok(recommendations)
+}
+
+/// The (normalized) profile attributes used by the rules.
+struct ProfileSignals {
+	interests: HashSet<String>,
+	is_beginner: bool,
+	region: String,
+	dependents: i32,
+}
+
+impl From<&UserProfile> for ProfileSignals {
+	fn from(profile: &UserProfile) -> Self {
+		ProfileSignals {
+			interests: profile
+				.interests
+				.split([',', ';', '/', '|'])
+				.map(normalize)
+				.filter(|interest| !interest.is_empty())
+				.collect(),
+			is_beginner: normalize(&profile.familiarity) == "beginner",
+			region: normalize(&profile.region),
+			dependents: profile.dependents,
+		}
+	}
+}
+
+/// Lowercase alphanumeric only (e.g., "Critical Illness" and "CriticalIllness" match).
+fn normalize(text: &str) -> String {
+	text.chars()
+		.filter(|c| c.is_alphanumeric())
+		.flat_map(char::to_lowercase)
+		.collect()
+}
+
+// endregion: --- Recommend
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::category::CategoryForCreate;
+	use crate::model::claim::{ClaimForCreate, ClaimStatus};
+	use crate::model::user::{UserBmc, UserForCreate};
+	use crate::model::user_profile::UserProfileForCreate;
+	use lib_utils::time::now_utc;
+	use serial_test::serial;
+	use sqlx::types::time::Date;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_recommend_products_ok_interest_first() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_username = "test_recommend_products_ok_interest_first@example.com";
+		let fx_category =
+			"test_recommend_products_ok_interest_first category 01".to_string();
+		let fx_user_id = UserProfileBmc::create_with_user(
+			&root_ctx,
+			&mm,
+			UserForCreate {
+				username: fx_username.to_string(),
+				pwd_clear: "test_recommend_products_ok pwd 01".to_string(),
+			},
+			UserProfileForCreate {
+				email: fx_username.to_string(),
+				phone: "+62 812 0000 0000".to_string(),
+				full_name: "Test User".to_string(),
+				gender: "Female".to_string(),
+				birth_date: Date::from_calendar_date(1990, time::Month::January, 1)?,
+				address: "Jakarta".to_string(),
+				marital_status: "Single".to_string(),
+				occupation: "Engineer".to_string(),
+				income: 10_000_000.,
+				dependents: 0,
+				region: "DKI Jakarta".to_string(),
+				familiarity: "Beginner".to_string(),
+				interests: format!("Travel, {fx_category}"),
+			},
+		)
+		.await?;
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_recommend_products_ok_interest_first company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_recommend_products_ok_interest_first product 01"],
+		)
+		.await?[0];
+		let fx_category_id = CategoryBmc::create(
+			&root_ctx,
+			&mm,
+			CategoryForCreate {
+				parent_id: None,
+				name: fx_category.clone(),
+			},
+		)
+		.await?;
+		CategoryBmc::add_product(&root_ctx, &mm, fx_category_id, fx_product_id)
+			.await?;
+		let fx_claim_id = ClaimBmc::create(
+			&root_ctx,
+			&mm,
+			ClaimForCreate {
+				product_id: fx_product_id,
+				status: Some(ClaimStatus::Paid),
+				submit_time: now_utc(),
+				decision_time: Some(now_utc()),
+				payment_time: Some(now_utc()),
+			},
+		)
+		.await?;
+		let ctx = Ctx::new(fx_user_id)?;
+
+		// -- Exec
+		let recommendations = recommend_products(&ctx, &mm, Some(3)).await?;
+		let res_over_max =
+			recommend_products(&ctx, &mm, Some(RECOMMENDATION_LIMIT_MAX + 1)).await;
+
+		// -- Check
+		assert!(recommendations.len() <= 3);
+		assert!(
+			matches!(
+				res_over_max,
+				Err(crate::model::Error::ListLimitOverMax {
+					max: RECOMMENDATION_LIMIT_MAX,
+					..
+				})
+			),
+			"ListLimitOverMax not matching"
+		);
+		let first = recommendations
+			.first()
+			.ok_or("Should have recommendations")?;
+		assert_eq!(first.product_id, fx_product_id);
+		assert_eq!(
+			first.reason,
+			format!("Matches your interest in {fx_category}")
+		);
+		assert!(first
+			.reasons
+			.contains(&"Settled 100% of 1 decided claim(s)".to_string()));
+
+		// -- Clean
+		ClaimBmc::delete(&root_ctx, &mm, fx_claim_id).await?;
+		CategoryBmc::delete(&root_ctx, &mm, fx_category_id).await?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+		UserBmc::delete(&root_ctx, &mm, fx_user_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -0,0 +1,1034 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, CommonIden, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::product::ProductBmc;
+use crate::model::review_flag::ReviewFlagBmc;
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use crate::model::{ListCursorMeta, ListCursorOptions, ListMeta};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, IntoSeaError, ListOptions, OpValValue, OpValsInt64, OpValsString,
+	OpValsValue, SeaResult,
+};
+use sea_query::{
+	Alias, ColumnRef, ConditionExpression, Expr, Func, Iden, PostgresQueryBuilder,
+	Query,
+};
+use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- Review Types
+
+/// The moderation state of a review. Only the `Published` reviews are public.
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	PartialEq,
+	Eq,
+	sqlx::Type,
+	derive_more::Display,
+	Deserialize,
+	Serialize,
+)]
+#[sqlx(type_name = "review_state")]
+pub enum ReviewState {
+	Pending,
+	Published,
+	Rejected,
+	Hidden,
+}
+impl From<ReviewState> for sea_query::Value {
+	fn from(val: ReviewState) -> Self {
+		val.to_string().into()
+	}
+}
+
+/// The review author is the `cid` (the user that created the review).
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct Review {
+	pub id: i64,
+	pub product_id: i64,
+
+	pub rating: i16,
+	pub comment: String,
+	pub pros: String,
+	pub cons: String,
+
+	// -- Moderation
+	pub state: ReviewState,
+	/// The reason of the last moderation action (empty if none).
+	pub moderation_reason: String,
+
+	// -- Helpful votes (see `ReviewVoteBmc`)
+	pub count_helpful: i32,
+	pub count_unhelpful: i32,
+	pub helpful_score: f64,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize)]
+pub struct ReviewForCreate {
+	pub product_id: i64,
+	pub rating: i16,
+	pub comment: Option<String>,
+	pub pros: Option<String>,
+	pub cons: Option<String>,
+}
+
+#[derive(Fields, Default, Deserialize)]
+pub struct ReviewForUpdate {
+	pub rating: Option<i16>,
+	pub comment: Option<String>,
+	pub pros: Option<String>,
+	pub cons: Option<String>,
+}
+
+/// The author update, which sends the review back to moderation.
+#[derive(Fields)]
+struct ReviewForUpdateByAuthor {
+	rating: Option<i16>,
+	comment: Option<String>,
+	pros: Option<String>,
+	cons: Option<String>,
+	#[field(cast_as = "review_state")]
+	state: ReviewState,
+}
+
+impl From<ReviewForUpdate> for ReviewForUpdateByAuthor {
+	fn from(review_u: ReviewForUpdate) -> Self {
+		Self {
+			rating: review_u.rating,
+			comment: review_u.comment,
+			pros: review_u.pros,
+			cons: review_u.cons,
+			state: ReviewState::Pending,
+		}
+	}
+}
+
+#[derive(Fields)]
+struct ReviewForModerate {
+	#[field(cast_as = "review_state")]
+	state: ReviewState,
+	moderation_reason: String,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ReviewFilter {
+	pub id: Option<OpValsInt64>,
+	pub product_id: Option<OpValsInt64>,
+
+	pub rating: Option<OpValsInt64>,
+	pub comment: Option<OpValsString>,
+
+	#[modql(cast_as = "review_state")]
+	pub state: Option<OpValsString>,
+
+	/// The reviews of the products with visible reviews (see `Product.review_visibility`).
+	/// Note: Set by the list fns only.
+	#[serde(skip)]
+	#[modql(to_sea_condition_fn = "review_product_visible_cond")]
+	pub product_visible: Option<OpValsValue>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+/// Review aggregates of a product (count and average rating).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ProductRating {
+	pub product_id: i64,
+	pub count_review: i64,
+	pub rating: f64,
+}
+
+/// The sort modes of the product reviews.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum ReviewSort {
+	/// The most helpful votes first.
+	MostHelpful,
+	Newest,
+	HighestRating,
+	LowestRating,
+	/// The best Wilson score of the helpful votes first (see `wilson_score`).
+	Wilson,
+}
+
+impl ReviewSort {
+	/// Note: The ties are broken by newest first.
+	fn order_bys(self) -> Vec<&'static str> {
+		match self {
+			Self::MostHelpful => vec!["!count_helpful", "!id"],
+			Self::Newest => vec!["!id"],
+			Self::HighestRating => vec!["!rating", "!id"],
+			Self::LowestRating => vec!["rating", "!id"],
+			Self::Wilson => vec!["!helpful_score", "!id"],
+		}
+	}
+}
+
+/// A review of the moderation queue, with its count of unresolved flags.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ReviewQueued {
+	#[sqlx(flatten)]
+	#[serde(flatten)]
+	pub review: Review,
+	pub count_flag: i64,
+}
+
+#[derive(Iden)]
+enum ReviewIden {
+	ProductId,
+	Rating,
+	State,
+}
+
+// endregion: --- Review Types
+
+// region:    --- ReviewBmc
+
+const RATING_MIN: i16 = 1;
+const RATING_MAX: i16 = 5;
+
+const QUEUE_LIMIT_DEFAULT: i64 = 100;
+const QUEUE_LIMIT_MAX: i64 = 1000;
+
+/// The pending reviews and the reviews with unresolved flags,
+/// most flagged first (then oldest first).
+const SQL_QUEUE: &str = r#"
+SELECT r.*, COUNT(f.id) AS count_flag
+FROM review r
+	LEFT JOIN review_flag f ON f.review_id = r.id AND NOT f.resolved
+WHERE NOT r.deleted
+GROUP BY r.id
+HAVING r.state = 'Pending' OR COUNT(f.id) > 0
+ORDER BY count_flag DESC, r.id
+LIMIT $1
+"#;
+
+pub struct ReviewBmc;
+
+impl DbBmc for ReviewBmc {
+	const TABLE: &'static str = "review";
+
+	fn has_soft_delete() -> bool {
+		true
+	}
+}
+
+impl ReviewBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		review_c: ReviewForCreate,
+	) -> Result<i64> {
+		validate_rating(review_c.rating)?;
+		let product_id = review_c.product_id;
+
+		// Fails if the product does not exist (or is deleted).
+		ProductBmc::get(ctx, mm, product_id).await?;
+
+		base::create::<Self, _>(ctx, mm, review_c)
+			.await
+			.map_err(|model_error| {
+				Error::resolve_unique_violation(
+					model_error,
+					Some(|table: &str, constraint: &str| {
+						if table == "review" && constraint.contains("product_id") {
+							Some(Error::ReviewAlreadyExists { product_id })
+						} else {
+							None // Error::UniqueViolation will be created by resolve_unique_violation
+						}
+					}),
+				)
+			})
+	}
+
+	/// Fails with `Error::EntityNotFound` if the review is not published, or with
+	/// `Error::ProductReviewsHidden` if the product reviews are hidden, unless
+	/// the ctx user is its author or a moderator (see `UserTyp::MODERATORS`).
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Review> {
+		let review: Review = base::get::<Self, _>(ctx, mm, id).await?;
+		if review.cid == ctx.user_id() {
+			return Ok(review);
+		}
+		let product = ProductBmc::get(ctx, mm, review.product_id).await?;
+		let is_published = review.state == ReviewState::Published;
+		if is_published && product.review_visibility {
+			return Ok(review);
+		}
+
+		match UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await {
+			Err(Error::UserNotPrivileged { .. }) if !is_published => {
+				Err(Error::EntityNotFound {
+					entity: Self::TABLE,
+					id,
+				})
+			}
+			Err(Error::UserNotPrivileged { .. }) => {
+				Err(Error::ProductReviewsHidden {
+					product_id: product.id,
+				})
+			}
+			res => res.map(|_| review),
+		}
+	}
+
+	/// Note: Only the published reviews of the products with visible reviews
+	///       (as all the list fns below, except `list_moderation_queue`,
+	///       and `list_including_deleted` for the moderators).
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Review>> {
+		let filter = published_only(filter);
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	/// Note: All the review states for the moderators (see `UserTyp::MODERATORS`),
+	///       only the published reviews for the other users.
+	pub async fn list_including_deleted(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<Review>> {
+		let filter = match UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await {
+			Err(Error::UserNotPrivileged { .. }) => published_only(filter),
+			res => {
+				res?;
+				filter
+			}
+		};
+		base::list_including_deleted::<Self, _, _>(ctx, mm, filter, list_options)
+			.await
+	}
+
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<Review>, ListMeta)> {
+		let filter = published_only(filter);
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	pub async fn list_by_cursor(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewFilter>>,
+		cursor_options: Option<ListCursorOptions>,
+	) -> Result<(Vec<Review>, ListCursorMeta)> {
+		let filter = published_only(filter);
+		base::list_by_cursor::<Self, _, _>(ctx, mm, filter, cursor_options).await
+	}
+
+	pub async fn count(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewFilter>>,
+	) -> Result<i64> {
+		let filter = published_only(filter);
+		base::count::<Self, _>(ctx, mm, filter).await
+	}
+
+	/// Returns the (published) reviews of the product,
+	/// in the `sort` order (insertion order if None).
+	/// Fails with `Error::ProductReviewsHidden` if the product reviews are hidden.
+	pub async fn list_by_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+		sort: Option<ReviewSort>,
+	) -> Result<Vec<Review>> {
+		let product = ProductBmc::get(ctx, mm, product_id).await?;
+		if !product.review_visibility {
+			return Err(Error::ProductReviewsHidden { product_id });
+		}
+
+		let filter = ReviewFilter {
+			product_id: Some(product_id.into()),
+			..Default::default()
+		};
+		let list_options = sort.map(|sort| ListOptions {
+			order_bys: Some(sort.order_bys().into()),
+			..Default::default()
+		});
+		Self::list(ctx, mm, Some(vec![filter]), list_options).await
+	}
+
+	/// Returns the published review count and average rating for each of the given products.
+	/// Products without published reviews are not part of the result.
+	pub async fn ratings_by_products(
+		_ctx: &Ctx,
+		mm: &ModelManager,
+		product_ids: &[i64],
+	) -> Result<Vec<ProductRating>> {
+		// -- Build query
+		let mut query = Query::select();
+		query
+			.from(Self::table_ref())
+			.column(ReviewIden::ProductId)
+			.expr_as(
+				Func::count(Expr::col(CommonIden::Id)),
+				Alias::new("count_review"),
+			)
+			.expr_as(
+				Func::cast_as(
+					Func::avg(Expr::col(ReviewIden::Rating)),
+					Alias::new("float8"),
+				),
+				Alias::new("rating"),
+			)
+			.and_where(Expr::col(ReviewIden::ProductId).is_in(product_ids.to_vec()))
+			.and_where(Expr::col(CommonIden::Deleted).eq(false))
+			.and_where(
+				Expr::col(ReviewIden::State).eq(Expr::val(ReviewState::Published)
+					.as_enum(Alias::new("review_state"))),
+			)
+			.group_by_col(ReviewIden::ProductId);
+
+		// -- Exec query
+		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+		let sqlx_query = sqlx::query_as_with::<_, ProductRating, _>(&sql, values);
+		let ratings = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(ratings)
+	}
+
+	/// Only the review author can update its review,
+	/// which then goes back to `Pending` (moderation).
+	pub async fn update(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		review_u: ReviewForUpdate,
+	) -> Result<()> {
+		if let Some(rating) = review_u.rating {
+			validate_rating(rating)?;
+		}
+		Self::check_author(ctx, mm, id).await?;
+
+		let review_u = ReviewForUpdateByAuthor::from(review_u);
+		base::update::<Self, _>(ctx, mm, id, review_u).await
+	}
+
+	/// Same as `update`, but fails with `Error::ConcurrentModification`
+	/// if the review was modified since `expected_mtime`.
+	pub async fn update_if_unchanged(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		review_u: ReviewForUpdate,
+		expected_mtime: OffsetDateTime,
+	) -> Result<()> {
+		if let Some(rating) = review_u.rating {
+			validate_rating(rating)?;
+		}
+		Self::check_author(ctx, mm, id).await?;
+
+		let review_u = ReviewForUpdateByAuthor::from(review_u);
+		base::update_if_unchanged::<Self, _>(ctx, mm, id, review_u, expected_mtime)
+			.await
+	}
+
+	/// Only the review author can delete its review.
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		Self::check_author(ctx, mm, id).await?;
+
+		base::delete::<Self>(ctx, mm, id).await
+	}
+
+	// -- Moderation
+	// Note: Only the moderators (see `UserTyp::MODERATORS`), and each action is
+	//       recorded in the `entity_change` audit log (by `base::update`).
+
+	/// Returns the reviews to moderate (see `SQL_QUEUE`).
+	pub async fn list_moderation_queue(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		limit: Option<i64>,
+	) -> Result<Vec<ReviewQueued>> {
+		UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await?;
+		let limit = limit.unwrap_or(QUEUE_LIMIT_DEFAULT);
+		if limit < 0 {
+			return Err(Error::ListLimitInvalid { actual: limit });
+		}
+		if limit > QUEUE_LIMIT_MAX {
+			return Err(Error::ListLimitOverMax {
+				max: QUEUE_LIMIT_MAX,
+				actual: limit,
+			});
+		}
+
+		let sqlx_query = sqlx::query_as::<_, ReviewQueued>(SQL_QUEUE).bind(limit);
+		let reviews = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(reviews)
+	}
+
+	/// Publishes the review (the reason is optional).
+	pub async fn approve(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		reason: Option<String>,
+	) -> Result<()> {
+		let reason = reason.unwrap_or_default();
+		Self::moderate(ctx, mm, id, ReviewState::Published, reason).await
+	}
+
+	pub async fn reject(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		reason: String,
+	) -> Result<()> {
+		check_moderation_reason(&reason)?;
+		Self::moderate(ctx, mm, id, ReviewState::Rejected, reason).await
+	}
+
+	/// Hides a (published) review, e.g., after it was flagged.
+	pub async fn hide(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		reason: String,
+	) -> Result<()> {
+		check_moderation_reason(&reason)?;
+		Self::moderate(ctx, mm, id, ReviewState::Hidden, reason).await
+	}
+
+	/// Sets the review state, and resolves its flags.
+	async fn moderate(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		state: ReviewState,
+		reason: String,
+	) -> Result<()> {
+		UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await?;
+		let review_u = ReviewForModerate {
+			state,
+			moderation_reason: reason.trim().to_string(),
+		};
+
+		// Start the transaction
+		let mm = mm.new_with_txn()?;
+
+		mm.dbx().begin_txn().await?;
+
+		base::update::<Self, _>(ctx, &mm, id, review_u).await?;
+		ReviewFlagBmc::resolve_by_review(ctx, &mm, id).await?;
+
+		// Commit the transaction
+		mm.dbx().commit_txn().await?;
+
+		Ok(())
+	}
+
+	/// Fails with `Error::EntityAccessDenied` if the ctx user is not the review author.
+	/// Note: The root ctx (system) is always allowed.
+	async fn check_author(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		let review = Self::get(ctx, mm, id).await?;
+
+		if ctx.user_id() == 0 || review.cid == ctx.user_id() {
+			Ok(())
+		} else {
+			Err(Error::EntityAccessDenied {
+				entity: Self::TABLE,
+				id,
+			})
+		}
+	}
+}
+
+/// Restricts the filter to the published reviews of the products with visible
+/// reviews.
+fn published_only(filter: Option<Vec<ReviewFilter>>) -> Option<Vec<ReviewFilter>> {
+	base::restrict_filters(filter, |filter| ReviewFilter {
+		state: Some(ReviewState::Published.to_string().into()),
+		product_visible: Some(OpValValue::Eq(true.into()).into()),
+		..filter
+	})
+}
+
+/// The `ReviewFilter.product_visible` condition: the reviews of the products
+/// with (`$eq: true`) or without (`$eq: false`) visible reviews.
+fn review_product_visible_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let visible = match op_value {
+		OpValValue::Eq(visible) => visible.as_bool(),
+		_ => None,
+	};
+	let visible = visible.ok_or_else(|| {
+		IntoSeaError::Custom("product_visible only supports $eq a bool".to_string())
+	})?;
+
+	Ok(Expr::cust_with_values(
+		r#""review"."product_id" IN (
+			SELECT p.id FROM product p WHERE p.review_visibility = $1
+		)"#,
+		[visible],
+	)
+	.into())
+}
+
+fn check_moderation_reason(reason: &str) -> Result<()> {
+	if reason.trim().is_empty() {
+		Err(Error::ReviewModerationReasonEmpty)
+	} else {
+		Ok(())
+	}
+}
+
+fn validate_rating(rating: i16) -> Result<()> {
+	if (RATING_MIN..=RATING_MAX).contains(&rating) {
+		Ok(())
+	} else {
+		Err(Error::ReviewRatingOutOfRange {
+			min: RATING_MIN,
+			max: RATING_MAX,
+			actual: rating,
+		})
+	}
+}
+
+// endregion: --- ReviewBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::company::CompanyBmc;
+	use crate::model::entity_change::EntityChangeBmc;
+	use crate::model::product::ProductBmc;
+	use crate::model::review_flag::ReviewFlagForCreate;
+	use crate::model::Error as ModelError;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_err_already_exists() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_create_err_already_exists company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_create_err_already_exists product 01"],
+		)
+		.await?[0];
+		let fx_user_id = _dev_utils::seed_user(
+			&root_ctx,
+			&mm,
+			"test_create_err_already_exists-user-01",
+		)
+		.await?;
+		let ctx = Ctx::new(fx_user_id)?;
+		let review_c = || ReviewForCreate {
+			product_id: fx_product_id,
+			rating: 4,
+			comment: Some("Great insurance!".to_string()),
+			pros: None,
+			cons: None,
+		};
+		let review_id = ReviewBmc::create(&ctx, &mm, review_c()).await?;
+
+		// -- Exec
+		let res = ReviewBmc::create(&ctx, &mm, review_c()).await;
+
+		// -- Check
+		assert!(
+			matches!(
+				res,
+				Err(ModelError::ReviewAlreadyExists { product_id }) if product_id == fx_product_id
+			),
+			"ReviewAlreadyExists not matching"
+		);
+		let review = ReviewBmc::get(&ctx, &mm, review_id).await?;
+		assert_eq!(review.cid, fx_user_id);
+		ReviewBmc::approve(&root_ctx, &mm, review_id, None).await?;
+		let ratings =
+			ReviewBmc::ratings_by_products(&ctx, &mm, &[fx_product_id]).await?;
+		assert_eq!(ratings.len(), 1);
+		assert_eq!(ratings[0].count_review, 1);
+		assert_eq!(ratings[0].rating, 4.);
+
+		// -- Clean
+		ReviewBmc::delete(&ctx, &mm, review_id).await?;
+		UserBmc::delete(&root_ctx, &mm, fx_user_id).await?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_err_deleted_product() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_create_err_deleted_product company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_create_err_deleted_product product 01"],
+		)
+		.await?[0];
+		let fx_user_id = _dev_utils::seed_user(
+			&root_ctx,
+			&mm,
+			"test_create_err_deleted_product-user-01",
+		)
+		.await?;
+		let ctx = Ctx::new(fx_user_id)?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+
+		// -- Exec
+		let res = ReviewBmc::create(
+			&ctx,
+			&mm,
+			ReviewForCreate {
+				product_id: fx_product_id,
+				rating: 4,
+				comment: None,
+				pros: None,
+				cons: None,
+			},
+		)
+		.await;
+
+		// -- Check
+		assert!(
+			matches!(
+				res,
+				Err(ModelError::EntityNotFound { entity: "product", id }) if id == fx_product_id
+			),
+			"EntityNotFound not matching"
+		);
+
+		// -- Clean
+		UserBmc::delete(&root_ctx, &mm, fx_user_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_update_err_not_author() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_update_err_not_author company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_update_err_not_author product 01"],
+		)
+		.await?[0];
+		let fx_user_ids = _dev_utils::seed_users(
+			&root_ctx,
+			&mm,
+			&[
+				"test_update_err_not_author-user-01",
+				"test_update_err_not_author-user-02",
+			],
+		)
+		.await?;
+		let author_ctx = Ctx::new(fx_user_ids[0])?;
+		let other_ctx = Ctx::new(fx_user_ids[1])?;
+		let review_id = ReviewBmc::create(
+			&author_ctx,
+			&mm,
+			ReviewForCreate {
+				product_id: fx_product_id,
+				rating: 3,
+				comment: None,
+				pros: None,
+				cons: None,
+			},
+		)
+		.await?;
+		ReviewBmc::approve(&root_ctx, &mm, review_id, None).await?;
+
+		// -- Exec
+		let res = ReviewBmc::update(
+			&other_ctx,
+			&mm,
+			review_id,
+			ReviewForUpdate {
+				rating: Some(1),
+				..Default::default()
+			},
+		)
+		.await;
+
+		// -- Check
+		assert!(
+			matches!(res, Err(ModelError::EntityAccessDenied { entity: "review", id }) if id == review_id),
+			"EntityAccessDenied not matching"
+		);
+		let review = ReviewBmc::get(&author_ctx, &mm, review_id).await?;
+		assert_eq!(review.rating, 3);
+
+		// -- Clean
+		ReviewBmc::delete(&author_ctx, &mm, review_id).await?;
+		_dev_utils::clean_users(&root_ctx, &mm, "test_update_err_not_author")
+			.await?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_get_err_not_published() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_get_err_not_published company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_get_err_not_published product 01"],
+		)
+		.await?[0];
+		let fx_user_ids = _dev_utils::seed_users(
+			&root_ctx,
+			&mm,
+			&[
+				"test_get_err_not_published-user-01",
+				"test_get_err_not_published-user-02",
+			],
+		)
+		.await?;
+		let author_ctx = Ctx::new(fx_user_ids[0])?;
+		let other_ctx = Ctx::new(fx_user_ids[1])?;
+		let review_id = ReviewBmc::create(
+			&author_ctx,
+			&mm,
+			ReviewForCreate {
+				product_id: fx_product_id,
+				rating: 2,
+				comment: None,
+				pros: None,
+				cons: None,
+			},
+		)
+		.await?;
+		let filter = || {
+			Some(vec![ReviewFilter {
+				product_id: Some(fx_product_id.into()),
+				..Default::default()
+			}])
+		};
+
+		// -- Exec
+		let res = ReviewBmc::get(&other_ctx, &mm, review_id).await;
+		let other_reviews =
+			ReviewBmc::list_including_deleted(&other_ctx, &mm, filter(), None)
+				.await?;
+
+		// -- Check
+		assert!(
+			matches!(res, Err(ModelError::EntityNotFound { entity: "review", id }) if id == review_id),
+			"EntityNotFound not matching"
+		);
+		assert!(other_reviews.is_empty());
+		let review = ReviewBmc::get(&author_ctx, &mm, review_id).await?;
+		assert_eq!(review.state, ReviewState::Pending);
+		let reviews =
+			ReviewBmc::list_including_deleted(&root_ctx, &mm, filter(), None)
+				.await?;
+		assert_eq!(reviews.len(), 1);
+
+		// -- Clean
+		ReviewBmc::delete(&author_ctx, &mm, review_id).await?;
+		_dev_utils::clean_users(&root_ctx, &mm, "test_get_err_not_published")
+			.await?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_moderation_ok() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_moderation_ok company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_moderation_ok product 01"],
+		)
+		.await?[0];
+		let fx_user_ids = _dev_utils::seed_users(
+			&root_ctx,
+			&mm,
+			&[
+				"test_moderation_ok-user-01",
+				"test_moderation_ok-user-02",
+				"test_moderation_ok-moderator-01",
+			],
+		)
+		.await?;
+		UserBmc::update_typ(&root_ctx, &mm, fx_user_ids[2], UserTyp::Moderator)
+			.await?;
+		let author_ctx = Ctx::new(fx_user_ids[0])?;
+		let user_ctx = Ctx::new(fx_user_ids[1])?;
+		let moderator_ctx = Ctx::new(fx_user_ids[2])?;
+		let review_id = ReviewBmc::create(
+			&author_ctx,
+			&mm,
+			ReviewForCreate {
+				product_id: fx_product_id,
+				rating: 2,
+				comment: Some("Claim took months".to_string()),
+				pros: None,
+				cons: None,
+			},
+		)
+		.await?;
+
+		// -- Exec & Check - pending, then approved
+		let reviews =
+			ReviewBmc::list_by_product(&user_ctx, &mm, fx_product_id, None).await?;
+		assert!(reviews.is_empty(), "pending review should not be listed");
+		let res = ReviewBmc::approve(&user_ctx, &mm, review_id, None).await;
+		assert!(
+			matches!(res, Err(ModelError::UserNotPrivileged { user_id }) if user_id == fx_user_ids[1]),
+			"UserNotPrivileged not matching"
+		);
+		ReviewBmc::approve(&moderator_ctx, &mm, review_id, None).await?;
+		let reviews =
+			ReviewBmc::list_by_product(&user_ctx, &mm, fx_product_id, None).await?;
+		assert_eq!(reviews.len(), 1);
+
+		// -- Exec & Check - flagged, then hidden
+		ReviewFlagBmc::create(
+			&user_ctx,
+			&mm,
+			ReviewFlagForCreate {
+				review_id,
+				reason: "Spam".to_string(),
+			},
+		)
+		.await?;
+		let queue =
+			ReviewBmc::list_moderation_queue(&moderator_ctx, &mm, None).await?;
+		let queued = queue
+			.iter()
+			.find(|q| q.review.id == review_id)
+			.ok_or("review should be queued")?;
+		assert_eq!(queued.count_flag, 1);
+		let res =
+			ReviewBmc::hide(&moderator_ctx, &mm, review_id, " ".to_string()).await;
+		assert!(
+			matches!(res, Err(ModelError::ReviewModerationReasonEmpty)),
+			"ReviewModerationReasonEmpty not matching"
+		);
+		ReviewBmc::hide(&moderator_ctx, &mm, review_id, "Spam".to_string()).await?;
+		let review = ReviewBmc::get(&moderator_ctx, &mm, review_id).await?;
+		assert_eq!(review.state, ReviewState::Hidden);
+		assert_eq!(review.moderation_reason, "Spam");
+		let reviews =
+			ReviewBmc::list_by_product(&user_ctx, &mm, fx_product_id, None).await?;
+		assert!(reviews.is_empty(), "hidden review should not be listed");
+		let queue =
+			ReviewBmc::list_moderation_queue(&moderator_ctx, &mm, None).await?;
+		assert!(queue.iter().all(|q| q.review.id != review_id));
+		let res_limit =
+			ReviewBmc::list_moderation_queue(&moderator_ctx, &mm, Some(-1)).await;
+		assert!(
+			matches!(res_limit, Err(ModelError::ListLimitInvalid { actual: -1 })),
+			"ListLimitInvalid not matching"
+		);
+
+		// -- Check - audited
+		let changes =
+			EntityChangeBmc::list_by_entity(&root_ctx, &mm, "review", review_id)
+				.await?;
+		let moderator_changes: Vec<_> =
+			changes.iter().filter(|c| c.cid == fx_user_ids[2]).collect();
+		assert_eq!(moderator_changes.len(), 2);
+
+		// -- Clean
+		ReviewBmc::delete(&root_ctx, &mm, review_id).await?;
+		_dev_utils::clean_users(&root_ctx, &mm, "test_moderation_ok").await?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
@@ -0,0 +1,170 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::review::{ReviewBmc, ReviewState};
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::{Error, ListMeta, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, ListOptions, OpValsBool, OpValsInt64, OpValsString, OpValsValue,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- ReviewFlag Types
+
+/// A user report of a review. The reporter is the `cid`.
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct ReviewFlag {
+	pub id: i64,
+	pub review_id: i64,
+
+	pub reason: String,
+	/// Set when a moderator acted on the review (see `ReviewBmc::approve`, ...).
+	pub resolved: bool,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields, Deserialize)]
+pub struct ReviewFlagForCreate {
+	pub review_id: i64,
+	pub reason: String,
+}
+
+#[derive(Fields)]
+struct ReviewFlagForResolve {
+	resolved: bool,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ReviewFlagFilter {
+	pub id: Option<OpValsInt64>,
+	pub review_id: Option<OpValsInt64>,
+
+	pub reason: Option<OpValsString>,
+	pub resolved: Option<OpValsBool>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+// endregion: --- ReviewFlag Types
+
+// region:    --- ReviewFlagBmc
+
+pub struct ReviewFlagBmc;
+
+impl DbBmc for ReviewFlagBmc {
+	const TABLE: &'static str = "review_flag";
+}
+
+impl ReviewFlagBmc {
+	/// Any user can flag a published review (once).
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		review_flag_c: ReviewFlagForCreate,
+	) -> Result<i64> {
+		let review_id = review_flag_c.review_id;
+		let review = ReviewBmc::get(ctx, mm, review_id).await?;
+		if review.state != ReviewState::Published {
+			return Err(Error::EntityNotFound {
+				entity: ReviewBmc::TABLE,
+				id: review_id,
+			});
+		}
+
+		base::create::<Self, _>(ctx, mm, review_flag_c)
+			.await
+			.map_err(|model_error| {
+				Error::resolve_unique_violation(
+					model_error,
+					Some(|table: &str, constraint: &str| {
+						if table == "review_flag" && constraint.contains("review_id")
+						{
+							Some(Error::ReviewFlagAlreadyExists { review_id })
+						} else {
+							None // Error::UniqueViolation will be created by resolve_unique_violation
+						}
+					}),
+				)
+			})
+	}
+
+	/// Only the reporter or a moderator can get a flag.
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<ReviewFlag> {
+		let flag: ReviewFlag = base::get::<Self, _>(ctx, mm, id).await?;
+		if flag.cid != ctx.user_id() {
+			UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await?;
+		}
+
+		Ok(flag)
+	}
+
+	/// Only the moderators can list the flags (the reporters are the `cid`).
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewFlagFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<ReviewFlag>> {
+		UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await?;
+
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	/// Same as `list`, with the list metadata (see `base::list_with_meta`).
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewFlagFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<ReviewFlag>, ListMeta)> {
+		UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await?;
+
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	/// Resolves the (unresolved) flags of the review.
+	/// Note: Called by the `ReviewBmc` moderation actions (which check the user type).
+	pub(in crate::model) async fn resolve_by_review(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		review_id: i64,
+	) -> Result<()> {
+		let filter = ReviewFlagFilter {
+			review_id: Some(review_id.into()),
+			resolved: Some(false.into()),
+			..Default::default()
+		};
+		base::update_many::<Self, _, _>(
+			ctx,
+			mm,
+			vec![filter],
+			ReviewFlagForResolve { resolved: true },
+		)
+		.await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- ReviewFlagBmc
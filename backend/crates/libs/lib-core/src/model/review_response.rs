@@ -0,0 +1,433 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::company_member::CompanyMemberBmc;
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::product::ProductBmc;
+use crate::model::review::{ReviewBmc, ReviewState};
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::{Error, ListMeta, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, IntoSeaError, ListOptions, OpValInt64, OpValValue, OpValsInt64,
+	OpValsValue, SeaResult,
+};
+use sea_query::{ColumnRef, ConditionExpression, Expr};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- ReviewResponse Types
+
+/// The official response of the insurer (`company_id`) to a review.
+/// The `cid` is the company member who wrote it.
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct ReviewResponse {
+	pub id: i64,
+	pub review_id: i64,
+	pub company_id: i64,
+
+	pub body: String,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+/// Note: The company is the one of the reviewed product (see `ReviewResponseBmc::create`).
+#[derive(Deserialize)]
+pub struct ReviewResponseForCreate {
+	pub review_id: i64,
+	pub body: String,
+}
+
+#[derive(Fields)]
+struct ReviewResponseForInsert {
+	review_id: i64,
+	company_id: i64,
+	body: String,
+}
+
+#[derive(Fields, Default, Deserialize)]
+pub struct ReviewResponseForUpdate {
+	pub body: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ReviewResponseFilter {
+	pub id: Option<OpValsInt64>,
+	pub review_id: Option<OpValsInt64>,
+	pub company_id: Option<OpValsInt64>,
+
+	/// The responses the user (`$eq` a user id) can read (see `ReviewResponseBmc::list`).
+	/// Note: Set by the list fns only.
+	#[serde(skip)]
+	#[modql(to_sea_condition_fn = "review_response_readable_cond")]
+	pub readable_by: Option<OpValsValue>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+// endregion: --- ReviewResponse Types
+
+// region:    --- ReviewResponseBmc
+
+pub struct ReviewResponseBmc;
+
+impl DbBmc for ReviewResponseBmc {
+	const TABLE: &'static str = "review_response";
+}
+
+impl ReviewResponseBmc {
+	/// Only a member of the company of the reviewed product can respond
+	/// (once) to a published review.
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		review_response_c: ReviewResponseForCreate,
+	) -> Result<i64> {
+		let ReviewResponseForCreate { review_id, body } = review_response_c;
+		let review = ReviewBmc::get(ctx, mm, review_id).await?;
+		if review.state != ReviewState::Published {
+			return Err(Error::EntityNotFound {
+				entity: ReviewBmc::TABLE,
+				id: review_id,
+			});
+		}
+		let product = ProductBmc::get(ctx, mm, review.product_id).await?;
+		CompanyMemberBmc::check_member(ctx, mm, product.company_id).await?;
+
+		let review_response_fi = ReviewResponseForInsert {
+			review_id,
+			company_id: product.company_id,
+			body,
+		};
+		base::create::<Self, _>(ctx, mm, review_response_fi)
+			.await
+			.map_err(|model_error| {
+				Error::resolve_unique_violation(
+					model_error,
+					Some(|table: &str, constraint: &str| {
+						if table == "review_response"
+							&& constraint.contains("review_id")
+						{
+							Some(Error::ReviewResponseAlreadyExists { review_id })
+						} else {
+							None // Error::UniqueViolation will be created by resolve_unique_violation
+						}
+					}),
+				)
+			})
+	}
+
+	/// Fails as `ReviewBmc::get` on the review if the ctx user cannot get it
+	/// (e.g., not published), unless the ctx user is a member of the response company.
+	pub async fn get(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+	) -> Result<ReviewResponse> {
+		let response: ReviewResponse = base::get::<Self, _>(ctx, mm, id).await?;
+		match CompanyMemberBmc::check_member(ctx, mm, response.company_id).await {
+			Err(Error::EntityAccessDenied { .. }) => {
+				ReviewBmc::get(ctx, mm, response.review_id).await?;
+			}
+			res => res?,
+		}
+
+		Ok(response)
+	}
+
+	/// Note: Only the responses to the published reviews of the products with
+	///       visible reviews, to the reviews of the ctx user, and of the companies
+	///       of the ctx user (all the responses for the moderators).
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewResponseFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<ReviewResponse>> {
+		let filter = Self::readable_only(ctx, mm, filter).await?;
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	/// Same as `list`, with the list metadata (see `base::list_with_meta`).
+	pub async fn list_with_meta(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewResponseFilter>>,
+		list_options: Option<ListOptions>,
+		with_total: bool,
+	) -> Result<(Vec<ReviewResponse>, ListMeta)> {
+		let filter = Self::readable_only(ctx, mm, filter).await?;
+		base::list_with_meta::<Self, _, _>(ctx, mm, filter, list_options, with_total)
+			.await
+	}
+
+	/// Returns the responses of the given reviews (reviews without response are skipped).
+	pub async fn list_by_reviews(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		review_ids: &[i64],
+	) -> Result<Vec<ReviewResponse>> {
+		let filter = ReviewResponseFilter {
+			review_id: Some(OpValInt64::In(review_ids.to_vec()).into()),
+			..Default::default()
+		};
+		Self::list(ctx, mm, Some(vec![filter]), None).await
+	}
+
+	/// Only a member of the response company can update it.
+	pub async fn update(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		review_response_u: ReviewResponseForUpdate,
+	) -> Result<()> {
+		let response = Self::get(ctx, mm, id).await?;
+		CompanyMemberBmc::check_member(ctx, mm, response.company_id).await?;
+
+		base::update::<Self, _>(ctx, mm, id, review_response_u).await
+	}
+
+	/// Same as `update`, but fails with `Error::ConcurrentModification`
+	/// if the response was modified since `expected_mtime`.
+	pub async fn update_if_unchanged(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		review_response_u: ReviewResponseForUpdate,
+		expected_mtime: OffsetDateTime,
+	) -> Result<()> {
+		let response = Self::get(ctx, mm, id).await?;
+		CompanyMemberBmc::check_member(ctx, mm, response.company_id).await?;
+
+		base::update_if_unchanged::<Self, _>(
+			ctx,
+			mm,
+			id,
+			review_response_u,
+			expected_mtime,
+		)
+		.await
+	}
+
+	/// Only a member of the response company can delete it.
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		let response = Self::get(ctx, mm, id).await?;
+		CompanyMemberBmc::check_member(ctx, mm, response.company_id).await?;
+
+		base::delete::<Self>(ctx, mm, id).await
+	}
+
+	/// Restricts the filter to the responses the ctx user can read,
+	/// unless the ctx user is a moderator.
+	async fn readable_only(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<ReviewResponseFilter>>,
+	) -> Result<Option<Vec<ReviewResponseFilter>>> {
+		match UserBmc::check_typ(ctx, mm, UserTyp::MODERATORS).await {
+			Ok(()) => Ok(filter),
+			Err(Error::UserNotPrivileged { .. }) => {
+				Ok(base::restrict_filters(filter, |filter| {
+					ReviewResponseFilter {
+						readable_by: Some(
+							OpValValue::Eq(ctx.user_id().into()).into(),
+						),
+						..filter
+					}
+				}))
+			}
+			Err(ex) => Err(ex),
+		}
+	}
+}
+
+/// The `ReviewResponseFilter.readable_by` condition: the responses to the
+/// published reviews of the products with visible reviews (as `ReviewBmc::list`),
+/// to the reviews of the user (`$eq` a user id), and of the companies of the user.
+fn review_response_readable_cond(
+	_col: &ColumnRef,
+	op_value: OpValValue,
+) -> SeaResult<ConditionExpression> {
+	let user_id = match op_value {
+		OpValValue::Eq(user_id) => user_id.as_i64(),
+		_ => None,
+	};
+	let user_id = user_id.ok_or_else(|| {
+		IntoSeaError::Custom("readable_by only supports $eq a user id".to_string())
+	})?;
+
+	Ok(Expr::cust_with_values(
+		r#"("review_response"."review_id" IN (
+			SELECT r.id FROM review r JOIN product p ON p.id = r.product_id
+			WHERE NOT r.deleted
+				AND (r.cid = $1 OR (r.state = 'Published' AND p.review_visibility))
+		) OR "review_response"."company_id" IN (
+			SELECT m.company_id FROM company_member m WHERE m.user_id = $1
+		))"#,
+		[user_id],
+	)
+	.into())
+}
+
+// endregion: --- ReviewResponseBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::company::CompanyBmc;
+	use crate::model::company_member::CompanyMemberForCreate;
+	use crate::model::review::ReviewForCreate;
+	use crate::model::Error as ModelError;
+	use serial_test::serial;
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_ok_member_only() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let mut fx_company_ids = Vec::new();
+		for name in ["01", "02"] {
+			let company_id = _dev_utils::seed_company(
+				&root_ctx,
+				&mm,
+				&format!("test_create_ok_member_only company {name}"),
+			)
+			.await?;
+			fx_company_ids.push(company_id);
+		}
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_ids[0],
+			&["test_create_ok_member_only product 01"],
+		)
+		.await?[0];
+		let fx_user_ids = _dev_utils::seed_users(
+			&root_ctx,
+			&mm,
+			&[
+				"test_create_ok_member_only-author",
+				"test_create_ok_member_only-staff-01",
+				"test_create_ok_member_only-staff-02",
+			],
+		)
+		.await?;
+		let mut fx_member_ids = Vec::new();
+		for (company_id, user_id) in fx_company_ids.iter().zip(&fx_user_ids[1..]) {
+			let id = CompanyMemberBmc::create(
+				&root_ctx,
+				&mm,
+				CompanyMemberForCreate {
+					company_id: *company_id,
+					user_id: *user_id,
+				},
+			)
+			.await?;
+			fx_member_ids.push(id);
+		}
+		let author_ctx = Ctx::new(fx_user_ids[0])?;
+		let staff_ctx = Ctx::new(fx_user_ids[1])?;
+		let other_staff_ctx = Ctx::new(fx_user_ids[2])?;
+		let review_id = ReviewBmc::create(
+			&author_ctx,
+			&mm,
+			ReviewForCreate {
+				product_id: fx_product_id,
+				rating: 1,
+				comment: Some("Claim rejected without reason".to_string()),
+				pros: None,
+				cons: None,
+			},
+		)
+		.await?;
+		ReviewBmc::approve(&root_ctx, &mm, review_id, None).await?;
+		let response_c = || ReviewResponseForCreate {
+			review_id,
+			body: "Please contact our support".to_string(),
+		};
+
+		// -- Exec
+		let res_author =
+			ReviewResponseBmc::create(&author_ctx, &mm, response_c()).await;
+		let res_other =
+			ReviewResponseBmc::create(&other_staff_ctx, &mm, response_c()).await;
+		let id = ReviewResponseBmc::create(&staff_ctx, &mm, response_c()).await?;
+		let res_twice =
+			ReviewResponseBmc::create(&staff_ctx, &mm, response_c()).await;
+
+		// -- Check
+		let denied = |res: &crate::model::Result<i64>| matches!(res, Err(ModelError::EntityAccessDenied { entity: "company", id }) if *id == fx_company_ids[0]);
+		assert!(denied(&res_author), "author should be denied");
+		assert!(denied(&res_other), "other company staff should be denied");
+		assert!(
+			matches!(res_twice, Err(ModelError::ReviewResponseAlreadyExists { review_id: rid }) if rid == review_id),
+			"ReviewResponseAlreadyExists not matching"
+		);
+		let responses =
+			ReviewResponseBmc::list_by_reviews(&author_ctx, &mm, &[review_id])
+				.await?;
+		assert_eq!(responses.len(), 1);
+		assert_eq!(responses[0].id, id);
+		assert_eq!(responses[0].company_id, fx_company_ids[0]);
+		assert_eq!(responses[0].cid, fx_user_ids[1]);
+
+		// -- Exec & Check - hidden review
+		ReviewBmc::hide(&root_ctx, &mm, review_id, "Spam".to_string()).await?;
+		let res_get = ReviewResponseBmc::get(&other_staff_ctx, &mm, id).await;
+		assert!(
+			matches!(res_get, Err(ModelError::EntityNotFound { entity: "review", id: rid }) if rid == review_id),
+			"EntityNotFound not matching"
+		);
+		let responses =
+			ReviewResponseBmc::list_by_reviews(&other_staff_ctx, &mm, &[review_id])
+				.await?;
+		assert!(
+			responses.is_empty(),
+			"hidden review response should not be listed"
+		);
+		ReviewResponseBmc::get(&staff_ctx, &mm, id).await?;
+		let responses =
+			ReviewResponseBmc::list_by_reviews(&author_ctx, &mm, &[review_id])
+				.await?;
+		assert_eq!(responses.len(), 1, "author should list the response");
+
+		// -- Clean
+		ReviewResponseBmc::delete(&staff_ctx, &mm, id).await?;
+		ReviewBmc::delete(&author_ctx, &mm, review_id).await?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		for id in fx_member_ids {
+			CompanyMemberBmc::delete(&root_ctx, &mm, id).await?;
+		}
+		for company_id in fx_company_ids {
+			CompanyBmc::delete(&root_ctx, &mm, company_id).await?;
+		}
+		_dev_utils::clean_users(&root_ctx, &mm, "test_create_ok_member_only")
+			.await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
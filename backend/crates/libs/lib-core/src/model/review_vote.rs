@@ -0,0 +1,343 @@
+//! The "helpful" votes of the reviews.
+//!
+//! Notes:
+//!   - A vote is a row of `review_vote` (one per user and review), and the review
+//!     `count_helpful`, `count_unhelpful` and `helpful_score` are updated on each vote.
+//!   - The counts are updated without changing the review `mid`/`mtime`,
+//!     so that a vote does not conflict with an update of the author.
+
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::review::{ReviewBmc, ReviewState};
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, OpValsBool, OpValsInt64, OpValsValue};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- ReviewVote Types
+
+/// The vote of the user `cid` on a review.
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct ReviewVote {
+	pub id: i64,
+	pub review_id: i64,
+
+	pub helpful: bool,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields)]
+struct ReviewVoteForCreate {
+	review_id: i64,
+	helpful: bool,
+}
+
+#[derive(Fields)]
+struct ReviewVoteForUpdate {
+	helpful: bool,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct ReviewVoteFilter {
+	pub id: Option<OpValsInt64>,
+	pub review_id: Option<OpValsInt64>,
+
+	pub helpful: Option<OpValsBool>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+// endregion: --- ReviewVote Types
+
+// region:    --- ReviewVoteBmc
+
+/// The z-score of the Wilson score interval (95% confidence).
+const WILSON_Z: f64 = 1.96;
+
+pub struct ReviewVoteBmc;
+
+impl DbBmc for ReviewVoteBmc {
+	const TABLE: &'static str = "review_vote";
+}
+
+impl ReviewVoteBmc {
+	/// Sets the vote of the ctx user on the (published) review.
+	/// Note: The author cannot vote on its own review.
+	pub async fn vote(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		review_id: i64,
+		helpful: bool,
+	) -> Result<()> {
+		// Start the transaction
+		let mm = mm.new_with_txn()?;
+
+		mm.dbx().begin_txn().await?;
+
+		Self::lock_review(ctx, &mm, review_id).await?;
+		match Self::first_by_review(ctx, &mm, review_id).await? {
+			Some(vote) if vote.helpful == helpful => (),
+			Some(vote) => {
+				let vote_u = ReviewVoteForUpdate { helpful };
+				base::update::<Self, _>(ctx, &mm, vote.id, vote_u).await?;
+			}
+			None => {
+				let vote_c = ReviewVoteForCreate { review_id, helpful };
+				base::create::<Self, _>(ctx, &mm, vote_c).await?;
+			}
+		}
+		Self::update_review_counts(&mm, review_id).await?;
+
+		// Commit the transaction
+		mm.dbx().commit_txn().await?;
+
+		Ok(())
+	}
+
+	/// Removes the vote of the ctx user on the review (no-op if none).
+	pub async fn unvote(ctx: &Ctx, mm: &ModelManager, review_id: i64) -> Result<()> {
+		// Start the transaction
+		let mm = mm.new_with_txn()?;
+
+		mm.dbx().begin_txn().await?;
+
+		Self::lock_review(ctx, &mm, review_id).await?;
+		if let Some(vote) = Self::first_by_review(ctx, &mm, review_id).await? {
+			base::delete::<Self>(ctx, &mm, vote.id).await?;
+			Self::update_review_counts(&mm, review_id).await?;
+		}
+
+		// Commit the transaction
+		mm.dbx().commit_txn().await?;
+
+		Ok(())
+	}
+
+	/// Returns the vote of the ctx user on the review.
+	pub async fn first_by_review(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		review_id: i64,
+	) -> Result<Option<ReviewVote>> {
+		let filter = ReviewVoteFilter {
+			review_id: Some(review_id.into()),
+			cid: Some(ctx.user_id().into()),
+			..Default::default()
+		};
+		base::first::<Self, _, _>(ctx, mm, Some(vec![filter]), None).await
+	}
+
+	/// Locks the review row (so that the concurrent votes are serialized),
+	/// and checks that the ctx user can vote on it.
+	async fn lock_review(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		review_id: i64,
+	) -> Result<()> {
+		let sqlx_query = sqlx::query_as::<_, (i64, ReviewState)>(
+			"SELECT cid, state FROM review WHERE id = $1 AND NOT deleted FOR UPDATE",
+		)
+		.bind(review_id);
+		match mm.dbx().fetch_optional(sqlx_query).await? {
+			Some((_, state)) if state != ReviewState::Published => {
+				Err(Error::EntityNotFound {
+					entity: ReviewBmc::TABLE,
+					id: review_id,
+				})
+			}
+			Some((cid, _)) if cid == ctx.user_id() => {
+				Err(Error::EntityAccessDenied {
+					entity: ReviewBmc::TABLE,
+					id: review_id,
+				})
+			}
+			Some(_) => Ok(()),
+			None => Err(Error::EntityNotFound {
+				entity: ReviewBmc::TABLE,
+				id: review_id,
+			}),
+		}
+	}
+
+	async fn update_review_counts(mm: &ModelManager, review_id: i64) -> Result<()> {
+		let sqlx_query = sqlx::query_as::<_, (i64, i64)>(
+			"SELECT COUNT(*) FILTER (WHERE helpful), COUNT(*) FILTER (WHERE NOT helpful)
+			FROM review_vote WHERE review_id = $1",
+		)
+		.bind(review_id);
+		let (count_helpful, count_unhelpful) =
+			mm.dbx().fetch_one(sqlx_query).await?;
+
+		let sqlx_query = sqlx::query(
+			"UPDATE review SET count_helpful = $2, count_unhelpful = $3, helpful_score = $4
+			WHERE id = $1",
+		)
+		.bind(review_id)
+		.bind(count_helpful as i32)
+		.bind(count_unhelpful as i32)
+		.bind(wilson_score(count_helpful, count_unhelpful));
+		mm.dbx().execute(sqlx_query).await?;
+
+		Ok(())
+	}
+}
+
+/// The lower bound of the Wilson score interval of the helpful ratio,
+/// which ranks a 9/10 review above a 1/1 review (0 when no votes).
+pub fn wilson_score(count_helpful: i64, count_unhelpful: i64) -> f64 {
+	let n = (count_helpful + count_unhelpful) as f64;
+	if n == 0. {
+		return 0.;
+	}
+	let p = count_helpful as f64 / n;
+	let z2 = WILSON_Z * WILSON_Z;
+
+	(p + z2 / (2. * n) - WILSON_Z * ((p * (1. - p) + z2 / (4. * n)) / n).sqrt())
+		/ (1. + z2 / n)
+}
+
+// endregion: --- ReviewVoteBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::company::CompanyBmc;
+	use crate::model::product::ProductBmc;
+	use crate::model::review::{ReviewForCreate, ReviewSort};
+	use serial_test::serial;
+
+	#[test]
+	fn test_wilson_score_ok() {
+		assert_eq!(wilson_score(0, 0), 0.);
+		assert!(wilson_score(9, 1) > wilson_score(1, 0));
+		assert!(wilson_score(10, 0) > wilson_score(9, 1));
+		assert!(wilson_score(1, 0) > wilson_score(0, 1));
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_vote_ok_counts_and_sort() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_vote_ok_counts_and_sort company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_vote_ok_counts_and_sort product 01"],
+		)
+		.await?[0];
+		let fx_user_ids = _dev_utils::seed_users(
+			&root_ctx,
+			&mm,
+			&[
+				"test_vote_ok_counts_and_sort-user-01",
+				"test_vote_ok_counts_and_sort-user-02",
+				"test_vote_ok_counts_and_sort-user-03",
+			],
+		)
+		.await?;
+		let ctxs = fx_user_ids
+			.iter()
+			.map(|id| Ctx::new(*id))
+			.collect::<core::result::Result<Vec<_>, _>>()?;
+		let mut review_ids = Vec::new();
+		for (ctx, rating) in ctxs[..2].iter().zip([5, 2]) {
+			let review_id = ReviewBmc::create(
+				ctx,
+				&mm,
+				ReviewForCreate {
+					product_id: fx_product_id,
+					rating,
+					comment: None,
+					pros: None,
+					cons: None,
+				},
+			)
+			.await?;
+			ReviewBmc::approve(&root_ctx, &mm, review_id, None).await?;
+			review_ids.push(review_id);
+		}
+
+		// -- Exec
+		// Review 0: down (user 2, changed from up), Review 1: up (users 1 & 2).
+		ReviewVoteBmc::vote(&ctxs[2], &mm, review_ids[0], true).await?;
+		ReviewVoteBmc::vote(&ctxs[2], &mm, review_ids[0], false).await?;
+		ReviewVoteBmc::vote(&ctxs[0], &mm, review_ids[1], true).await?;
+		ReviewVoteBmc::vote(&ctxs[2], &mm, review_ids[1], true).await?;
+		ReviewVoteBmc::vote(&ctxs[2], &mm, review_ids[1], true).await?;
+		let res_own = ReviewVoteBmc::vote(&ctxs[0], &mm, review_ids[0], true).await;
+
+		// -- Check
+		assert!(
+			matches!(res_own, Err(crate::model::Error::EntityAccessDenied { .. })),
+			"Should be EntityAccessDenied"
+		);
+		let review = ReviewBmc::get(&root_ctx, &mm, review_ids[0]).await?;
+		assert_eq!((review.count_helpful, review.count_unhelpful), (0, 1));
+		let review = ReviewBmc::get(&root_ctx, &mm, review_ids[1]).await?;
+		assert_eq!((review.count_helpful, review.count_unhelpful), (2, 0));
+		assert_eq!(review.helpful_score, wilson_score(2, 0));
+		let sorted = |sort| {
+			ReviewBmc::list_by_product(&root_ctx, &mm, fx_product_id, Some(sort))
+		};
+		let ids = |reviews: Vec<crate::model::review::Review>| -> Vec<i64> {
+			reviews.iter().map(|r| r.id).collect()
+		};
+		let review_ids_rev = vec![review_ids[1], review_ids[0]];
+		assert_eq!(ids(sorted(ReviewSort::MostHelpful).await?), review_ids_rev);
+		assert_eq!(ids(sorted(ReviewSort::Wilson).await?), review_ids_rev);
+		assert_eq!(ids(sorted(ReviewSort::Newest).await?), review_ids_rev);
+		assert_eq!(ids(sorted(ReviewSort::HighestRating).await?), review_ids);
+		assert_eq!(ids(sorted(ReviewSort::LowestRating).await?), review_ids_rev);
+
+		// -- Exec & Check - unvote
+		ReviewVoteBmc::unvote(&ctxs[2], &mm, review_ids[1]).await?;
+		let review = ReviewBmc::get(&root_ctx, &mm, review_ids[1]).await?;
+		assert_eq!((review.count_helpful, review.count_unhelpful), (1, 0));
+
+		// -- Clean
+		for review_id in review_ids {
+			ReviewBmc::delete(&root_ctx, &mm, review_id).await?;
+		}
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+		_dev_utils::clean_users(&root_ctx, &mm, "test_vote_ok_counts_and_sort")
+			.await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
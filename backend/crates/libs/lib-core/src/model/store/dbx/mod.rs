@@ -115,6 +115,10 @@ impl Dbx {
 		&self.db_pool
 	}
 
+	pub fn with_txn(&self) -> bool {
+		self.with_txn
+	}
+
 	pub async fn fetch_one<'q, O, A>(
 		&self,
 		query: QueryAs<'q, Postgres, O, A>,
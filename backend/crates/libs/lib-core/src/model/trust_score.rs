@@ -0,0 +1,807 @@
+//! The composite trust score (0 to 100) of a company or product.
+//!
+//! The score is the weighted sum of the following factors (each from 0 to 1):
+//!
+//!   - Rating: the Bayesian average rating of the published reviews (pulled toward
+//!     the prior mean for few reviews), from 1 star (0) to 5 stars (1).
+//!   - Settlement: the claim settlement ratio over one year (see `ClaimMetrics`).
+//!   - Complaint: one minus the complaint rate (complaints per claim) over one year,
+//!     capped at 0 for more complaints than claims (see `ComplaintRateCount`).
+//!   - Freshness: halves every `freshness_half_life_days` since the latest product
+//!     update, review or claim.
+//!
+//! Notes:
+//!   - A factor without data (e.g., no decided claims, hidden claim data) is left out,
+//!     and the weights of the other factors are scaled up to still sum to 1.
+//!   - The complaint rate is over all the claims of the products, even with hidden
+//!     claim data (only the rate is part of the score, not the claim counts).
+//!   - The weights are versioned (see `TRUST_SCORE_WEIGHTS`), and each snapshot records
+//!     its version, so that a past score can still be explained.
+//!   - The scores are snapshotted daily (see `TrustScoreBmc::snapshot_all`).
+
+use crate::ctx::Ctx;
+use crate::model::base::DbBmc;
+use crate::model::claim::{ClaimBmc, ClaimHistoryResolution};
+use crate::model::company::{CompanyBmc, CompanyWithProducts};
+use crate::model::complaint::ComplaintBmc;
+use crate::model::product::{Product, ProductBmc};
+use crate::model::review::ReviewBmc;
+use crate::model::user::{UserBmc, UserTyp};
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use lib_utils::time::{now_utc, Rfc3339};
+use modql::filter::ListOptions;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use sqlx::types::time::{Date, OffsetDateTime};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use tracing::warn;
+
+/// The number of days of the trust score history.
+pub const TRUST_SCORE_HISTORY_DAYS: i64 = 365;
+/// The max number of days of the trust score history.
+pub const TRUST_SCORE_HISTORY_DAYS_MAX: i64 = 3650;
+
+/// The number of companies snapshotted per page (see `TrustScoreBmc::snapshot_all`).
+const SNAPSHOT_PAGE_SIZE: i64 = 500;
+
+// region:    --- TrustScore Weights
+
+/// The weights of the factors, and the parameters of their values.
+/// Note: A new weighting is a new version (never change a released version).
+#[derive(Debug, Clone, Copy)]
+pub struct TrustScoreWeights {
+	pub version: i32,
+
+	pub rating: f64,
+	pub settlement: f64,
+	pub complaint: f64,
+	pub freshness: f64,
+
+	/// The Bayesian average prior (mean rating, and its weight in reviews).
+	pub rating_prior_mean: f64,
+	pub rating_prior_count: f64,
+	pub freshness_half_life_days: f64,
+}
+
+pub const TRUST_SCORE_WEIGHTS_V1: TrustScoreWeights = TrustScoreWeights {
+	version: 1,
+	rating: 0.4,
+	settlement: 0.3,
+	complaint: 0.2,
+	freshness: 0.1,
+	rating_prior_mean: 3.,
+	rating_prior_count: 10.,
+	freshness_half_life_days: 180.,
+};
+
+/// The weights of the new scores.
+pub const TRUST_SCORE_WEIGHTS: TrustScoreWeights = TRUST_SCORE_WEIGHTS_V1;
+
+impl TrustScoreWeights {
+	/// The Bayesian average of the `rating` over `count_review` reviews
+	/// (the prior mean without reviews).
+	pub fn bayesian_rating(&self, rating: f64, count_review: i64) -> f64 {
+		let count_review = count_review as f64;
+		(self.rating_prior_mean * self.rating_prior_count + rating * count_review)
+			/ (self.rating_prior_count + count_review)
+	}
+
+	pub fn score(&self, inputs: &TrustScoreInputs) -> TrustScore {
+		// -- Compute the factor values (None when no data).
+		let rating = inputs.count_review.map(|count_review| {
+			let rating = self.bayesian_rating(inputs.rating, count_review);
+			((rating - 1.) / 4.).clamp(0., 1.)
+		});
+		let settlement = inputs.settlement_ratio.map(|ratio| ratio.clamp(0., 1.));
+		let complaint = inputs.complaint_rate.map(|rate| (1. - rate).clamp(0., 1.));
+		let freshness = inputs
+			.days_since_activity
+			.map(|days| 0.5_f64.powf(days.max(0.) / self.freshness_half_life_days));
+
+		// -- Scale the weights of the factors with data.
+		let factors = [
+			(TrustFactorKind::Rating, self.rating, rating),
+			(TrustFactorKind::Settlement, self.settlement, settlement),
+			(TrustFactorKind::Complaint, self.complaint, complaint),
+			(TrustFactorKind::Freshness, self.freshness, freshness),
+		];
+		let total_weight: f64 = factors
+			.iter()
+			.filter(|(_, _, value)| value.is_some())
+			.map(|(_, weight, _)| weight)
+			.sum();
+		let factors: Vec<TrustScoreFactor> = factors
+			.into_iter()
+			.map(|(kind, weight, value)| {
+				let weight = match value {
+					Some(_) if total_weight > 0. => weight / total_weight,
+					_ => 0.,
+				};
+				TrustScoreFactor {
+					kind,
+					value,
+					weight,
+					points: 100. * weight * value.unwrap_or(0.),
+				}
+			})
+			.collect();
+
+		TrustScore {
+			score: factors
+				.iter()
+				.map(|f| f.points)
+				.sum::<f64>()
+				.clamp(0., 100.),
+			weights_version: self.version,
+			factors,
+		}
+	}
+}
+
+// endregion: --- TrustScore Weights
+
+// region:    --- TrustScore Types
+
+#[derive(Debug, Clone, Default)]
+pub struct TrustScoreInputs {
+	/// The number of published reviews (`None` when the reviews are hidden).
+	pub count_review: Option<i64>,
+	/// The average rating of the published reviews (ignored without reviews).
+	pub rating: f64,
+	pub settlement_ratio: Option<f64>,
+	pub complaint_rate: Option<f64>,
+	pub days_since_activity: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TrustFactorKind {
+	Rating,
+	Settlement,
+	Complaint,
+	Freshness,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrustScoreFactor {
+	pub kind: TrustFactorKind,
+	/// From 0 to 1 (`None` when no data).
+	pub value: Option<f64>,
+	/// The scaled weight (0 when no data).
+	pub weight: f64,
+	/// The points of the factor in the score (out of 100).
+	pub points: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrustScore {
+	pub score: f64,
+	pub weights_version: i32,
+	pub factors: Vec<TrustScoreFactor>,
+}
+
+/// The trust score of a company (`product_id` None) or product on a given day (UTC).
+#[serde_as]
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct TrustScoreSnapshot {
+	pub id: i64,
+	pub company_id: i64,
+	pub product_id: Option<i64>,
+	#[serde_as(as = "DisplayFromStr")]
+	pub day: Date,
+
+	pub score: f64,
+	pub weights_version: i32,
+	#[sqlx(json)]
+	pub factors: Vec<TrustScoreFactor>,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+impl From<TrustScoreSnapshot> for TrustScore {
+	fn from(snapshot: TrustScoreSnapshot) -> Self {
+		TrustScore {
+			score: snapshot.score,
+			weights_version: snapshot.weights_version,
+			factors: snapshot.factors,
+		}
+	}
+}
+
+// endregion: --- TrustScore Types
+
+// region:    --- TrustScoreBmc
+
+pub struct TrustScoreBmc;
+
+impl DbBmc for TrustScoreBmc {
+	const TABLE: &'static str = "trust_score";
+}
+
+/// Days since the latest update, published review or claim of the product(s).
+///
+/// - `$1` product_id (NULL for all the products of the company)
+/// - `$2` company_id
+const SQL_DAYS_SINCE_ACTIVITY: &str = r#"
+SELECT extract(epoch FROM now() - max(activity_time))::float8 / 86400
+FROM (
+	SELECT product.mtime AS activity_time FROM product
+	WHERE product.company_id = $2 AND ($1::bigint IS NULL OR product.id = $1)
+		AND NOT product.deleted
+	UNION ALL
+	SELECT review.ctime FROM review
+		JOIN product ON product.id = review.product_id
+	WHERE product.company_id = $2 AND ($1::bigint IS NULL OR product.id = $1)
+		AND NOT product.deleted
+		AND NOT review.deleted AND review.state = 'Published'
+	UNION ALL
+	SELECT claim.submit_time FROM claim
+		JOIN product ON product.id = claim.product_id
+	WHERE product.company_id = $2 AND ($1::bigint IS NULL OR product.id = $1)
+		AND NOT product.deleted
+		AND NOT claim.deleted
+) AS activity
+"#;
+
+/// - `$1` company_id, `$2` product_id (NULL for the company score), `$3` day
+/// - `$4` score, `$5` weights_version, `$6` factors
+/// - `$7` user id, `$8` time
+const SQL_UPSERT_SNAPSHOT: &str = r#"
+INSERT INTO trust_score
+	(company_id, product_id, day, score, weights_version, factors,
+	 cid, ctime, mid, mtime)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $7, $8)
+ON CONFLICT (company_id, product_id, day) DO UPDATE
+SET score = EXCLUDED.score,
+	weights_version = EXCLUDED.weights_version,
+	factors = EXCLUDED.factors,
+	mid = EXCLUDED.mid,
+	mtime = EXCLUDED.mtime
+"#;
+
+/// - `$1` company_id, `$2` product_id (NULL for the company score)
+/// - `$3` number of days
+const SQL_HISTORY: &str = r#"
+SELECT * FROM trust_score
+WHERE company_id = $1 AND product_id IS NOT DISTINCT FROM $2
+	AND day > (now() AT TIME ZONE 'UTC')::date - $3::int
+ORDER BY day
+"#;
+
+impl TrustScoreBmc {
+	/// The current trust score of the product.
+	/// Note: The hidden reviews (or claim data) are left out of the score.
+	pub async fn score_by_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+	) -> Result<TrustScore> {
+		let product = ProductBmc::get(ctx, mm, product_id).await?;
+		Self::score_product(ctx, mm, &product).await
+	}
+
+	/// The current trust score of the company (over all its products).
+	/// Note: The hidden reviews (or claim data) are left out of the score.
+	pub async fn score_by_company(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+	) -> Result<TrustScore> {
+		let CompanyWithProducts { company, products } =
+			CompanyBmc::get_with_products(ctx, mm, company_id).await?;
+		Self::score_company(ctx, mm, company.id, &products).await
+	}
+
+	/// The trust score of the day of the product, i.e., its snapshot of the day,
+	/// or its current score if not snapshotted yet (e.g., a new product).
+	pub async fn daily_score_by_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+	) -> Result<TrustScore> {
+		let product = ProductBmc::get(ctx, mm, product_id).await?;
+		match Self::history(mm, product.company_id, Some(product.id), Some(1))
+			.await?
+			.pop()
+		{
+			Some(snapshot) => Ok(snapshot.into()),
+			None => Self::score_product(ctx, mm, &product).await,
+		}
+	}
+
+	/// Same as `daily_score_by_product`, for the company.
+	pub async fn daily_score_by_company(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+	) -> Result<TrustScore> {
+		let company = CompanyBmc::get(ctx, mm, company_id).await?;
+		match Self::history(mm, company.id, None, Some(1)).await?.pop() {
+			Some(snapshot) => Ok(snapshot.into()),
+			None => Self::score_by_company(ctx, mm, company.id).await,
+		}
+	}
+
+	/// The daily snapshots of the product score, oldest first
+	/// (`TRUST_SCORE_HISTORY_DAYS` if no days).
+	/// Fails with `Error::TrustScoreHistoryDaysOutOfRange` if the days are not
+	/// within 1 and `TRUST_SCORE_HISTORY_DAYS_MAX`.
+	pub async fn history_by_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product_id: i64,
+		days: Option<i64>,
+	) -> Result<Vec<TrustScoreSnapshot>> {
+		let product = ProductBmc::get(ctx, mm, product_id).await?;
+		Self::history(mm, product.company_id, Some(product.id), days).await
+	}
+
+	/// The daily snapshots of the company score, oldest first
+	/// (`TRUST_SCORE_HISTORY_DAYS` if no days).
+	/// Fails with `Error::TrustScoreHistoryDaysOutOfRange` if the days are not
+	/// within 1 and `TRUST_SCORE_HISTORY_DAYS_MAX`.
+	pub async fn history_by_company(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+		days: Option<i64>,
+	) -> Result<Vec<TrustScoreSnapshot>> {
+		let company = CompanyBmc::get(ctx, mm, company_id).await?;
+		Self::history(mm, company.id, None, days).await
+	}
+
+	/// Snapshots the current score of every company and product (for the day, UTC),
+	/// and returns the number of snapshots.
+	/// Notes:
+	///   - Only a `Sys` user (or the root ctx) can snapshot (e.g., the daily job).
+	///   - A company failing to snapshot is logged and skipped (not retried).
+	pub async fn snapshot_all(ctx: &Ctx, mm: &ModelManager) -> Result<usize> {
+		UserBmc::check_typ(ctx, mm, &[UserTyp::Sys]).await?;
+
+		let mut count = 0;
+		let mut offset = 0;
+		loop {
+			let list_options = ListOptions {
+				limit: Some(SNAPSHOT_PAGE_SIZE),
+				offset: Some(offset),
+				order_bys: Some("id".into()),
+			};
+			let companies =
+				CompanyBmc::list(ctx, mm, None, Some(list_options)).await?;
+			for company in &companies {
+				match Self::snapshot_company(ctx, mm, company.id).await {
+					Ok(company_count) => count += company_count,
+					Err(ex) => warn!(
+						"{:<12} - snapshot_all - company {} skipped: {ex:?}",
+						"TRUST-SCORE", company.id
+					),
+				}
+			}
+
+			if (companies.len() as i64) < SNAPSHOT_PAGE_SIZE {
+				break;
+			}
+			offset += SNAPSHOT_PAGE_SIZE;
+		}
+
+		Ok(count)
+	}
+
+	/// Snapshots the company and its products, and returns the number of snapshots.
+	async fn snapshot_company(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+	) -> Result<usize> {
+		let CompanyWithProducts { company, products } =
+			CompanyBmc::get_with_products(ctx, mm, company_id).await?;
+
+		let score = Self::score_company(ctx, mm, company.id, &products).await?;
+		Self::snapshot(ctx, mm, company.id, None, &score).await?;
+
+		for product in &products {
+			let score = Self::score_product(ctx, mm, product).await?;
+			Self::snapshot(ctx, mm, company.id, Some(product.id), &score).await?;
+		}
+
+		Ok(1 + products.len())
+	}
+
+	async fn score_product(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		product: &Product,
+	) -> Result<TrustScore> {
+		let mut inputs = TrustScoreInputs::default();
+
+		if product.review_visibility {
+			let rating = ReviewBmc::ratings_by_products(ctx, mm, &[product.id])
+				.await?
+				.pop();
+			inputs.count_review =
+				Some(rating.as_ref().map_or(0, |r| r.count_review));
+			inputs.rating = rating.map_or(0., |r| r.rating);
+		}
+		if product.claim_historical_data_visibility {
+			let resolution = ClaimHistoryResolution::OneYear;
+			let metrics =
+				ClaimBmc::metrics_by_product(ctx, mm, product.id, resolution)
+					.await?;
+			inputs.settlement_ratio = metrics.settlement_ratio();
+		}
+		let rate_count = ComplaintBmc::rate_count_by_product(
+			ctx,
+			mm,
+			product.id,
+			ClaimHistoryResolution::OneYear.months(),
+		)
+		.await?;
+		inputs.complaint_rate = rate_count.rate();
+		inputs.days_since_activity =
+			Self::days_since_activity(mm, Some(product.id), product.company_id)
+				.await?;
+
+		Ok(TRUST_SCORE_WEIGHTS.score(&inputs))
+	}
+
+	async fn score_company(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+		products: &[Product],
+	) -> Result<TrustScore> {
+		let mut inputs = TrustScoreInputs::default();
+
+		// -- The reviews of the products with visible reviews.
+		// Note: No rating factor when all the product reviews are hidden.
+		let product_ids: Vec<i64> = products
+			.iter()
+			.filter(|p| p.review_visibility)
+			.map(|p| p.id)
+			.collect();
+		if !product_ids.is_empty() {
+			let ratings =
+				ReviewBmc::ratings_by_products(ctx, mm, &product_ids).await?;
+			let count_review: i64 = ratings.iter().map(|r| r.count_review).sum();
+			inputs.count_review = Some(count_review);
+			if count_review > 0 {
+				inputs.rating = ratings
+					.iter()
+					.map(|r| r.rating * r.count_review as f64)
+					.sum::<f64>() / count_review as f64;
+			}
+		}
+
+		let resolution = ClaimHistoryResolution::OneYear;
+		let metrics =
+			ClaimBmc::metrics_by_company(ctx, mm, company_id, resolution).await?;
+		let rate_count = ComplaintBmc::rate_count_by_company(
+			ctx,
+			mm,
+			company_id,
+			resolution.months(),
+		)
+		.await?;
+		inputs.settlement_ratio = metrics.settlement_ratio();
+		inputs.complaint_rate = rate_count.rate();
+		inputs.days_since_activity =
+			Self::days_since_activity(mm, None, company_id).await?;
+
+		Ok(TRUST_SCORE_WEIGHTS.score(&inputs))
+	}
+
+	async fn days_since_activity(
+		mm: &ModelManager,
+		product_id: Option<i64>,
+		company_id: i64,
+	) -> Result<Option<f64>> {
+		let sqlx_query =
+			sqlx::query_as::<_, (Option<f64>,)>(SQL_DAYS_SINCE_ACTIVITY)
+				.bind(product_id)
+				.bind(company_id);
+		let (days,) = mm.dbx().fetch_one(sqlx_query).await?;
+
+		Ok(days)
+	}
+
+	async fn snapshot(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		company_id: i64,
+		product_id: Option<i64>,
+		score: &TrustScore,
+	) -> Result<()> {
+		let now = now_utc();
+		let sqlx_query = sqlx::query(SQL_UPSERT_SNAPSHOT)
+			.bind(company_id)
+			.bind(product_id)
+			.bind(now.date())
+			.bind(score.score)
+			.bind(score.weights_version)
+			.bind(Json(&score.factors))
+			.bind(ctx.user_id())
+			.bind(now);
+		mm.dbx().execute(sqlx_query).await?;
+
+		Ok(())
+	}
+
+	async fn history(
+		mm: &ModelManager,
+		company_id: i64,
+		product_id: Option<i64>,
+		days: Option<i64>,
+	) -> Result<Vec<TrustScoreSnapshot>> {
+		let days = days.unwrap_or(TRUST_SCORE_HISTORY_DAYS);
+		let out_of_range = || Error::TrustScoreHistoryDaysOutOfRange {
+			min: 1,
+			max: TRUST_SCORE_HISTORY_DAYS_MAX,
+			actual: days,
+		};
+		if !(1..=TRUST_SCORE_HISTORY_DAYS_MAX).contains(&days) {
+			return Err(out_of_range());
+		}
+		let days = i32::try_from(days).map_err(|_| out_of_range())?;
+
+		let sqlx_query = sqlx::query_as::<_, TrustScoreSnapshot>(SQL_HISTORY)
+			.bind(company_id)
+			.bind(product_id)
+			.bind(days);
+		let snapshots = mm.dbx().fetch_all(sqlx_query).await?;
+
+		Ok(snapshots)
+	}
+}
+
+// endregion: --- TrustScoreBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::base;
+	use crate::model::claim::{ClaimForCreate, ClaimStatus};
+	use crate::model::complaint::{ComplaintCategory, ComplaintForCreate};
+	use crate::model::product::ProductVisibilityForUpdate;
+	use serial_test::serial;
+
+	#[test]
+	fn test_score_ok_left_out_factors() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_inputs = TrustScoreInputs {
+			count_review: Some(10),
+			rating: 5.,
+			settlement_ratio: Some(0.5),
+			complaint_rate: None,
+			days_since_activity: Some(0.),
+		};
+
+		// -- Exec
+		let trust_score = TRUST_SCORE_WEIGHTS_V1.score(&fx_inputs);
+		let no_review = TRUST_SCORE_WEIGHTS_V1.score(&TrustScoreInputs {
+			count_review: Some(0),
+			..fx_inputs.clone()
+		});
+
+		// -- Check
+		// Rating (3 * 10 + 5 * 10) / 20 = 4 stars (0.75), weights scaled by 1 / 0.8.
+		let expected = 100. * (0.4 * 0.75 + 0.3 * 0.5 + 0.1 * 1.) / 0.8;
+		assert!((trust_score.score - expected).abs() < 0.001);
+		assert_eq!(trust_score.weights_version, 1);
+		let complaint = &trust_score.factors[2];
+		assert_eq!(complaint.kind, TrustFactorKind::Complaint);
+		assert_eq!((complaint.value, complaint.weight), (None, 0.));
+		let total_weight: f64 = trust_score.factors.iter().map(|f| f.weight).sum();
+		assert!((total_weight - 1.).abs() < 0.001);
+		// No reviews is the prior mean (3 stars).
+		assert_eq!(no_review.factors[0].value, Some(0.5));
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_snapshot_all_ok_history() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_snapshot_all_ok_history company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&["test_snapshot_all_ok_history product 01"],
+		)
+		.await?[0];
+		let mut fx_claim_ids = Vec::new();
+		for status in [ClaimStatus::Approved, ClaimStatus::Rejected] {
+			let id = ClaimBmc::create(
+				&ctx,
+				&mm,
+				ClaimForCreate {
+					product_id: fx_product_id,
+					status: Some(status),
+					submit_time: now_utc(),
+					decision_time: Some(now_utc()),
+					payment_time: None,
+				},
+			)
+			.await?;
+			fx_claim_ids.push(id);
+		}
+		let fx_complaint_id = ComplaintBmc::create(
+			&ctx,
+			&mm,
+			ComplaintForCreate {
+				company_id: fx_company_id,
+				product_id: Some(fx_product_id),
+				claim_id: None,
+				policy_number: None,
+				category: ComplaintCategory::ClaimDenial,
+				narrative: "test_snapshot_all_ok_history complaint".to_string(),
+				attachments: None,
+			},
+		)
+		.await?;
+		// Only the triaged complaints count.
+		ComplaintBmc::respond(&ctx, &mm, fx_complaint_id, "Reviewed".to_string())
+			.await?;
+
+		// -- Exec
+		TrustScoreBmc::snapshot_all(&ctx, &mm).await?;
+		ProductBmc::update_visibility(
+			&ctx,
+			&mm,
+			fx_product_id,
+			ProductVisibilityForUpdate {
+				review_visibility: None,
+				claim_historical_data_visibility: Some(false),
+			},
+		)
+		.await?;
+		// Same day, replaces the snapshots.
+		TrustScoreBmc::snapshot_all(&ctx, &mm).await?;
+		let product_history =
+			TrustScoreBmc::history_by_product(&ctx, &mm, fx_product_id, None)
+				.await?;
+		let company_history =
+			TrustScoreBmc::history_by_company(&ctx, &mm, fx_company_id, None)
+				.await?;
+		let mut res_days = Vec::new();
+		for days in [0, -1, TRUST_SCORE_HISTORY_DAYS_MAX + 1, i64::MAX] {
+			let res = TrustScoreBmc::history_by_product(
+				&ctx,
+				&mm,
+				fx_product_id,
+				Some(days),
+			)
+			.await;
+			res_days.push((days, res));
+		}
+
+		// -- Check
+		assert_eq!(product_history.len(), 1);
+		assert_eq!(company_history.len(), 1);
+		let snapshot = &product_history[0];
+		assert_eq!(snapshot.product_id, Some(fx_product_id));
+		assert_eq!(snapshot.weights_version, TRUST_SCORE_WEIGHTS.version);
+		let settlement = snapshot
+			.factors
+			.iter()
+			.find(|f| f.kind == TrustFactorKind::Settlement)
+			.ok_or("Should have a settlement factor")?;
+		assert_eq!(settlement.value, None, "claim data now hidden");
+		let complaint = snapshot
+			.factors
+			.iter()
+			.find(|f| f.kind == TrustFactorKind::Complaint)
+			.ok_or("Should have a complaint factor")?;
+		// 1 complaint for 2 claims, even with the claim data hidden.
+		assert_eq!(complaint.value, Some(0.5));
+		assert_eq!(company_history[0].product_id, None);
+		for (days, res) in res_days {
+			assert!(
+				matches!(
+					res,
+					Err(crate::model::Error::TrustScoreHistoryDaysOutOfRange { actual, .. })
+						if actual == days
+				),
+				"TrustScoreHistoryDaysOutOfRange not matching for {days}"
+			);
+		}
+		let current =
+			TrustScoreBmc::score_by_product(&ctx, &mm, fx_product_id).await?;
+		assert!((current.score - snapshot.score).abs() < 0.01);
+		let daily =
+			TrustScoreBmc::daily_score_by_product(&ctx, &mm, fx_product_id).await?;
+		assert_eq!(daily.score, snapshot.score);
+
+		// -- Clean
+		for id in fx_claim_ids {
+			ClaimBmc::delete(&ctx, &mm, id).await?;
+		}
+		base::delete::<ComplaintBmc>(&ctx, &mm, fx_complaint_id).await?;
+		ProductBmc::delete(&ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_score_by_company_ok_reviews_hidden() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_company_id = _dev_utils::seed_company(
+			&ctx,
+			&mm,
+			"test_score_by_company_ok_reviews_hidden company 01",
+		)
+		.await?;
+		let fx_product_ids = _dev_utils::seed_products(
+			&ctx,
+			&mm,
+			fx_company_id,
+			&[
+				"test_score_by_company_ok_reviews_hidden product 01",
+				"test_score_by_company_ok_reviews_hidden product 02",
+			],
+		)
+		.await?;
+
+		// -- Exec
+		let visible =
+			TrustScoreBmc::score_by_company(&ctx, &mm, fx_company_id).await?;
+		for &product_id in &fx_product_ids {
+			ProductBmc::update_visibility(
+				&ctx,
+				&mm,
+				product_id,
+				ProductVisibilityForUpdate {
+					review_visibility: Some(false),
+					claim_historical_data_visibility: None,
+				},
+			)
+			.await?;
+		}
+		let hidden =
+			TrustScoreBmc::score_by_company(&ctx, &mm, fx_company_id).await?;
+
+		// -- Check
+		let rating = &visible.factors[0];
+		assert_eq!(rating.kind, TrustFactorKind::Rating);
+		// No reviews is the prior mean (3 stars).
+		assert_eq!(rating.value, Some(0.5));
+		let rating = &hidden.factors[0];
+		assert_eq!(rating.kind, TrustFactorKind::Rating);
+		assert_eq!((rating.value, rating.weight), (None, 0.));
+
+		// -- Clean
+		for id in fx_product_ids {
+			ProductBmc::delete(&ctx, &mm, id).await?;
+		}
+		CompanyBmc::delete(&ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
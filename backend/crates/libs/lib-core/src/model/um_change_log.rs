@@ -0,0 +1,104 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::ModelManager;
+use crate::model::Result;
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64, OpValsValue};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::FromRow;
+
+// region:    --- UmChangeLog Types
+
+#[derive(Clone, Debug, sqlx::Type, derive_more::Display, Deserialize, Serialize)]
+#[sqlx(type_name = "um_change_action")]
+pub enum UmChangeAction {
+	Delete,
+}
+impl From<UmChangeAction> for sea_query::Value {
+	fn from(val: UmChangeAction) -> Self {
+		val.to_string().into()
+	}
+}
+
+/// A user management action (e.g., user deletion) performed on `user_id`.
+/// The `cid` is the user who performed the action.
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct UmChangeLog {
+	pub id: i64,
+	pub user_id: i64,
+	pub action: UmChangeAction,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Fields)]
+pub struct UmChangeLogForCreate {
+	pub user_id: i64,
+	#[field(cast_as = "um_change_action")]
+	pub action: UmChangeAction,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct UmChangeLogFilter {
+	pub id: Option<OpValsInt64>,
+	pub user_id: Option<OpValsInt64>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+}
+
+// endregion: --- UmChangeLog Types
+
+// region:    --- UmChangeLogBmc
+
+/// Note: The log is append only (no update/delete).
+pub struct UmChangeLogBmc;
+
+impl DbBmc for UmChangeLogBmc {
+	const TABLE: &'static str = "um_change_log";
+}
+
+impl UmChangeLogBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		log_c: UmChangeLogForCreate,
+	) -> Result<i64> {
+		base::create::<Self, _>(ctx, mm, log_c).await
+	}
+
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<UmChangeLogFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<UmChangeLog>> {
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn list_by_user(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		user_id: i64,
+	) -> Result<Vec<UmChangeLog>> {
+		let filter = UmChangeLogFilter {
+			user_id: Some(user_id.into()),
+			..Default::default()
+		};
+		Self::list(ctx, mm, Some(vec![filter]), None).await
+	}
+}
+
+// endregion: --- UmChangeLogBmc
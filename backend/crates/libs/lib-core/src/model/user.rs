@@ -1,6 +1,10 @@
 use crate::ctx::Ctx;
-use crate::model::base::{self, prep_fields_for_update, DbBmc};
+use crate::model::base::{self, DbBmc};
 use crate::model::modql_utils::time_to_sea_value;
+use crate::model::um_change_log::{
+	UmChangeAction, UmChangeLogBmc, UmChangeLogForCreate,
+};
+use crate::model::user_profile::UserProfileBmc;
 use crate::model::ModelManager;
 use crate::model::{Error, Result};
 use lib_auth::pwd::{self, ContentToHash};
@@ -16,12 +20,30 @@ use sqlx::FromRow;
 use uuid::Uuid;
 
 // region:    --- User Types
-#[derive(Clone, Debug, sqlx::Type, derive_more::Display, Deserialize, Serialize)]
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	PartialEq,
+	Eq,
+	sqlx::Type,
+	derive_more::Display,
+	Deserialize,
+	Serialize,
+)]
 #[sqlx(type_name = "user_typ")]
 pub enum UserTyp {
 	Sys,
+	/// Can manage the products of all the companies (e.g., their visibility).
+	Admin,
+	/// Can moderate the user content (e.g., the reviews).
+	Moderator,
 	User,
 }
+impl UserTyp {
+	/// The user types allowed to moderate (see `UserBmc::check_typ`).
+	pub const MODERATORS: &'static [Self] = &[Self::Sys, Self::Moderator];
+}
 impl From<UserTyp> for sea_query::Value {
 	fn from(val: UserTyp) -> Self {
 		val.to_string().into()
@@ -46,6 +68,12 @@ pub struct UserForInsert {
 	pub username: String,
 }
 
+#[derive(Fields)]
+struct UserForUpdateTyp {
+	#[field(cast_as = "user_typ")]
+	typ: UserTyp,
+}
+
 #[derive(Clone, FromRow, Fields, Debug)]
 pub struct UserForLogin {
 	pub id: i64,
@@ -78,9 +106,11 @@ impl UserBy for UserForAuth {}
 //       we use in our specific code.
 #[derive(Iden)]
 enum UserIden {
-	Id,
 	Username,
+	Deleted,
 	Pwd,
+	PwdSalt,
+	TokenSalt,
 }
 
 #[derive(FilterNodes, Deserialize, Default, Debug)]
@@ -105,6 +135,15 @@ pub struct UserBmc;
 
 impl DbBmc for UserBmc {
 	const TABLE: &'static str = "user";
+
+	fn has_soft_delete() -> bool {
+		true
+	}
+
+	/// Note: The `username` is PII, as it is the registration email.
+	fn audit_redacted_fields() -> &'static [&'static str] {
+		&["username", "pwd", "pwd_salt", "token_salt"]
+	}
 }
 
 impl UserBmc {
@@ -159,6 +198,17 @@ impl UserBmc {
 		base::get::<Self, _>(ctx, mm, id).await
 	}
 
+	pub async fn get_including_deleted<E>(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+	) -> Result<E>
+	where
+		E: UserBy,
+	{
+		base::get_including_deleted::<Self, _>(ctx, mm, id).await
+	}
+
 	pub async fn first_by_username<E>(
 		_ctx: &Ctx,
 		mm: &ModelManager,
@@ -172,7 +222,8 @@ impl UserBmc {
 		query
 			.from(Self::table_ref())
 			.columns(E::sea_idens())
-			.and_where(Expr::col(UserIden::Username).eq(username));
+			.and_where(Expr::col(UserIden::Username).eq(username))
+			.and_where(Expr::col(UserIden::Deleted).eq(false));
 
 		// -- Execute query
 		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
@@ -192,6 +243,38 @@ impl UserBmc {
 		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
 	}
 
+	/// Fails with `Error::UserNotPrivileged` if the ctx user type is not one of `typs`.
+	/// Note: The root ctx (system) is always allowed.
+	pub async fn check_typ(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		typs: &[UserTyp],
+	) -> Result<()> {
+		let user_id = ctx.user_id();
+		if user_id == 0 {
+			return Ok(());
+		}
+
+		let user: User = Self::get(ctx, mm, user_id).await?;
+		if typs.contains(&user.typ) {
+			Ok(())
+		} else {
+			Err(Error::UserNotPrivileged { user_id })
+		}
+	}
+
+	/// Only a `Sys` user (or the root ctx) can change the type of a user.
+	pub async fn update_typ(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		id: i64,
+		typ: UserTyp,
+	) -> Result<()> {
+		Self::check_typ(ctx, mm, &[UserTyp::Sys]).await?;
+
+		base::update::<Self, _>(ctx, mm, id, UserForUpdateTyp { typ }).await
+	}
+
 	pub async fn update_pwd(
 		ctx: &Ctx,
 		mm: &ModelManager,
@@ -206,35 +289,56 @@ impl UserBmc {
 		})
 		.await?;
 
+		// -- Update (audited, with the pwd redacted)
+		let fields = SeaFields::new(vec![SeaField::new(UserIden::Pwd, pwd)]);
+		base::update_fields::<Self>(ctx, mm, id, fields).await
+	}
+
+	/// Soft-delete of the user:
+	/// - Set `deleted: true` and change `username` to "DELETED-_user_id_".
+	/// - Clear the password, reset the salts (invalidates the tokens),
+	///   and delete the user profile (PII).
+	/// - The `mid`/`mtime` and the `um_change_log` record who performed the deletion.
+	/// - The `entity_change` log records the scrub (update) then the (soft) delete.
+	///
+	/// A deleted user cannot be found by username and is not listed anymore.
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
 		// -- Prep the data
-		let mut fields = SeaFields::new(vec![SeaField::new(UserIden::Pwd, pwd)]);
-		prep_fields_for_update::<Self>(&mut fields, ctx.user_id());
+		let fields = SeaFields::new(vec![
+			SeaField::new(UserIden::Username, format!("DELETED-{id}")),
+			SeaField::new(UserIden::Pwd, Option::<String>::None),
+			SeaField::new(UserIden::PwdSalt, Uuid::new_v4()),
+			SeaField::new(UserIden::TokenSalt, Uuid::new_v4()),
+		]);
 
-		// -- Build query
-		let fields = fields.for_sea_update();
-		let mut query = Query::update();
-		query
-			.table(Self::table_ref())
-			.values(fields)
-			.and_where(Expr::col(UserIden::Id).eq(id));
+		// Start the transaction
+		let mm = mm.new_with_txn()?;
 
-		// -- Exec query
-		let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
-		let sqlx_query = sqlx::query_with(&sql, values);
-		let _count = mm.dbx().execute(sqlx_query).await?;
+		mm.dbx().begin_txn().await?;
 
-		Ok(())
-	}
+		// -- Scrub and delete the user
+		// Note: Fails with EntityNotFound if not found (or already deleted).
+		base::update_fields::<Self>(ctx, &mm, id, fields).await?;
+		base::delete::<Self>(ctx, &mm, id).await?;
+
+		// -- Scrub the PII and log the action
+		if let Some(profile) = UserProfileBmc::first_by_user(ctx, &mm, id).await? {
+			UserProfileBmc::delete(ctx, &mm, profile.id).await?;
+		}
+		UmChangeLogBmc::create(
+			ctx,
+			&mm,
+			UmChangeLogForCreate {
+				user_id: id,
+				action: UmChangeAction::Delete,
+			},
+		)
+		.await?;
 
-	/// TODO: For User, deletion will require a soft-delete approach:
-	///       - Set `deleted: true`.
-	///       - Change `username` to "DELETED-_user_id_".
-	///       - Clear any other UUIDs or PII (Personally Identifiable Information).
-	///       - The automatically set `mid`/`mtime` will record who performed the deletion.
-	///       - It's likely necessary to record this action in a `um_change_log` (a user management change audit table).
-	///       - Remove or clean up any user-specific assets (messages, etc.).
-	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
-		base::delete::<Self>(ctx, mm, id).await
+		// Commit the transaction
+		mm.dbx().commit_txn().await?;
+
+		Ok(())
 	}
 }
 
@@ -249,6 +353,8 @@ mod tests {
 
 	use super::*;
 	use crate::_dev_utils;
+	use crate::model::entity_change::EntityChangeBmc;
+	use crate::model::Error as ModelError;
 	use serial_test::serial;
 
 	#[serial]
@@ -281,6 +387,59 @@ mod tests {
 		Ok(())
 	}
 
+	#[serial]
+	#[tokio::test]
+	async fn test_delete_ok_soft() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_username = "test_delete_ok_soft-user-01";
+		let fx_admin_id =
+			_dev_utils::seed_user(&root_ctx, &mm, "test_delete_ok_soft-admin-01")
+				.await?;
+		let user_id = _dev_utils::seed_user(&root_ctx, &mm, fx_username).await?;
+		let admin_ctx = Ctx::new(fx_admin_id)?;
+
+		// -- Exec
+		UserBmc::delete(&admin_ctx, &mm, user_id).await?;
+
+		// -- Check
+		let user: Option<UserForAuth> =
+			UserBmc::first_by_username(&root_ctx, &mm, fx_username).await?;
+		assert!(user.is_none(), "deleted user should not be found");
+		let users = _dev_utils::clean_users(&root_ctx, &mm, fx_username).await?;
+		assert_eq!(users, 0, "deleted user should not be listed");
+		let res = UserBmc::get::<User>(&root_ctx, &mm, user_id).await;
+		assert!(res.is_err(), "deleted user should not be found by id");
+		let user: UserForLogin =
+			UserBmc::get_including_deleted(&root_ctx, &mm, user_id).await?;
+		assert_eq!(user.username, format!("DELETED-{user_id}"));
+		assert!(user.pwd.is_none());
+		let logs = UmChangeLogBmc::list_by_user(&root_ctx, &mm, user_id).await?;
+		assert_eq!(logs.len(), 1);
+		assert_eq!(logs[0].cid, fx_admin_id);
+		let changes =
+			EntityChangeBmc::list_by_entity(&root_ctx, &mm, "user", user_id).await?;
+		assert!(!changes.is_empty(), "user changes should be audited");
+		for change in changes {
+			let values = [change.before, change.after];
+			assert!(
+				!serde_json::to_string(&values)?.contains(fx_username),
+				"audited user change should not contain the username"
+			);
+		}
+		let res = UserBmc::delete(&admin_ctx, &mm, user_id).await;
+		assert!(
+			matches!(res, Err(ModelError::EntityNotFound { entity: "user", id }) if id == user_id),
+			"EntityNotFound not matching"
+		);
+
+		// -- Clean
+		UserBmc::delete(&root_ctx, &mm, fx_admin_id).await?;
+
+		Ok(())
+	}
+
 	#[serial]
 	#[tokio::test]
 	async fn test_first_ok_demo1() -> Result<()> {
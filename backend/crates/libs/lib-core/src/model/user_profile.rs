@@ -0,0 +1,433 @@
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::modql_utils::time_to_sea_value;
+use crate::model::user::{UserBmc, UserForCreate};
+use crate::model::ModelManager;
+use crate::model::Result;
+use lib_utils::time::Rfc3339;
+use modql::field::Fields;
+use modql::filter::{
+	FilterNodes, ListOptions, OpValInt64, OpValsInt64, OpValsString, OpValsValue,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use sqlx::types::time::{Date, OffsetDateTime};
+use sqlx::FromRow;
+use std::collections::HashMap;
+
+// region:    --- UserProfile Types
+
+/// The personal information of a user, as collected at registration.
+/// Note: One profile per user (`user_id` is unique).
+#[serde_as]
+#[derive(Debug, Clone, Fields, FromRow, Serialize)]
+pub struct UserProfile {
+	pub id: i64,
+	pub user_id: i64,
+
+	pub email: String,
+	pub phone: String,
+	pub full_name: String,
+	pub gender: String,
+	#[serde_as(as = "DisplayFromStr")]
+	pub birth_date: Date,
+	pub address: String,
+	pub marital_status: String,
+	pub occupation: String,
+	pub income: f64,
+	pub dependents: i32,
+	pub region: String,
+	pub familiarity: String,
+	pub interests: String,
+
+	// -- Timestamps
+	pub cid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub ctime: OffsetDateTime,
+	pub mid: i64,
+	#[serde_as(as = "Rfc3339")]
+	pub mtime: OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct UserProfileForCreate {
+	pub email: String,
+	pub phone: String,
+	pub full_name: String,
+	pub gender: String,
+	pub birth_date: Date,
+	pub address: String,
+	pub marital_status: String,
+	pub occupation: String,
+	pub income: f64,
+	pub dependents: i32,
+	pub region: String,
+	pub familiarity: String,
+	pub interests: String,
+}
+
+#[derive(Fields)]
+struct UserProfileForInsert {
+	user_id: i64,
+	email: String,
+	phone: String,
+	full_name: String,
+	gender: String,
+	birth_date: Date,
+	address: String,
+	marital_status: String,
+	occupation: String,
+	income: f64,
+	dependents: i32,
+	region: String,
+	familiarity: String,
+	interests: String,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct UserProfileFilter {
+	pub id: Option<OpValsInt64>,
+	pub user_id: Option<OpValsInt64>,
+
+	pub email: Option<OpValsString>,
+	pub region: Option<OpValsString>,
+
+	pub cid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub ctime: Option<OpValsValue>,
+	pub mid: Option<OpValsInt64>,
+	#[modql(to_sea_value_fn = "time_to_sea_value")]
+	pub mtime: Option<OpValsValue>,
+}
+
+// endregion: --- UserProfile Types
+
+// region:    --- UserProfileBmc
+
+pub struct UserProfileBmc;
+
+impl DbBmc for UserProfileBmc {
+	const TABLE: &'static str = "user_profile";
+
+	/// All the profile information is PII.
+	fn audit_redacted_fields() -> &'static [&'static str] {
+		&[
+			"email",
+			"phone",
+			"full_name",
+			"gender",
+			"birth_date",
+			"address",
+			"marital_status",
+			"occupation",
+			"income",
+			"dependents",
+			"region",
+			"familiarity",
+			"interests",
+		]
+	}
+}
+
+impl UserProfileBmc {
+	pub async fn create(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		user_id: i64,
+		profile_c: UserProfileForCreate,
+	) -> Result<i64> {
+		let UserProfileForCreate {
+			email,
+			phone,
+			full_name,
+			gender,
+			birth_date,
+			address,
+			marital_status,
+			occupation,
+			income,
+			dependents,
+			region,
+			familiarity,
+			interests,
+		} = profile_c;
+
+		let profile_fi = UserProfileForInsert {
+			user_id,
+			email,
+			phone,
+			full_name,
+			gender,
+			birth_date,
+			address,
+			marital_status,
+			occupation,
+			income,
+			dependents,
+			region,
+			familiarity,
+			interests,
+		};
+
+		base::create::<Self, _>(ctx, mm, profile_fi).await
+	}
+
+	/// Creates the user (see `UserBmc::create`) and its profile
+	/// in the same transaction, and returns the new user id.
+	pub async fn create_with_user(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		user_c: UserForCreate,
+		profile_c: UserProfileForCreate,
+	) -> Result<i64> {
+		// Start the transaction
+		let mm = mm.new_with_txn()?;
+
+		mm.dbx().begin_txn().await?;
+
+		let user_id = UserBmc::create(ctx, &mm, user_c).await?;
+		Self::create(ctx, &mm, user_id, profile_c).await?;
+
+		// Commit the transaction
+		mm.dbx().commit_txn().await?;
+
+		Ok(user_id)
+	}
+
+	pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<UserProfile> {
+		base::get::<Self, _>(ctx, mm, id).await
+	}
+
+	pub async fn list(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		filter: Option<Vec<UserProfileFilter>>,
+		list_options: Option<ListOptions>,
+	) -> Result<Vec<UserProfile>> {
+		base::list::<Self, _, _>(ctx, mm, filter, list_options).await
+	}
+
+	pub async fn first_by_user(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		user_id: i64,
+	) -> Result<Option<UserProfile>> {
+		let filter = UserProfileFilter {
+			user_id: Some(user_id.into()),
+			..Default::default()
+		};
+		let profile = Self::list(ctx, mm, Some(vec![filter]), None)
+			.await?
+			.into_iter()
+			.next();
+
+		Ok(profile)
+	}
+
+	/// Returns the public display name (see `mask_full_name`) of each of the
+	/// given users. Users without a profile are not part of the result.
+	/// Note: Never the username, which is the registration email.
+	pub async fn display_names_by_users(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		user_ids: &[i64],
+	) -> Result<HashMap<i64, String>> {
+		let filter = UserProfileFilter {
+			user_id: Some(OpValInt64::In(user_ids.to_vec()).into()),
+			..Default::default()
+		};
+		let display_names = Self::list(ctx, mm, Some(vec![filter]), None)
+			.await?
+			.into_iter()
+			.map(|profile| (profile.user_id, mask_full_name(&profile.full_name)))
+			.collect();
+
+		Ok(display_names)
+	}
+
+	pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+		base::delete::<Self>(ctx, mm, id).await
+	}
+}
+
+/// Keeps the first name and only the initials of the other names
+/// (e.g. "Budi Santoso" -> "Budi S.").
+fn mask_full_name(full_name: &str) -> String {
+	let mut names = full_name.split_whitespace();
+	let Some(first_name) = names.next() else {
+		return String::new();
+	};
+
+	names.filter_map(|name| name.chars().next()).fold(
+		first_name.to_string(),
+		|mut display_name, initial| {
+			display_name.push(' ');
+			display_name.push(initial);
+			display_name.push('.');
+			display_name
+		},
+	)
+}
+
+// endregion: --- UserProfileBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+	use crate::_dev_utils;
+	use crate::model::company::CompanyBmc;
+	use crate::model::product::ProductBmc;
+	use crate::model::review::{ReviewBmc, ReviewForCreate};
+	use crate::model::Error as ModelError;
+	use serial_test::serial;
+	use time::Month;
+
+	fn fx_birth_date() -> Date {
+		Date::from_calendar_date(1990, Month::January, 31).unwrap()
+	}
+
+	fn fx_profile_c(email: &str) -> UserProfileForCreate {
+		UserProfileForCreate {
+			email: email.to_string(),
+			phone: "+62 812 0000 0000".to_string(),
+			full_name: "Test User".to_string(),
+			gender: "Female".to_string(),
+			birth_date: fx_birth_date(),
+			address: "Jakarta".to_string(),
+			marital_status: "Single".to_string(),
+			occupation: "Engineer".to_string(),
+			income: 10_000_000.,
+			dependents: 0,
+			region: "DKI Jakarta".to_string(),
+			familiarity: "Beginner".to_string(),
+			interests: "Health".to_string(),
+		}
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_create_with_user_err_already_exists() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let ctx = Ctx::root_ctx();
+		let fx_username = "test_create_with_user_err_already_exists@example.com";
+		let user_c = || UserForCreate {
+			username: fx_username.to_string(),
+			pwd_clear: "test_create_with_user pwd 01".to_string(),
+		};
+		let user_id = UserProfileBmc::create_with_user(
+			&ctx,
+			&mm,
+			user_c(),
+			fx_profile_c(fx_username),
+		)
+		.await?;
+
+		// -- Exec
+		let res = UserProfileBmc::create_with_user(
+			&ctx,
+			&mm,
+			user_c(),
+			fx_profile_c(fx_username),
+		)
+		.await;
+
+		// -- Check
+		assert!(
+			matches!(
+				res,
+				Err(ModelError::UserAlreadyExists { ref username }) if username == fx_username
+			),
+			"UserAlreadyExists not matching"
+		);
+		let profile = UserProfileBmc::first_by_user(&ctx, &mm, user_id)
+			.await?
+			.ok_or("Should have a profile")?;
+		assert_eq!(profile.email, fx_username);
+		assert_eq!(profile.birth_date, fx_birth_date());
+
+		// -- Clean
+		UserBmc::delete(&ctx, &mm, user_id).await?;
+
+		Ok(())
+	}
+
+	#[serial]
+	#[tokio::test]
+	async fn test_display_names_by_users_ok_no_email() -> Result<()> {
+		// -- Setup & Fixtures
+		let mm = _dev_utils::init_test().await;
+		let root_ctx = Ctx::root_ctx();
+		let fx_email = "test_display_names_by_users_ok_no_email@example.com";
+		let fx_company_id = _dev_utils::seed_company(
+			&root_ctx,
+			&mm,
+			"test_display_names_by_users_ok_no_email company 01",
+		)
+		.await?;
+		let fx_product_id = _dev_utils::seed_products(
+			&root_ctx,
+			&mm,
+			fx_company_id,
+			&["test_display_names_by_users_ok_no_email product 01"],
+		)
+		.await?[0];
+		let fx_user_id = UserProfileBmc::create_with_user(
+			&root_ctx,
+			&mm,
+			UserForCreate {
+				username: fx_email.to_string(),
+				pwd_clear: "test_display_names_by_users pwd 01".to_string(),
+			},
+			fx_profile_c(fx_email),
+		)
+		.await?;
+		let review_id = ReviewBmc::create(
+			&Ctx::new(fx_user_id)?,
+			&mm,
+			ReviewForCreate {
+				product_id: fx_product_id,
+				rating: 5,
+				comment: None,
+				pros: None,
+				cons: None,
+			},
+		)
+		.await?;
+		ReviewBmc::approve(&root_ctx, &mm, review_id, None).await?;
+
+		// -- Exec
+		let reviews =
+			ReviewBmc::list_by_product(&root_ctx, &mm, fx_product_id, None).await?;
+		let author_ids: Vec<i64> = reviews.iter().map(|r| r.cid).collect();
+		let display_names =
+			UserProfileBmc::display_names_by_users(&root_ctx, &mm, &author_ids)
+				.await?;
+
+		// -- Check
+		assert_eq!(author_ids, vec![fx_user_id]);
+		assert_eq!(
+			display_names.get(&fx_user_id).map(String::as_str),
+			Some("Test U.")
+		);
+		assert!(
+			display_names.values().all(|name| !name.contains(fx_email)),
+			"Display names should not contain the email"
+		);
+
+		// -- Clean
+		ReviewBmc::delete(&root_ctx, &mm, review_id).await?;
+		UserBmc::delete(&root_ctx, &mm, fx_user_id).await?;
+		ProductBmc::delete(&root_ctx, &mm, fx_product_id).await?;
+		CompanyBmc::delete(&root_ctx, &mm, fx_company_id).await?;
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
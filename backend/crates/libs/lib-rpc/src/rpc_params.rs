@@ -7,11 +7,14 @@
 //! `IntoParams` or `IntoDefaultRpcParams` are implemented to ensure these Params conform to the
 //! `RpcRouter` (i.e., `rpc::router`) model.
 
+use lib_core::model::ListCursorOptions;
+use lib_utils::time::Rfc3339;
 use modql::filter::ListOptions;
 use rpc_router::{IntoDefaultRpcParams, IntoParams};
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde_with::{serde_as, OneOrMany};
+use time::OffsetDateTime;
 
 /// Params structure for any RPC Create call.
 #[derive(Deserialize)]
@@ -22,14 +25,55 @@ pub struct ParamsForCreate<D> {
 impl<D> IntoParams for ParamsForCreate<D> where D: DeserializeOwned + Send {}
 
 /// Params structure for any RPC Update call.
+///
+/// When `expected_mtime` is given (the `mtime` of the entity as last read),
+/// the update fails with `ConcurrentModification` if the entity was modified since.
+#[serde_as]
 #[derive(Deserialize)]
 pub struct ParamsForUpdate<D> {
 	pub id: i64,
 	pub data: D,
+	#[serde_as(as = "Option<Rfc3339>")]
+	#[serde(default)]
+	pub expected_mtime: Option<OffsetDateTime>,
 }
 
 impl<D> IntoParams for ParamsForUpdate<D> where D: DeserializeOwned + Send {}
 
+/// Params structure for any RPC Update Many call.
+/// All the entities matching the `filters` are updated with the same `data`.
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsForUpdateMany<F, D>
+where
+	F: DeserializeOwned,
+{
+	#[serde_as(deserialize_as = "OneOrMany<_>")]
+	pub filters: Vec<F>,
+	pub data: D,
+}
+
+impl<F, D> IntoParams for ParamsForUpdateMany<F, D>
+where
+	F: DeserializeOwned + Send,
+	D: DeserializeOwned + Send,
+{
+}
+
+/// Params structure for any RPC Delete Many call.
+/// All the entities matching the `filters` are deleted.
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsForDeleteMany<F>
+where
+	F: DeserializeOwned,
+{
+	#[serde_as(deserialize_as = "OneOrMany<_>")]
+	pub filters: Vec<F>,
+}
+
+impl<F> IntoParams for ParamsForDeleteMany<F> where F: DeserializeOwned + Send {}
+
 /// Params structure for any RPC Update call.
 #[derive(Deserialize)]
 pub struct ParamsIded {
@@ -47,9 +91,31 @@ where
 	#[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
 	pub filters: Option<Vec<F>>,
 	pub list_options: Option<ListOptions>,
+	/// When true, the result `meta` includes the `total` count (extra count query).
+	#[serde(default)]
+	pub with_total: bool,
 }
 
 impl<D> IntoDefaultRpcParams for ParamsList<D> where
 	D: DeserializeOwned + Send + Default
 {
 }
+
+/// Params structure for any RPC List by cursor call (keyset pagination).
+///
+/// The `cursor_options.cursor` is the opaque `meta.next_cursor` of the previous page.
+#[serde_as]
+#[derive(Deserialize, Default)]
+pub struct ParamsListCursor<F>
+where
+	F: DeserializeOwned,
+{
+	#[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
+	pub filters: Option<Vec<F>>,
+	pub cursor_options: Option<ListCursorOptions>,
+}
+
+impl<D> IntoDefaultRpcParams for ParamsListCursor<D> where
+	D: DeserializeOwned + Send + Default
+{
+}
@@ -1,30 +1,52 @@
 //! The `lib_rpc::response` module normalizes the JSON-RPC `.result` format for various
 //! JSON-RPC APIs.
 //!
-//! The primary type is the simple DataRpcResult, which contains only a `data` property.
+//! The primary type is the simple DataRpcResult, which contains a `data` property,
+//! and, for lists, a `meta` property with the pagination information
+//! (offset, limit, has_more, and the optional total, or for the cursor lists,
+//! limit and next_cursor).
 //!
 //! Notes:
-//!   - In the future, we may introduce types like `DataRpcResult` that include metadata
-//!     about the returned list data (e.g., pagination information).
 //!   - Although the struct is named with `Result`, it is not a typical Rust result. Instead,
 //!     it represents the `.result` property of a JSON-RPC response.
 //!
 
+use lib_core::model::ListMeta;
 use serde::Serialize;
 
 #[derive(Serialize)]
-pub struct DataRpcResult<T>
+pub struct DataRpcResult<T, M = ListMeta>
 where
 	T: Serialize,
+	M: Serialize,
 {
 	data: T,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	meta: Option<M>,
 }
 
-impl<T> From<T> for DataRpcResult<T>
+impl<T, M> DataRpcResult<T, M>
 where
 	T: Serialize,
+	M: Serialize,
+{
+	pub fn with_meta(data: T, meta: M) -> Self {
+		Self {
+			data,
+			meta: Some(meta),
+		}
+	}
+}
+
+impl<T, M> From<T> for DataRpcResult<T, M>
+where
+	T: Serialize,
+	M: Serialize,
 {
 	fn from(val: T) -> Self {
-		Self { data: val }
+		Self {
+			data: val,
+			meta: None,
+		}
 	}
 }
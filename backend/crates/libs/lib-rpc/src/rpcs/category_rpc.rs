@@ -0,0 +1,71 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::category::{
+	Category, CategoryBmc, CategoryFilter, CategoryForCreate, CategoryForUpdate,
+};
+use rpc_router::IntoParams;
+use serde::Deserialize;
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_category,
+		get_category,
+		list_categorys,
+		list_categorys_by_cursor,
+		update_category,
+		delete_category,
+		list_category_subtree,
+		add_category_product,
+		remove_category_product,
+	)
+}
+
+generate_common_rpc_fns!(
+	Bmc: CategoryBmc,
+	Entity: Category,
+	ForCreate: CategoryForCreate,
+	ForUpdate: CategoryForUpdate,
+	Filter: CategoryFilter,
+	Suffix: category,
+	WriteCheck: CategoryBmc
+);
+
+/// Params for the product/category (many-to-many) rpc functions.
+#[derive(Deserialize)]
+pub struct ParamsCategoryProduct {
+	pub id: i64,
+	pub product_id: i64,
+}
+impl IntoParams for ParamsCategoryProduct {}
+
+/// Returns the category and all its descendants (the category first).
+pub async fn list_category_subtree(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<Vec<Category>>> {
+	let categories = CategoryBmc::list_subtree(&ctx, &mm, params.id).await?;
+	Ok(categories.into())
+}
+
+pub async fn add_category_product(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsCategoryProduct,
+) -> Result<DataRpcResult<Vec<Category>>> {
+	let ParamsCategoryProduct { id, product_id } = params;
+	CategoryBmc::add_product(&ctx, &mm, id, product_id).await?;
+	let categories = CategoryBmc::list_by_product(&ctx, &mm, product_id).await?;
+	Ok(categories.into())
+}
+
+pub async fn remove_category_product(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsCategoryProduct,
+) -> Result<DataRpcResult<Vec<Category>>> {
+	let ParamsCategoryProduct { id, product_id } = params;
+	CategoryBmc::remove_product(&ctx, &mm, id, product_id).await?;
+	let categories = CategoryBmc::list_by_product(&ctx, &mm, product_id).await?;
+	Ok(categories.into())
+}
@@ -0,0 +1,38 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::claim::{
+	Claim, ClaimBmc, ClaimFilter, ClaimForCreate, ClaimForUpdate,
+};
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_claim,
+		get_claim,
+		list_claims,
+		list_claims_by_cursor,
+		update_claim,
+		delete_claim,
+		// -- Bulk
+		create_claims,
+		update_claims,
+		delete_claims,
+	)
+}
+
+generate_common_rpc_fns!(
+	Bmc: ClaimBmc,
+	Entity: Claim,
+	ForCreate: ClaimForCreate,
+	ForUpdate: ClaimForUpdate,
+	Filter: ClaimFilter,
+	Suffix: claim,
+	WriteCheck: ClaimBmc
+);
+
+generate_bulk_rpc_fns!(
+	Bmc: ClaimBmc,
+	ForCreate: ClaimForCreate,
+	ForUpdate: ClaimForUpdate,
+	Filter: ClaimFilter,
+	Suffix: claim
+);
@@ -0,0 +1,103 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::company::{
+	Company, CompanyBmc, CompanyFilter, CompanyForCreate, CompanyForUpdate,
+	CompanyWithProducts,
+};
+use lib_core::model::company_member::{
+	CompanyMember, CompanyMemberBmc, CompanyMemberFilter, CompanyMemberForCreate,
+};
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_company,
+		get_company,
+		get_company_with_products,
+		list_companys,
+		list_companys_by_cursor,
+		update_company,
+		delete_company,
+		// -- Bulk
+		create_companys,
+		update_companys,
+		delete_companys,
+		// -- Members
+		create_company_member,
+		list_company_members,
+		delete_company_member,
+	)
+}
+
+generate_common_rpc_fns!(
+	Bmc: CompanyBmc,
+	Entity: Company,
+	ForCreate: CompanyForCreate,
+	ForUpdate: CompanyForUpdate,
+	Filter: CompanyFilter,
+	Suffix: company,
+	WriteCheck: CompanyBmc
+);
+
+generate_bulk_rpc_fns!(
+	Bmc: CompanyBmc,
+	ForCreate: CompanyForCreate,
+	ForUpdate: CompanyForUpdate,
+	Filter: CompanyFilter,
+	Suffix: company
+);
+
+pub async fn get_company_with_products(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<CompanyWithProducts>> {
+	let company = CompanyBmc::get_with_products(&ctx, &mm, params.id).await?;
+	Ok(company.into())
+}
+
+// -- Members
+// Note: Only a `Sys` user can add or remove a member, and only an admin or
+//       a member of the same company can get or list them (see `CompanyMemberBmc`).
+
+pub async fn create_company_member(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForCreate<CompanyMemberForCreate>,
+) -> Result<DataRpcResult<CompanyMember>> {
+	let ParamsForCreate { data } = params;
+	let id = CompanyMemberBmc::create(&ctx, &mm, data).await?;
+	let member = CompanyMemberBmc::get(&ctx, &mm, id).await?;
+	Ok(member.into())
+}
+
+pub async fn list_company_members(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<CompanyMemberFilter>,
+) -> Result<DataRpcResult<Vec<CompanyMember>>> {
+	let ParamsList {
+		filters,
+		list_options,
+		with_total,
+	} = params;
+	let (members, meta) = CompanyMemberBmc::list_with_meta(
+		&ctx,
+		&mm,
+		filters,
+		list_options,
+		with_total,
+	)
+	.await?;
+	Ok(DataRpcResult::with_meta(members, meta))
+}
+
+pub async fn delete_company_member(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<CompanyMember>> {
+	let ParamsIded { id } = params;
+	let member = CompanyMemberBmc::get(&ctx, &mm, id).await?;
+	CompanyMemberBmc::delete(&ctx, &mm, id).await?;
+	Ok(member.into())
+}
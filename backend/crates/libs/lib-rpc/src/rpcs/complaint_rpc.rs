@@ -0,0 +1,90 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::complaint::{
+	Complaint, ComplaintBmc, ComplaintFilter, ComplaintForCreate,
+};
+use rpc_router::IntoParams;
+use serde::Deserialize;
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_complaint,
+		get_complaint,
+		list_complaints,
+		// -- Lifecycle
+		escalate_complaint,
+		resolve_complaint,
+	)
+}
+
+// Note: The complainant (or a moderator) side, the insurer staff side is
+//       the `insurer_rpc` namespace.
+
+pub async fn create_complaint(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForCreate<ComplaintForCreate>,
+) -> Result<DataRpcResult<Complaint>> {
+	let ParamsForCreate { data } = params;
+	let id = ComplaintBmc::create(&ctx, &mm, data).await?;
+	let complaint = ComplaintBmc::get(&ctx, &mm, id).await?;
+	Ok(complaint.into())
+}
+
+pub async fn get_complaint(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<Complaint>> {
+	let ParamsIded { id } = params;
+	let complaint = ComplaintBmc::get(&ctx, &mm, id).await?;
+	Ok(complaint.into())
+}
+
+/// The complaints of the ctx user (all the complaints for a moderator).
+pub async fn list_complaints(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<ComplaintFilter>,
+) -> Result<DataRpcResult<Vec<Complaint>>> {
+	let ParamsList {
+		filters,
+		list_options,
+		with_total,
+	} = params;
+	let (complaints, meta) =
+		ComplaintBmc::list_with_meta(&ctx, &mm, filters, list_options, with_total)
+			.await?;
+	Ok(DataRpcResult::with_meta(complaints, meta))
+}
+
+/// Params for the lifecycle transitions, with the text of the transition
+/// (i.e., the insurer response, the escalation reason, or the resolution).
+#[derive(Deserialize)]
+pub struct ParamsComplaintTransition {
+	pub id: i64,
+	pub text: String,
+}
+impl IntoParams for ParamsComplaintTransition {}
+
+pub async fn escalate_complaint(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsComplaintTransition,
+) -> Result<DataRpcResult<Complaint>> {
+	let ParamsComplaintTransition { id, text } = params;
+	ComplaintBmc::escalate(&ctx, &mm, id, text).await?;
+	let complaint = ComplaintBmc::get(&ctx, &mm, id).await?;
+	Ok(complaint.into())
+}
+
+pub async fn resolve_complaint(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsComplaintTransition,
+) -> Result<DataRpcResult<Complaint>> {
+	let ParamsComplaintTransition { id, text } = params;
+	ComplaintBmc::resolve(&ctx, &mm, id, text).await?;
+	let complaint = ComplaintBmc::get(&ctx, &mm, id).await?;
+	Ok(complaint.into())
+}
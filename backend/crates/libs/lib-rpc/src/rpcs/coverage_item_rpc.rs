@@ -0,0 +1,27 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::coverage_item::{
+	CoverageItem, CoverageItemBmc, CoverageItemFilter, CoverageItemForCreate,
+	CoverageItemForUpdate,
+};
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_coverage_item,
+		get_coverage_item,
+		list_coverage_items,
+		list_coverage_items_by_cursor,
+		update_coverage_item,
+		delete_coverage_item,
+	)
+}
+
+generate_common_rpc_fns!(
+	Bmc: CoverageItemBmc,
+	Entity: CoverageItem,
+	ForCreate: CoverageItemForCreate,
+	ForUpdate: CoverageItemForUpdate,
+	Filter: CoverageItemFilter,
+	Suffix: coverage_item,
+	WriteCheck: CoverageItemBmc
+);
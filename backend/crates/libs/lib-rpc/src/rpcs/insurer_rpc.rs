@@ -0,0 +1,83 @@
+//! The insurer staff namespace (`insurer_` methods).
+//!
+//! Note: All the methods require the ctx user to be a member of the company
+//!       (see `CompanyMemberBmc::check_member`).
+
+use crate::rpcs::complaint_rpc::ParamsComplaintTransition;
+use crate::rpcs::prelude::*;
+use lib_core::model::company_member::CompanyMemberBmc;
+use lib_core::model::complaint::{Complaint, ComplaintBmc, ComplaintFilter};
+use modql::filter::ListOptions;
+use rpc_router::IntoParams;
+use serde::Deserialize;
+use serde_with::{serde_as, OneOrMany};
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		insurer_list_complaints,
+		insurer_get_complaint,
+		insurer_respond_complaint,
+	)
+}
+
+/// Params for the complaints against the company.
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsInsurerComplaintList {
+	pub company_id: i64,
+	#[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
+	pub filters: Option<Vec<ComplaintFilter>>,
+	pub list_options: Option<ListOptions>,
+	/// When true, the result `meta` includes the `total` count (see `ParamsList`).
+	#[serde(default)]
+	pub with_total: bool,
+}
+impl IntoParams for ParamsInsurerComplaintList {}
+
+pub async fn insurer_list_complaints(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsInsurerComplaintList,
+) -> Result<DataRpcResult<Vec<Complaint>>> {
+	let ParamsInsurerComplaintList {
+		company_id,
+		filters,
+		list_options,
+		with_total,
+	} = params;
+	let (complaints, meta) = ComplaintBmc::list_by_company(
+		&ctx,
+		&mm,
+		company_id,
+		filters,
+		list_options,
+		with_total,
+	)
+	.await?;
+	Ok(DataRpcResult::with_meta(complaints, meta))
+}
+
+pub async fn insurer_get_complaint(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<Complaint>> {
+	let ParamsIded { id } = params;
+	let complaint = ComplaintBmc::get(&ctx, &mm, id).await?;
+	// Note: The complainant can get the complaint, but not from this namespace.
+	CompanyMemberBmc::check_member(&ctx, &mm, complaint.company_id).await?;
+	Ok(complaint.into())
+}
+
+/// Responds to an open complaint (the `text` is the insurer response).
+pub async fn insurer_respond_complaint(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsComplaintTransition,
+) -> Result<DataRpcResult<Complaint>> {
+	let ParamsComplaintTransition { id, text } = params;
+	ComplaintBmc::respond(&ctx, &mm, id, text).await?;
+	let complaint = ComplaintBmc::get(&ctx, &mm, id).await?;
+	Ok(complaint.into())
+}
@@ -1,6 +1,11 @@
 /// Create the base crud rpc functions following the common pattern.
 /// - `create_...`
 /// - `get_...`
+/// - `list_...s` and `list_...s_by_cursor`
+/// - `update_...` and `delete_...`
+///
+/// With `WriteCheck`, the type (usually the Bmc) with the `check_create`
+/// and `check_write` access checks of the create and update/delete functions.
 ///
 /// NOTE: Make sure to import the Ctx, ModelManager, ... in the model that uses this macro.
 #[macro_export]
@@ -12,6 +17,7 @@ macro_rules! generate_common_rpc_fns {
         ForUpdate: $for_update:ty,
         Filter: $filter:ty,
         Suffix: $suffix:ident
+        $(, WriteCheck: $write_check:ident)?
     ) => {
         paste! {
             pub async fn [<create_ $suffix>](
@@ -20,6 +26,7 @@ macro_rules! generate_common_rpc_fns {
                 params: ParamsForCreate<$for_create>,
             ) -> Result<DataRpcResult<$entity>> {
                 let ParamsForCreate { data } = params;
+                $($write_check::check_create(&ctx, &mm, &data).await?;)?
                 let id = $bmc::create(&ctx, &mm, data).await?;
                 let entity = $bmc::get(&ctx, &mm, id).await?;
                 Ok(entity.into())
@@ -40,8 +47,21 @@ macro_rules! generate_common_rpc_fns {
                 mm: ModelManager,
                 params: ParamsList<$filter>,
             ) -> Result<DataRpcResult<Vec<$entity>>> {
-                let entities = $bmc::list(&ctx, &mm, params.filters, params.list_options).await?;
-                Ok(entities.into())
+                let ParamsList { filters, list_options, with_total } = params;
+                let (entities, meta) =
+                    $bmc::list_with_meta(&ctx, &mm, filters, list_options, with_total).await?;
+                Ok(DataRpcResult::with_meta(entities, meta))
+            }
+
+            pub async fn [<list_ $suffix s_by_cursor>](
+                ctx: Ctx,
+                mm: ModelManager,
+                params: ParamsListCursor<$filter>,
+            ) -> Result<DataRpcResult<Vec<$entity>, ListCursorMeta>> {
+                let ParamsListCursor { filters, cursor_options } = params;
+                let (entities, meta) =
+                    $bmc::list_by_cursor(&ctx, &mm, filters, cursor_options).await?;
+                Ok(DataRpcResult::with_meta(entities, meta))
             }
 
             pub async fn [<update_ $suffix>](
@@ -49,8 +69,14 @@ macro_rules! generate_common_rpc_fns {
                 mm: ModelManager,
                 params: ParamsForUpdate<$for_update>,
             ) -> Result<DataRpcResult<$entity>> {
-                let ParamsForUpdate { id, data } = params;
-                $bmc::update(&ctx, &mm, id, data).await?;
+                let ParamsForUpdate { id, data, expected_mtime } = params;
+                $($write_check::check_write(&ctx, &mm, id).await?;)?
+                match expected_mtime {
+                    Some(expected_mtime) => {
+                        $bmc::update_if_unchanged(&ctx, &mm, id, data, expected_mtime).await?
+                    }
+                    None => $bmc::update(&ctx, &mm, id, data).await?,
+                }
                 let entity = $bmc::get(&ctx, &mm, id).await?;
                 Ok(entity.into())
             }
@@ -62,9 +88,64 @@ macro_rules! generate_common_rpc_fns {
             ) -> Result<DataRpcResult<$entity>> {
                 let ParamsIded { id } = params;
                 let entity = $bmc::get(&ctx, &mm, id).await?;
+                $($write_check::check_write(&ctx, &mm, id).await?;)?
                 $bmc::delete(&ctx, &mm, id).await?;
                 Ok(entity.into())
             }
         }
     };
 }
+
+/// Create the bulk rpc functions (see `base::create_many`, ...).
+/// - `create_...s` (params data is the list of entities to create)
+/// - `update_...s` (by filters)
+/// - `delete_...s` (by filters)
+///
+/// Note: Only the `Sys` and `Admin` users (see `UserBmc::check_typ`).
+///
+/// NOTE: Make sure to import the Ctx, ModelManager, ... in the model that uses this macro.
+#[macro_export]
+macro_rules! generate_bulk_rpc_fns {
+    (
+        Bmc: $bmc:ident,
+        ForCreate: $for_create:ty,
+        ForUpdate: $for_update:ty,
+        Filter: $filter:ty,
+        Suffix: $suffix:ident
+    ) => {
+        paste! {
+            pub async fn [<create_ $suffix s>](
+                ctx: Ctx,
+                mm: ModelManager,
+                params: ParamsForCreate<Vec<$for_create>>,
+            ) -> Result<DataRpcResult<BulkAffected>> {
+                UserBmc::check_typ(&ctx, &mm, &[UserTyp::Sys, UserTyp::Admin]).await?;
+                let ParamsForCreate { data } = params;
+                let affected = $bmc::create_many(&ctx, &mm, data).await?;
+                Ok(affected.into())
+            }
+
+            pub async fn [<update_ $suffix s>](
+                ctx: Ctx,
+                mm: ModelManager,
+                params: ParamsForUpdateMany<$filter, $for_update>,
+            ) -> Result<DataRpcResult<BulkAffected>> {
+                UserBmc::check_typ(&ctx, &mm, &[UserTyp::Sys, UserTyp::Admin]).await?;
+                let ParamsForUpdateMany { filters, data } = params;
+                let affected = $bmc::update_many(&ctx, &mm, filters, data).await?;
+                Ok(affected.into())
+            }
+
+            pub async fn [<delete_ $suffix s>](
+                ctx: Ctx,
+                mm: ModelManager,
+                params: ParamsForDeleteMany<$filter>,
+            ) -> Result<DataRpcResult<BulkAffected>> {
+                UserBmc::check_typ(&ctx, &mm, &[UserTyp::Sys, UserTyp::Admin]).await?;
+                let ParamsForDeleteMany { filters } = params;
+                let affected = $bmc::delete_many(&ctx, &mm, filters).await?;
+                Ok(affected.into())
+            }
+        }
+    };
+}
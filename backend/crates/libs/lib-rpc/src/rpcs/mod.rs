@@ -1,7 +1,18 @@
 // region:    --- Modules
 
+pub mod category_rpc;
+pub mod claim_rpc;
+pub mod company_rpc;
+pub mod complaint_rpc;
+pub mod coverage_item_rpc;
+pub mod insurer_rpc;
 mod macro_utils;
 mod prelude;
+pub mod product_rpc;
+pub mod quote_rpc;
+pub mod rating_table_rpc;
+pub mod review_response_rpc;
+pub mod review_rpc;
 
 use rpc_router::{Router, RouterBuilder};
 
@@ -9,4 +20,15 @@ use rpc_router::{Router, RouterBuilder};
 
 pub fn all_rpc_router_builder() -> RouterBuilder {
 	Router::builder()
+		.extend(category_rpc::rpc_router_builder())
+		.extend(claim_rpc::rpc_router_builder())
+		.extend(company_rpc::rpc_router_builder())
+		.extend(complaint_rpc::rpc_router_builder())
+		.extend(coverage_item_rpc::rpc_router_builder())
+		.extend(insurer_rpc::rpc_router_builder())
+		.extend(product_rpc::rpc_router_builder())
+		.extend(quote_rpc::rpc_router_builder())
+		.extend(rating_table_rpc::rpc_router_builder())
+		.extend(review_response_rpc::rpc_router_builder())
+		.extend(review_rpc::rpc_router_builder())
 }
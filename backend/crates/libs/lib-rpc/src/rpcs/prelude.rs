@@ -1,11 +1,15 @@
 //! This is a prelude for all .._rpc modules to avoid redundant imports.
 //! NOTE: This is only for the `rpcs` module and sub-modules.
 
-pub use crate::generate_common_rpc_fns;
 pub use crate::rpc_result::DataRpcResult;
 pub use crate::Result;
-pub use crate::{ParamsForCreate, ParamsForUpdate, ParamsIded, ParamsList};
+pub use crate::{generate_bulk_rpc_fns, generate_common_rpc_fns};
+pub use crate::{
+	ParamsForCreate, ParamsForDeleteMany, ParamsForUpdate, ParamsForUpdateMany,
+	ParamsIded, ParamsList, ParamsListCursor,
+};
 pub use lib_core::ctx::Ctx;
-pub use lib_core::model::ModelManager;
+pub use lib_core::model::user::{UserBmc, UserTyp};
+pub use lib_core::model::{BulkAffected, ListCursorMeta, ModelManager};
 pub use paste::paste;
 pub use rpc_router::{router_builder, RouterBuilder};
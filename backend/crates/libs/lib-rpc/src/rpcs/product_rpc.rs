@@ -0,0 +1,120 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::product::{
+	Product, ProductBmc, ProductFilter, ProductForCreate, ProductForUpdate,
+	ProductSearchHit, ProductVisibilityForUpdate,
+};
+use lib_core::model::product_comparison::{self, ProductComparison};
+use rpc_router::IntoParams;
+use serde::Deserialize;
+use serde_with::{serde_as, OneOrMany};
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_product,
+		get_product,
+		list_products,
+		list_products_by_cursor,
+		update_product,
+		delete_product,
+		// -- Bulk
+		create_products,
+		update_products,
+		delete_products,
+		// -- Search
+		search_products,
+		// -- Comparison
+		compare_products,
+		// -- Visibility
+		update_product_visibility,
+	)
+}
+
+generate_common_rpc_fns!(
+	Bmc: ProductBmc,
+	Entity: Product,
+	ForCreate: ProductForCreate,
+	ForUpdate: ProductForUpdate,
+	Filter: ProductFilter,
+	Suffix: product,
+	WriteCheck: ProductBmc
+);
+
+generate_bulk_rpc_fns!(
+	Bmc: ProductBmc,
+	ForCreate: ProductForCreate,
+	ForUpdate: ProductForUpdate,
+	Filter: ProductFilter,
+	Suffix: product
+);
+
+/// Params for the product full-text search (see `ProductBmc::search`).
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsProductSearch {
+	pub query: String,
+	#[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
+	pub filters: Option<Vec<ProductFilter>>,
+	pub limit: Option<i64>,
+}
+impl IntoParams for ParamsProductSearch {}
+
+pub async fn search_products(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsProductSearch,
+) -> Result<DataRpcResult<Vec<ProductSearchHit>>> {
+	let ParamsProductSearch {
+		query,
+		filters,
+		limit,
+	} = params;
+	let hits = ProductBmc::search(&ctx, &mm, &query, filters, limit).await?;
+	Ok(hits.into())
+}
+
+/// Params for the product comparison (2 to 5 product ids).
+#[derive(Deserialize)]
+pub struct ParamsProductComparison {
+	pub ids: Vec<i64>,
+}
+impl IntoParams for ParamsProductComparison {}
+
+pub async fn compare_products(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsProductComparison,
+) -> Result<DataRpcResult<ProductComparison>> {
+	let comparison =
+		product_comparison::compare_products(&ctx, &mm, &params.ids).await?;
+	Ok(comparison.into())
+}
+
+/// Toggles the visibility of the product reviews and claim data
+/// (only an admin or a member of the product company, see `ProductBmc`).
+pub async fn update_product_visibility(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForUpdate<ProductVisibilityForUpdate>,
+) -> Result<DataRpcResult<Product>> {
+	let ParamsForUpdate {
+		id,
+		data,
+		expected_mtime,
+	} = params;
+	match expected_mtime {
+		Some(expected_mtime) => {
+			ProductBmc::update_visibility_if_unchanged(
+				&ctx,
+				&mm,
+				id,
+				data,
+				expected_mtime,
+			)
+			.await?
+		}
+		None => ProductBmc::update_visibility(&ctx, &mm, id, data).await?,
+	}
+	let product = ProductBmc::get(&ctx, &mm, id).await?;
+	Ok(product.into())
+}
@@ -0,0 +1,34 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::quote::{self, PremiumQuote};
+use rpc_router::IntoParams;
+use serde::Deserialize;
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		quote_product,
+	)
+}
+
+/// Params for the product quote, `version` being the rating table version
+/// (the latest if None).
+#[derive(Deserialize)]
+pub struct ParamsQuoteProduct {
+	pub product_id: i64,
+	pub version: Option<i32>,
+}
+impl IntoParams for ParamsQuoteProduct {}
+
+/// Quotes the product for the authenticated user (from its profile).
+pub async fn quote_product(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsQuoteProduct,
+) -> Result<DataRpcResult<PremiumQuote>> {
+	let ParamsQuoteProduct {
+		product_id,
+		version,
+	} = params;
+	let quote = quote::quote_product(&ctx, &mm, product_id, version).await?;
+	Ok(quote.into())
+}
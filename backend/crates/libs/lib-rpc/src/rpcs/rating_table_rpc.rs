@@ -0,0 +1,53 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::rating_table::{
+	RatingTable, RatingTableBmc, RatingTableFilter, RatingTableForCreate,
+};
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_rating_table,
+		get_rating_table,
+		list_rating_tables,
+	)
+}
+
+// Note: No update/delete, a rating table version is immutable
+//       (creating a rating table for a product creates its next version).
+
+pub async fn create_rating_table(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForCreate<RatingTableForCreate>,
+) -> Result<DataRpcResult<RatingTable>> {
+	let ParamsForCreate { data } = params;
+	RatingTableBmc::check_create(&ctx, &mm, &data).await?;
+	let id = RatingTableBmc::create(&ctx, &mm, data).await?;
+	let rating_table = RatingTableBmc::get(&ctx, &mm, id).await?;
+	Ok(rating_table.into())
+}
+
+pub async fn get_rating_table(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<RatingTable>> {
+	let rating_table = RatingTableBmc::get(&ctx, &mm, params.id).await?;
+	Ok(rating_table.into())
+}
+
+pub async fn list_rating_tables(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<RatingTableFilter>,
+) -> Result<DataRpcResult<Vec<RatingTable>>> {
+	let ParamsList {
+		filters,
+		list_options,
+		with_total,
+	} = params;
+	let (rating_tables, meta) =
+		RatingTableBmc::list_with_meta(&ctx, &mm, filters, list_options, with_total)
+			.await?;
+	Ok(DataRpcResult::with_meta(rating_tables, meta))
+}
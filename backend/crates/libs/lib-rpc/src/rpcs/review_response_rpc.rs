@@ -0,0 +1,99 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::review_response::{
+	ReviewResponse, ReviewResponseBmc, ReviewResponseFilter,
+	ReviewResponseForCreate, ReviewResponseForUpdate,
+};
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_review_response,
+		get_review_response,
+		list_review_responses,
+		update_review_response,
+		delete_review_response,
+	)
+}
+
+// Note: Only the members of the company of the reviewed product can create,
+//       update, or delete a response (checked from the `Ctx`, see `ReviewResponseBmc`).
+
+pub async fn create_review_response(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForCreate<ReviewResponseForCreate>,
+) -> Result<DataRpcResult<ReviewResponse>> {
+	let ParamsForCreate { data } = params;
+	let id = ReviewResponseBmc::create(&ctx, &mm, data).await?;
+	let response = ReviewResponseBmc::get(&ctx, &mm, id).await?;
+	Ok(response.into())
+}
+
+pub async fn get_review_response(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<ReviewResponse>> {
+	let ParamsIded { id } = params;
+	let response = ReviewResponseBmc::get(&ctx, &mm, id).await?;
+	Ok(response.into())
+}
+
+pub async fn list_review_responses(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<ReviewResponseFilter>,
+) -> Result<DataRpcResult<Vec<ReviewResponse>>> {
+	let ParamsList {
+		filters,
+		list_options,
+		with_total,
+	} = params;
+	let (responses, meta) = ReviewResponseBmc::list_with_meta(
+		&ctx,
+		&mm,
+		filters,
+		list_options,
+		with_total,
+	)
+	.await?;
+	Ok(DataRpcResult::with_meta(responses, meta))
+}
+
+pub async fn update_review_response(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForUpdate<ReviewResponseForUpdate>,
+) -> Result<DataRpcResult<ReviewResponse>> {
+	let ParamsForUpdate {
+		id,
+		data,
+		expected_mtime,
+	} = params;
+	match expected_mtime {
+		Some(expected_mtime) => {
+			ReviewResponseBmc::update_if_unchanged(
+				&ctx,
+				&mm,
+				id,
+				data,
+				expected_mtime,
+			)
+			.await?
+		}
+		None => ReviewResponseBmc::update(&ctx, &mm, id, data).await?,
+	}
+	let response = ReviewResponseBmc::get(&ctx, &mm, id).await?;
+	Ok(response.into())
+}
+
+pub async fn delete_review_response(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<ReviewResponse>> {
+	let ParamsIded { id } = params;
+	let response = ReviewResponseBmc::get(&ctx, &mm, id).await?;
+	ReviewResponseBmc::delete(&ctx, &mm, id).await?;
+	Ok(response.into())
+}
@@ -0,0 +1,167 @@
+use crate::rpcs::prelude::*;
+use lib_core::model::review::{
+	Review, ReviewBmc, ReviewFilter, ReviewForCreate, ReviewForUpdate, ReviewQueued,
+};
+use lib_core::model::review_flag::{
+	ReviewFlag, ReviewFlagBmc, ReviewFlagFilter, ReviewFlagForCreate,
+};
+use lib_core::model::review_vote::ReviewVoteBmc;
+use rpc_router::IntoParams;
+use serde::Deserialize;
+
+pub fn rpc_router_builder() -> RouterBuilder {
+	router_builder!(
+		// Same as RpcRouter::new().add...
+		create_review,
+		get_review,
+		list_reviews,
+		list_reviews_by_cursor,
+		update_review,
+		delete_review,
+		// -- Vote
+		vote_review,
+		unvote_review,
+		// -- Flag
+		flag_review,
+		// -- Moderation
+		list_review_moderation_queue,
+		list_review_flags,
+		approve_review,
+		reject_review,
+		hide_review,
+	)
+}
+
+// Note: The review author is the `Ctx::user_id()` of the rpc call,
+//       and only the author can update or delete its review (see `ReviewBmc`).
+generate_common_rpc_fns!(
+	Bmc: ReviewBmc,
+	Entity: Review,
+	ForCreate: ReviewForCreate,
+	ForUpdate: ReviewForUpdate,
+	Filter: ReviewFilter,
+	Suffix: review
+);
+
+// -- Vote
+
+/// Params for the "helpful" vote of the ctx user (`helpful: false` for a down vote).
+#[derive(Deserialize)]
+pub struct ParamsReviewVote {
+	pub review_id: i64,
+	pub helpful: bool,
+}
+impl IntoParams for ParamsReviewVote {}
+
+/// Votes on the review (the voter being the ctx user), and returns the review.
+pub async fn vote_review(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsReviewVote,
+) -> Result<DataRpcResult<Review>> {
+	let ParamsReviewVote { review_id, helpful } = params;
+	ReviewVoteBmc::vote(&ctx, &mm, review_id, helpful).await?;
+	let review = ReviewBmc::get(&ctx, &mm, review_id).await?;
+	Ok(review.into())
+}
+
+/// Removes the vote of the ctx user on the review (`id`), and returns the review.
+pub async fn unvote_review(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsIded,
+) -> Result<DataRpcResult<Review>> {
+	let ParamsIded { id } = params;
+	ReviewVoteBmc::unvote(&ctx, &mm, id).await?;
+	let review = ReviewBmc::get(&ctx, &mm, id).await?;
+	Ok(review.into())
+}
+
+// -- Flag
+
+/// Flags (reports) a published review, the reporter being the ctx user.
+pub async fn flag_review(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsForCreate<ReviewFlagForCreate>,
+) -> Result<DataRpcResult<ReviewFlag>> {
+	let ParamsForCreate { data } = params;
+	let id = ReviewFlagBmc::create(&ctx, &mm, data).await?;
+	let flag = ReviewFlagBmc::get(&ctx, &mm, id).await?;
+	Ok(flag.into())
+}
+
+// -- Moderation
+// Note: The moderation fns require a moderator ctx user (see `ReviewBmc`).
+
+#[derive(Deserialize)]
+pub struct ParamsReviewModerationQueue {
+	pub limit: Option<i64>,
+}
+impl IntoParams for ParamsReviewModerationQueue {}
+
+/// Params for the moderation actions. The `reason` is required to reject or hide.
+#[derive(Deserialize)]
+pub struct ParamsReviewModerate {
+	pub id: i64,
+	pub reason: Option<String>,
+}
+impl IntoParams for ParamsReviewModerate {}
+
+pub async fn list_review_moderation_queue(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsReviewModerationQueue,
+) -> Result<DataRpcResult<Vec<ReviewQueued>>> {
+	let reviews = ReviewBmc::list_moderation_queue(&ctx, &mm, params.limit).await?;
+	Ok(reviews.into())
+}
+
+pub async fn list_review_flags(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsList<ReviewFlagFilter>,
+) -> Result<DataRpcResult<Vec<ReviewFlag>>> {
+	let ParamsList {
+		filters,
+		list_options,
+		with_total,
+	} = params;
+	let (flags, meta) =
+		ReviewFlagBmc::list_with_meta(&ctx, &mm, filters, list_options, with_total)
+			.await?;
+	Ok(DataRpcResult::with_meta(flags, meta))
+}
+
+pub async fn approve_review(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsReviewModerate,
+) -> Result<DataRpcResult<Review>> {
+	let ParamsReviewModerate { id, reason } = params;
+	ReviewBmc::approve(&ctx, &mm, id, reason).await?;
+	let review = ReviewBmc::get(&ctx, &mm, id).await?;
+	Ok(review.into())
+}
+
+pub async fn reject_review(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsReviewModerate,
+) -> Result<DataRpcResult<Review>> {
+	let ParamsReviewModerate { id, reason } = params;
+	ReviewBmc::reject(&ctx, &mm, id, reason.unwrap_or_default()).await?;
+	let review = ReviewBmc::get(&ctx, &mm, id).await?;
+	Ok(review.into())
+}
+
+pub async fn hide_review(
+	ctx: Ctx,
+	mm: ModelManager,
+	params: ParamsReviewModerate,
+) -> Result<DataRpcResult<Review>> {
+	let ParamsReviewModerate { id, reason } = params;
+	ReviewBmc::hide(&ctx, &mm, id, reason.unwrap_or_default()).await?;
+	let review = ReviewBmc::get(&ctx, &mm, id).await?;
+	Ok(review.into())
+}
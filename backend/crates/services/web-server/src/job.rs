@@ -0,0 +1,22 @@
+//! Background jobs, spawned at startup.
+
+use lib_core::ctx::Ctx;
+use lib_core::model::trust_score::TrustScoreBmc;
+use lib_core::model::ModelManager;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const TRUST_SCORE_SNAPSHOT_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Snapshots the trust scores at startup, then daily.
+/// Note: A restart on the same day replaces the snapshots of the day.
+pub async fn run_trust_score_snapshots(mm: ModelManager) {
+	let mut interval = tokio::time::interval(TRUST_SCORE_SNAPSHOT_PERIOD);
+	loop {
+		interval.tick().await;
+		match TrustScoreBmc::snapshot_all(&Ctx::root_ctx(), &mm).await {
+			Ok(count) => info!("{:<12} - trust score snapshots: {count}", "JOB"),
+			Err(ex) => warn!("{:<12} - trust score snapshots failed: {ex:?}", "JOB"),
+		}
+	}
+}
@@ -2,6 +2,7 @@
 
 mod config;
 mod error;
+mod job;
 mod log;
 mod web;
 
@@ -35,6 +36,9 @@ async fn main() -> Result<()> {
 
 	let mm = ModelManager::new().await?;
 
+	// -- Start Jobs
+	tokio::spawn(job::run_trust_score_snapshots(mm.clone()));
+
 	// -- Define Routes
 	let routes_rpc = web::routes_rpc::routes(mm.clone())
 		.route_layer(middleware::from_fn(mw_ctx_require));
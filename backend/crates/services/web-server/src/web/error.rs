@@ -25,6 +25,14 @@ pub enum Error {
 		user_id: i64,
 	},
 
+	// -- Register
+	RegisterFailInvalidBirthDate {
+		birth_date: String,
+	},
+	RegisterFailInvalidDependents {
+		dependents: u32,
+	},
+
 	// -- CtxExtError
 	#[from]
 	CtxExt(web::mw_auth::CtxExtError),
@@ -147,14 +155,246 @@ impl Error {
 				(StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL)
 			}
 
+			// -- Register
+			RegisterFailInvalidBirthDate { .. } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::REGISTER_INVALID_BIRTH_DATE,
+			),
+			RegisterFailInvalidDependents { .. } => (
+				StatusCode::BAD_REQUEST,
+				ClientError::REGISTER_INVALID_DEPENDENTS,
+			),
+			Model(model::Error::UserAlreadyExists { .. }) => {
+				(StatusCode::BAD_REQUEST, ClientError::USER_ALREADY_EXISTS)
+			}
+
 			// -- Auth
 			CtxExt(_) => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
 
 			// -- Model
-			Model(model::Error::EntityNotFound { entity, id }) => (
+			Model(model::Error::EntityNotFound { entity, id })
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::EntityNotFound {
+				entity,
+				id,
+			})) => (
 				StatusCode::BAD_REQUEST,
 				ClientError::ENTITY_NOT_FOUND { entity, id: *id },
 			),
+			Model(model::Error::ListCursorInvalid)
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::ListCursorInvalid)) => {
+				(StatusCode::BAD_REQUEST, ClientError::LIST_CURSOR_INVALID)
+			}
+			Model(
+				ex @ (model::Error::ListLimitInvalid { .. }
+				| model::Error::ListLimitOverMax { .. }),
+			)
+			| RpcLibRpc(lib_rpc::Error::Model(
+				ex @ (model::Error::ListLimitInvalid { .. }
+				| model::Error::ListLimitOverMax { .. }),
+			)) => {
+				let client_error = match ex {
+					model::Error::ListLimitOverMax { max, .. } => {
+						ClientError::LIST_LIMIT_OVER_MAX { max: *max }
+					}
+					_ => ClientError::LIST_LIMIT_INVALID,
+				};
+				(StatusCode::BAD_REQUEST, client_error)
+			}
+			Model(model::Error::ModqlIntoSea(ex))
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::ModqlIntoSea(ex))) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::LIST_FILTER_INVALID(ex.to_string()),
+			),
+			Model(model::Error::BulkFilterEmpty)
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::BulkFilterEmpty)) => {
+				(StatusCode::BAD_REQUEST, ClientError::BULK_FILTER_EMPTY)
+			}
+			Model(model::Error::EntityAccessDenied { entity, id })
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::EntityAccessDenied {
+				entity,
+				id,
+			})) => (
+				StatusCode::FORBIDDEN,
+				ClientError::ACCESS_DENIED { entity, id: *id },
+			),
+			Model(model::Error::ConcurrentModification { entity, id })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ConcurrentModification { entity, id },
+			)) => (
+				StatusCode::CONFLICT,
+				ClientError::CONCURRENT_MODIFICATION { entity, id: *id },
+			),
+
+			// -- Model User
+			Model(model::Error::UserNotPrivileged { .. })
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::UserNotPrivileged {
+				..
+			})) => (StatusCode::FORBIDDEN, ClientError::USER_NOT_PRIVILEGED),
+
+			// -- Model Product
+			Model(model::Error::ProductReviewsHidden { product_id })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ProductReviewsHidden { product_id },
+			)) => (
+				StatusCode::FORBIDDEN,
+				ClientError::PRODUCT_REVIEWS_HIDDEN {
+					product_id: *product_id,
+				},
+			),
+			Model(model::Error::ProductClaimDataHidden { product_id })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ProductClaimDataHidden { product_id },
+			)) => (
+				StatusCode::FORBIDDEN,
+				ClientError::PRODUCT_CLAIM_DATA_HIDDEN {
+					product_id: *product_id,
+				},
+			),
+
+			// -- Model Review
+			Model(model::Error::ReviewAlreadyExists { product_id })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ReviewAlreadyExists { product_id },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::REVIEW_ALREADY_EXISTS {
+					product_id: *product_id,
+				},
+			),
+			Model(model::Error::ReviewRatingOutOfRange { min, max, .. })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ReviewRatingOutOfRange { min, max, .. },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::REVIEW_RATING_OUT_OF_RANGE {
+					min: *min,
+					max: *max,
+				},
+			),
+			Model(model::Error::ReviewModerationReasonEmpty)
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ReviewModerationReasonEmpty,
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::REVIEW_MODERATION_REASON_EMPTY,
+			),
+			Model(model::Error::ReviewFlagAlreadyExists { review_id })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ReviewFlagAlreadyExists { review_id },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::REVIEW_FLAG_ALREADY_EXISTS {
+					review_id: *review_id,
+				},
+			),
+			Model(model::Error::ReviewResponseAlreadyExists { review_id })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ReviewResponseAlreadyExists { review_id },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::REVIEW_RESPONSE_ALREADY_EXISTS {
+					review_id: *review_id,
+				},
+			),
+
+			// -- Model Complaint
+			Model(model::Error::ComplaintTextEmpty { field })
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::ComplaintTextEmpty {
+				field,
+			})) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::COMPLAINT_TEXT_EMPTY { field },
+			),
+			Model(model::Error::ComplaintLinkInvalid { entity, id })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ComplaintLinkInvalid { entity, id },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::COMPLAINT_LINK_INVALID { entity, id: *id },
+			),
+			Model(model::Error::ComplaintAttachmentUrlInvalid { url })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ComplaintAttachmentUrlInvalid { url },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::COMPLAINT_ATTACHMENT_URL_INVALID { url: url.clone() },
+			),
+			Model(model::Error::ComplaintStatusInvalid { status, .. })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ComplaintStatusInvalid { status, .. },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::COMPLAINT_STATUS_INVALID {
+					status: status.to_string(),
+				},
+			),
+
+			// -- Model Product Comparison
+			Model(model::Error::ProductComparisonCountOutOfRange { min, max, .. })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::ProductComparisonCountOutOfRange { min, max, .. },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::PRODUCT_COMPARISON_COUNT_OUT_OF_RANGE {
+					min: *min,
+					max: *max,
+				},
+			),
+
+			// -- Model Trust Score
+			Model(model::Error::TrustScoreHistoryDaysOutOfRange { min, max, .. })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::TrustScoreHistoryDaysOutOfRange { min, max, .. },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::TRUST_SCORE_HISTORY_DAYS_OUT_OF_RANGE {
+					min: *min,
+					max: *max,
+				},
+			),
+
+			// -- Model Quote
+			Model(model::Error::RatingTableInvalid { reason })
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::RatingTableInvalid {
+				reason,
+			})) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::RATING_TABLE_INVALID(reason.to_string()),
+			),
+			Model(model::Error::QuoteUserProfileNotFound { .. })
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::QuoteUserProfileNotFound { .. },
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::QUOTE_USER_PROFILE_NOT_FOUND,
+			),
+			Model(model::Error::QuoteRatingTableNotFound {
+				product_id,
+				version,
+			})
+			| RpcLibRpc(lib_rpc::Error::Model(
+				model::Error::QuoteRatingTableNotFound {
+					product_id,
+					version,
+				},
+			)) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::QUOTE_RATING_TABLE_NOT_FOUND {
+					product_id: *product_id,
+					version: *version,
+				},
+			),
+			Model(model::Error::QuoteAgeNotRated { product_id, age })
+			| RpcLibRpc(lib_rpc::Error::Model(model::Error::QuoteAgeNotRated {
+				product_id,
+				age,
+			})) => (
+				StatusCode::BAD_REQUEST,
+				ClientError::QUOTE_AGE_NOT_RATED {
+					product_id: *product_id,
+					age: *age,
+				},
+			),
 
 			// -- Rpc
 			RpcRequestParsing(req_parsing_err) => (
@@ -204,7 +444,41 @@ impl Error {
 pub enum ClientError {
 	LOGIN_FAIL,
 	NO_AUTH,
+	REGISTER_INVALID_BIRTH_DATE,
+	REGISTER_INVALID_DEPENDENTS,
+	USER_ALREADY_EXISTS,
 	ENTITY_NOT_FOUND { entity: &'static str, id: i64 },
+	LIST_CURSOR_INVALID,
+	LIST_LIMIT_INVALID,
+	LIST_LIMIT_OVER_MAX { max: i64 },
+	LIST_FILTER_INVALID(String),
+	BULK_FILTER_EMPTY,
+	ACCESS_DENIED { entity: &'static str, id: i64 },
+	CONCURRENT_MODIFICATION { entity: &'static str, id: i64 },
+	USER_NOT_PRIVILEGED,
+
+	PRODUCT_REVIEWS_HIDDEN { product_id: i64 },
+	PRODUCT_CLAIM_DATA_HIDDEN { product_id: i64 },
+
+	REVIEW_ALREADY_EXISTS { product_id: i64 },
+	REVIEW_RATING_OUT_OF_RANGE { min: i16, max: i16 },
+	REVIEW_MODERATION_REASON_EMPTY,
+	REVIEW_FLAG_ALREADY_EXISTS { review_id: i64 },
+	REVIEW_RESPONSE_ALREADY_EXISTS { review_id: i64 },
+
+	COMPLAINT_TEXT_EMPTY { field: &'static str },
+	COMPLAINT_LINK_INVALID { entity: &'static str, id: i64 },
+	COMPLAINT_ATTACHMENT_URL_INVALID { url: String },
+	COMPLAINT_STATUS_INVALID { status: String },
+
+	PRODUCT_COMPARISON_COUNT_OUT_OF_RANGE { min: usize, max: usize },
+
+	TRUST_SCORE_HISTORY_DAYS_OUT_OF_RANGE { min: i64, max: i64 },
+
+	RATING_TABLE_INVALID(String),
+	QUOTE_USER_PROFILE_NOT_FOUND,
+	QUOTE_RATING_TABLE_NOT_FOUND { product_id: i64, version: Option<i32> },
+	QUOTE_AGE_NOT_RATED { product_id: i64, age: i32 },
 
 	RPC_REQUEST_INVALID(String),
 	RPC_REQUEST_METHOD_UNKNOWN(String),
@@ -213,3 +487,98 @@ pub enum ClientError {
 	SERVICE_ERROR,
 }
 // endregion: --- Client Error
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+	pub type Result<T> = core::result::Result<T, Error>;
+	pub type Error = Box<dyn std::error::Error>; // For tests.
+
+	use super::*;
+
+	#[test]
+	fn test_client_status_and_error_list_limit_over_max() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_model_error = || model::Error::ListLimitOverMax {
+			max: 5000,
+			actual: 5001,
+		};
+
+		// -- Exec
+		let errors = [
+			web::Error::Model(fx_model_error()),
+			web::Error::RpcLibRpc(lib_rpc::Error::Model(fx_model_error())),
+		];
+
+		// -- Check
+		for error in errors {
+			let (status, client_error) = error.client_status_and_error();
+			assert_eq!(status, StatusCode::BAD_REQUEST);
+			assert!(
+				matches!(
+					client_error,
+					ClientError::LIST_LIMIT_OVER_MAX { max: 5000 }
+				),
+				"LIST_LIMIT_OVER_MAX not matching"
+			);
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_client_status_and_error_list_limit_invalid() -> Result<()> {
+		// -- Setup & Fixtures
+		let fx_model_error = || model::Error::ListLimitInvalid { actual: -1 };
+
+		// -- Exec
+		let errors = [
+			web::Error::Model(fx_model_error()),
+			web::Error::RpcLibRpc(lib_rpc::Error::Model(fx_model_error())),
+		];
+
+		// -- Check
+		for error in errors {
+			let (status, client_error) = error.client_status_and_error();
+			assert_eq!(status, StatusCode::BAD_REQUEST);
+			assert!(
+				matches!(client_error, ClientError::LIST_LIMIT_INVALID),
+				"LIST_LIMIT_INVALID not matching"
+			);
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_call_error_moderation_queue_limit_over_max() -> Result<()> {
+		// -- Setup & Fixtures
+		// Note: As returned by the `list_review_moderation_queue` rpc handler.
+		let fx_call_error = rpc_router::CallError {
+			id: Value::from(1),
+			method: "list_review_moderation_queue".to_string(),
+			error: rpc_router::Error::Handler(rpc_router::HandlerError::new(
+				lib_rpc::Error::Model(model::Error::ListLimitOverMax {
+					max: 1000,
+					actual: 1001,
+				}),
+			)),
+		};
+
+		// -- Exec
+		let error = web::Error::from(fx_call_error);
+		let (status, client_error) = error.client_status_and_error();
+
+		// -- Check
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		assert!(
+			matches!(client_error, ClientError::LIST_LIMIT_OVER_MAX { max: 1000 }),
+			"LIST_LIMIT_OVER_MAX not matching"
+		);
+
+		Ok(())
+	}
+}
+
+// endregion: --- Tests
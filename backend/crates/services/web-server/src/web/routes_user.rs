@@ -1,3 +1,4 @@
+use crate::web::mw_auth::CtxW;
 use crate::web::{self, remove_token_cookie, Error, Result};
 use axum::extract::State;
 use axum::routing::post;
@@ -5,15 +6,34 @@ use axum::routing::get;
 use axum::{Json, Router};
 use lib_auth::pwd::{self, ContentToHash, SchemeStatus};
 use lib_core::ctx::Ctx;
-use lib_core::model::user::{UserBmc, UserForLogin};
-use lib_core::model::ModelManager;
+use lib_core::model::category::CategoryBmc;
+use lib_core::model::claim::{
+	ClaimBmc, ClaimHistoryResolution, ClaimMetrics, ProductClaimCount,
+};
+use lib_core::model::company::{
+	Company, CompanyBmc, CompanyFilter, CompanyWithProducts,
+};
+use lib_core::model::complaint::ComplaintBmc;
+use lib_core::model::coverage_item::{CoverageItem, CoverageItemBmc};
+use lib_core::model::product::{Product, ProductBmc, ProductFilter};
+use lib_core::model::product_comparison::{self, ProductComparison};
+use lib_core::model::recommendation;
+use lib_core::model::review::{ProductRating, ReviewBmc, ReviewSort};
+use lib_core::model::review_response::{ReviewResponse, ReviewResponseBmc};
+use lib_core::model::trust_score::{TrustScore, TrustScoreBmc, TrustScoreSnapshot};
+use lib_core::model::user::{UserBmc, UserForAuth, UserForCreate, UserForLogin};
+use lib_core::model::user_profile::{UserProfileBmc, UserProfileForCreate};
+use lib_core::model::{self, ModelManager};
+use modql::filter::{OpValInt64, OpValValue};
+use std::collections::HashMap;
 use serde::Serialize;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use serde_with::{serde_as, DisplayFromStr};
+use time::format_description::well_known::Iso8601;
+use time::Date;
 use tower_cookies::Cookies;
 use tracing::debug;
-use std::time::{SystemTime, UNIX_EPOCH};
-use rand::{Rng, thread_rng};
 
 pub fn routes(mm: ModelManager) -> Router {
 	Router::new()
@@ -25,11 +45,17 @@ pub fn routes(mm: ModelManager) -> Router {
 
 		.route("/api/product/list", post(api_product_list_handler))
 		.route("/api/product/detail", post(api_product_detail_handler))
+		.route("/api/product/compare", post(api_product_compare_handler))
+		.route("/api/product/recommended", post(api_product_recommended_handler))
 
 		.route("/api/product/claim/historical_data/config", post(api_product_claim_historical_data_config_handler))
 		.route("/api/product/claim/historical_data", post(api_product_claim_historical_data_handler))
+		.route("/api/product/claim/metrics", post(api_product_claim_metrics_handler))
 
 		.route("/api/product/review/list", post(api_product_review_list_handler))
+
+		.route("/api/company/detail", post(api_company_detail_handler))
+		.route("/api/company/claim/metrics", post(api_company_claim_metrics_handler))
 		.with_state(mm)
 }
 
@@ -141,6 +167,7 @@ async fn api_register_handler(
 
 	let RegisterPayload {
 		email,
+		pwd: pwd_clear,
 		phone,
 		full_name,
 		gender,
@@ -154,6 +181,38 @@ async fn api_register_handler(
 		familiarity,
 		interests,
 	} = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	let birth_date = Date::parse(&birth_date, &Iso8601::DATE)
+		.map_err(|_| Error::RegisterFailInvalidBirthDate { birth_date })?;
+
+	// -- Create the user and its profile (the email is the username).
+	let user_c = UserForCreate {
+		username: email.clone(),
+		pwd_clear,
+	};
+	let profile_c = UserProfileForCreate {
+		email,
+		phone,
+		full_name,
+		gender,
+		birth_date,
+		address,
+		marital_status,
+		occupation,
+		income,
+		dependents: i32::try_from(dependents)
+			.map_err(|_| Error::RegisterFailInvalidDependents { dependents })?,
+		region,
+		familiarity,
+		interests,
+	};
+	let user_id =
+		UserProfileBmc::create_with_user(&root_ctx, &mm, user_c, profile_c).await?;
+
+	// -- Set web token (the user is logged in after registration).
+	let user: UserForAuth = UserBmc::get(&root_ctx, &mm, user_id).await?;
+	web::set_token_cookie(&cookies, &user.username, user.token_salt)?;
 
 	// Create the success body.
 	let body = Json(json!({
@@ -168,9 +227,13 @@ async fn api_register_handler(
 #[derive(Debug, Deserialize)]
 struct RegisterPayload {
 	email: String,
+	/// Note: Required (a breaking change from the former stub endpoint),
+	///       as the registration now creates the user, who is logged in.
+	pwd: String,
 	phone: String,
 	full_name: String,
 	gender: String,
+	/// Format: `YYYY-MM-DD`
 	birth_date: String,
 	address: String,
 	marital_status: String,
@@ -184,54 +247,86 @@ struct RegisterPayload {
 // endregion: --- Register
 
 
-// region:    --- Product List 
+// region:    --- Product List
 async fn api_product_list_handler(
-    State(_mm): State<ModelManager>,
-    _cookies: Cookies,
+	State(mm): State<ModelManager>,
+	_cookies: Cookies,
 	Json(payload): Json<ProductListPayload>,
 ) -> Result<Json<Value>> {
-	debug!("{:<12} - api_register_handler", "HANDLER");
+	debug!("{:<12} - api_product_list_handler", "HANDLER");
 
-    // Create a vector to hold the dummy product data
-    let mut data = Vec::new();
-    for i in 0..8 {
-        data.push(
-			ProductResponse {
-				id: (i + 1).to_string(),
-				name: "Asuransi Kesehatan".to_string() + &i.to_string(),		
-				description: "Dengan asuransi kesehatan, Anda akan mendapatkan perlindungan kesehatan yang lengkap.".to_string(),
-				count_claim: 10,
-				count_review: 20,
-				rating: 4.0,
-				company: CompanyInfo {
-					name: "AXA Insurance".to_string(),
-					logo: "https://upload.wikimedia.org/wikipedia/commons/thumb/9/94/AXA_Logo.svg/640px-AXA_Logo.svg.png".to_string(),
-					description: "AXA adalah perusahaan asuransi multinasional yang berkantor pusat di Paris, Prancis.".to_string(),
-					website_url: "https://www.axa.com".to_string(),
-					location: "Paris, France".to_string(),
-					founded_year: 1817,
-				},
-				banner: "https://umsu.ac.id/health/wp-content/uploads/2023/08/Hospitals-and-Insurance-750x375.jpeg".to_string(),
+	let ProductListPayload { search, category_id } = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	// -- Build the filter from the eventual category (subtree).
+	let filter = category_id.map(|category_id| {
+		vec![ProductFilter {
+			category_id: Some(OpValValue::Eq(category_id.into()).into()),
+			..Default::default()
+		}]
+	});
+
+	// -- Get the products (ranked with their snippets if search) and their companies.
+	let search = search.filter(|search| !search.trim().is_empty());
+	let (products, mut snippets): (Vec<Product>, HashMap<i64, String>) =
+		match search {
+			Some(search) => {
+				let hits =
+					ProductBmc::search(&root_ctx, &mm, &search, filter, None).await?;
+				let snippets = hits
+					.iter()
+					.map(|hit| (hit.product.id, hit.snippet.clone()))
+					.collect();
+				(hits.into_iter().map(|hit| hit.product).collect(), snippets)
 			}
-		);
-    }
+			None => (
+				ProductBmc::list(&root_ctx, &mm, filter, None).await?,
+				HashMap::new(),
+			),
+		};
+	let company_ids: Vec<i64> = products.iter().map(|p| p.company_id).collect();
+	let company_filter = CompanyFilter {
+		id: Some(OpValInt64::In(company_ids).into()),
+		..Default::default()
+	};
+	let companies: HashMap<i64, Company> =
+		CompanyBmc::list(&root_ctx, &mm, Some(vec![company_filter]), None)
+			.await?
+			.into_iter()
+			.map(|company| (company.id, company))
+			.collect();
+	let stats = ProductStats::load(&root_ctx, &mm, &products).await?;
 
-    // Create the success body with the dummy product data
+	// Create the success body.
+	let data = products
+		.into_iter()
+		.filter_map(|product| {
+			let company = companies.get(&product.company_id)?.clone();
+			let snippet = snippets.remove(&product.id);
+			Some(ProductListItem {
+				product: ProductResponse::new(product, company, &stats),
+				snippet,
+			})
+		})
+		.collect();
 	let response = ProductListResponse {
 		success: true,
 		data,
 	};
 	let body = Json(serde_json::to_value(response)?);
 
-    Ok(body)
+	Ok(body)
 }
 
+#[serde_as]
 #[derive(Debug, Deserialize)]
 struct ProductListPayload {
 	search: Option<String>,
+	#[serde_as(as = "Option<DisplayFromStr>")]
+	#[serde(default)]
+	category_id: Option<i64>,
 }
 
-
 #[derive(Debug, Serialize, Deserialize)]
 struct ProductResponse {
 	id: String,
@@ -244,45 +339,148 @@ struct ProductResponse {
 	banner: String,
 }
 
+impl ProductResponse {
+	fn new(
+		product: Product,
+		company: Company,
+		stats: &ProductStats,
+	) -> Self {
+		// Note: The hidden review/claim data is redacted (see `ProductBmc::update_visibility`).
+		let rating = stats
+			.ratings
+			.get(&product.id)
+			.filter(|_| product.review_visibility);
+		let count_claim = stats
+			.claim_counts
+			.get(&product.id)
+			.filter(|_| product.claim_historical_data_visibility);
+		ProductResponse {
+			id: product.id.to_string(),
+			name: product.name,
+			description: product.description,
+			count_claim: count_claim
+				.map(|c| c.count_claim as u32)
+				.unwrap_or_default(),
+			count_review: rating.map(|r| r.count_review as u32).unwrap_or_default(),
+			rating: rating.map(|r| r.rating).unwrap_or_default(),
+			company: company.into(),
+			banner: product.banner.unwrap_or_default(),
+		}
+	}
+}
+
+/// The review and claim aggregates of a set of products, by product id.
+struct ProductStats {
+	ratings: HashMap<i64, ProductRating>,
+	claim_counts: HashMap<i64, ProductClaimCount>,
+}
+
+impl ProductStats {
+	async fn load(
+		ctx: &Ctx,
+		mm: &ModelManager,
+		products: &[Product],
+	) -> Result<Self> {
+		let product_ids: Vec<i64> = products.iter().map(|p| p.id).collect();
+		let ratings = ReviewBmc::ratings_by_products(ctx, mm, &product_ids)
+			.await?
+			.into_iter()
+			.map(|rating| (rating.product_id, rating))
+			.collect();
+		let claim_counts = ClaimBmc::counts_by_products(ctx, mm, &product_ids)
+			.await?
+			.into_iter()
+			.map(|count| (count.product_id, count))
+			.collect();
+
+		Ok(ProductStats {
+			ratings,
+			claim_counts,
+		})
+	}
+}
+
+/// A product of the list, with its highlighted snippet when searched.
+#[derive(Debug, Serialize, Deserialize)]
+struct ProductListItem {
+	#[serde(flatten)]
+	product: ProductResponse,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	snippet: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ProductListResponse {
 	success: bool,
-	data: Vec<ProductResponse>,
+	data: Vec<ProductListItem>,
 }
 // endregion: --- Product List
 
 // region:    --- Product Detail
 async fn api_product_detail_handler(
-	State(_mm): State<ModelManager>,
+	State(mm): State<ModelManager>,
 	_cookies: Cookies,
 	Json(payload): Json<ProductDetailPayload>,
 ) -> Result<Json<Value>> {
 	debug!("{:<12} - api_product_detail_handler", "HANDLER");
 
 	let ProductDetailPayload { id } = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	// -- Get the product and its company.
+	let product = ProductBmc::get(&root_ctx, &mm, id).await?;
+	let company = CompanyBmc::get(&root_ctx, &mm, product.company_id).await?;
+	let stats =
+		ProductStats::load(&root_ctx, &mm, std::slice::from_ref(&product)).await?;
+	let categories = CategoryBmc::list_by_product(&root_ctx, &mm, product.id)
+		.await?
+		.into_iter()
+		.map(|category| category.name)
+		.collect();
+	let coverage = CoverageItemBmc::list_by_products(&root_ctx, &mm, &[product.id])
+		.await?
+		.into_iter()
+		.map(ProductCoverageItem::from)
+		.collect();
+	let trust_score =
+		TrustScoreBmc::daily_score_by_product(&root_ctx, &mm, product.id).await?;
+	let trust_score_history =
+		TrustScoreBmc::history_by_product(&root_ctx, &mm, product.id, None)
+			.await?
+			.into_iter()
+			.map(TrustScoreHistoryItem::from)
+			.collect();
+	let review_visibility = product.review_visibility;
+	let claim_historical_data_visibility = product.claim_historical_data_visibility;
+	let ProductResponse {
+		id,
+		name,
+		description,
+		count_claim,
+		count_review,
+		rating,
+		company,
+		banner,
+	} = ProductResponse::new(product, company, &stats);
 
-	// Create the success body with the dummy product data
+	// Create the success body.
 	let response = ProductDetailResponse {
 		success: true,
 		data: ProductDetail {
 			id,
-			name: "Asuransi Kesehatan".to_string(),
-			description: "Dengan asuransi kesehatan, Anda akan mendapatkan perlindungan kesehatan yang lengkap.".to_string(),
-			count_claim: 10,
-			count_review: 20,
-			rating: 4.0,
-			company: CompanyInfo {
-				name: "AXA Insurance".to_string(),
-				logo: "https://upload.wikimedia.org/wikipedia/commons/thumb/9/94/AXA_Logo.svg/640px-AXA_Logo.svg.png".to_string(),
-				description: "AXA adalah perusahaan asuransi multinasional yang berkantor pusat di Paris, Prancis.".to_string(),
-				website_url: "https://www.axa.com".to_string(),
-				location: "Paris, France".to_string(),
-				founded_year: 1817,
-			},
-			banner: "https://umsu.ac.id/health/wp-content/uploads/2023/08/Hospitals-and-Insurance-750x375.jpeg".to_string(),
-			categories: vec!["Health Insurance".to_string(), "Life Insurance".to_string()],
-			review_visibility: [true, false][rand::random::<usize>() % 2],
-			claim_historical_data_visibility: [true, false][rand::random::<usize>() % 2],
+			name,
+			description,
+			count_claim,
+			count_review,
+			rating,
+			company,
+			banner,
+			categories,
+			coverage,
+			review_visibility,
+			claim_historical_data_visibility,
+			trust_score,
+			trust_score_history,
 		},
 	};
 	let body = Json(serde_json::to_value(response)?);
@@ -297,10 +495,13 @@ async fn api_product_claim_historical_data_config_handler(
 ) -> Result<Json<Value>> {
 	debug!("{:<12} - api_product_claim_historical_data_config_handler", "HANDLER");
 
-	// Create the success body with the dummy product data
-	let response: ProductClaimHistoricalDataConfig = ProductClaimHistoricalDataConfig {
+	// Create the success body.
+	let response = ProductClaimHistoricalDataConfig {
 		success: true,
-		resolutions: vec!["All time".to_string(), "1 year".to_string(), "3 year".to_string()],
+		resolutions: ClaimHistoryResolution::ALL
+			.iter()
+			.map(|res| res.label().to_string())
+			.collect(),
 	};
 	let body = Json(serde_json::to_value(response)?);
 
@@ -309,14 +510,14 @@ async fn api_product_claim_historical_data_config_handler(
 
 // region:    --- Product Claim Historical Data
 async fn api_product_claim_historical_data_handler(
-	State(_mm): State<ModelManager>,
+	State(mm): State<ModelManager>,
 	_cookies: Cookies,
 	Json(payload): Json<ProductClaimHistoricalDataPayload>,
 ) -> Result<Json<Value>> {
 	debug!("{:<12} - api_product_claim_historical_data_handler", "HANDLER");
 
-	let ProductClaimHistoricalDataPayload { resolution } = payload;
-	if ![ "All time", "1 year", "3 year"].contains(&resolution.as_str()) {
+	let ProductClaimHistoricalDataPayload { id, resolution } = payload;
+	let Some(resolution) = ClaimHistoryResolution::from_label(&resolution) else {
 		let body = Json(json!({
 			"result": {
 				"success": false,
@@ -326,88 +527,194 @@ async fn api_product_claim_historical_data_handler(
 				},
 			}
 		}));
-	
+
 		return Ok(body);
-	}
+	};
+	let root_ctx = Ctx::root_ctx();
 
-	let months = match resolution.as_str() {
-		"All time" => 60,
-		"1 year" => 12,
-		"3 year" => 36,
-		_ => 0,
+	// -- Get the monthly claim volumes of the product.
+	let product = ProductBmc::get(&root_ctx, &mm, id).await?;
+	check_claim_data_public(&product)?;
+	let volumes =
+		ClaimBmc::monthly_volume(&root_ctx, &mm, product.id, resolution).await?;
+
+	// Create the success body.
+	let response = ProductClaimHistoricalDataResponse {
+		success: true,
+		data: volumes
+			.into_iter()
+			.map(|volume| ProductClaimHistoricalData {
+				timestamp_month: volume.month.unix_timestamp() as u32,
+				volume: volume.volume as u32,
+			})
+			.collect(),
 	};
+	let body = Json(serde_json::to_value(response)?);
 
-	// create list of timestamp_month
-	let mut timestamp_month = Vec::new();
-	let now = SystemTime::now();
-	let since_epoch = now.duration_since(UNIX_EPOCH).expect("Time went backwards");
-	let timestamp = since_epoch.as_secs();
+	Ok(body)
+}
 
-	for i in 0..months {
-		timestamp_month.push(timestamp - i * 30 * 24 * 60 * 60 /* 30 days */);
-	}
+// region:    --- Claim Metrics
+async fn api_product_claim_metrics_handler(
+	State(mm): State<ModelManager>,
+	_cookies: Cookies,
+	Json(payload): Json<ClaimMetricsPayload>,
+) -> Result<Json<Value>> {
+	debug!("{:<12} - api_product_claim_metrics_handler", "HANDLER");
+
+	let ClaimMetricsPayload { id } = payload;
+	let root_ctx = Ctx::root_ctx();
 
-	// reverse the list
-	timestamp_month.reverse();
+	// -- Get the claim metrics of the product, for each resolution.
+	let product = ProductBmc::get(&root_ctx, &mm, id).await?;
+	check_claim_data_public(&product)?;
+	let mut data = Vec::new();
+	for resolution in ClaimHistoryResolution::ALL {
+		let metrics =
+			ClaimBmc::metrics_by_product(&root_ctx, &mm, product.id, resolution)
+				.await?;
+		data.push(ClaimMetricsData::new(resolution, metrics));
+	}
 
-	let mut rng = thread_rng();
-	
-	// Create the success body with the dummy product data, 5 years of monthly data, random volume
-	let response = ProductClaimHistoricalDataResponse {
+	// Create the success body.
+	let response = ClaimMetricsResponse {
 		success: true,
-		data: timestamp_month.iter().map(|timestamp| ProductClaimHistoricalData {
-			timestamp_month: *timestamp as u32,
-			volume: rng.gen_range(0.0..500.0) as u32,
-		}).collect(),
+		data,
 	};
+	let body = Json(serde_json::to_value(response)?);
+
+	Ok(body)
+}
+
+async fn api_company_claim_metrics_handler(
+	State(mm): State<ModelManager>,
+	_cookies: Cookies,
+	Json(payload): Json<ClaimMetricsPayload>,
+) -> Result<Json<Value>> {
+	debug!("{:<12} - api_company_claim_metrics_handler", "HANDLER");
+
+	let ClaimMetricsPayload { id } = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	// -- Get the claim metrics of the company, for each resolution.
+	let mut data = Vec::new();
+	for resolution in ClaimHistoryResolution::ALL {
+		let metrics =
+			ClaimBmc::metrics_by_company(&root_ctx, &mm, id, resolution).await?;
+		data.push(ClaimMetricsData::new(resolution, metrics));
+	}
 
+	// Create the success body.
+	let response = ClaimMetricsResponse {
+		success: true,
+		data,
+	};
 	let body = Json(serde_json::to_value(response)?);
-	return Ok(body);
+
+	Ok(body)
 }
 
+/// The public endpoints read with the root ctx, which can read the hidden
+/// claim data, so only the visible product claim data is served.
+fn check_claim_data_public(product: &Product) -> model::Result<()> {
+	if !product.claim_historical_data_visibility {
+		return Err(model::Error::ProductClaimDataHidden {
+			product_id: product.id,
+		});
+	}
+
+	Ok(())
+}
+// endregion: --- Claim Metrics
 
 // region:    --- Product Review List
 async fn api_product_review_list_handler(
-	State(_mm): State<ModelManager>,
+	State(mm): State<ModelManager>,
 	_cookies: Cookies,
-	Json(payload): Json<ProductDetailPayload>,
+	Json(payload): Json<ProductReviewListPayload>,
 ) -> Result<Json<Value>> {
 	debug!("{:<12} - api_product_review_list_handler", "HANDLER");
 
-	let ProductDetailPayload { id } = payload;
+	let ProductReviewListPayload { id, sort } = payload;
+	let root_ctx = Ctx::root_ctx();
 
-	// Create the success body with the dummy product data
+	// -- Get the reviews and their authors.
+	// Note: The author display names, as the usernames are the emails.
+	let reviews = ReviewBmc::list_by_product(&root_ctx, &mm, id, sort).await?;
+	let author_ids: Vec<i64> = reviews.iter().map(|r| r.cid).collect();
+	let display_names =
+		UserProfileBmc::display_names_by_users(&root_ctx, &mm, &author_ids).await?;
+
+	// -- Get the official responses and their companies.
+	let review_ids: Vec<i64> = reviews.iter().map(|r| r.id).collect();
+	let mut responses: HashMap<i64, ReviewResponse> =
+		ReviewResponseBmc::list_by_reviews(&root_ctx, &mm, &review_ids)
+			.await?
+			.into_iter()
+			.map(|response| (response.review_id, response))
+			.collect();
+	let company_ids: Vec<i64> = responses.values().map(|r| r.company_id).collect();
+	let company_filter = CompanyFilter {
+		id: Some(OpValInt64::In(company_ids).into()),
+		..Default::default()
+	};
+	let company_names: HashMap<i64, String> =
+		CompanyBmc::list(&root_ctx, &mm, Some(vec![company_filter]), None)
+			.await?
+			.into_iter()
+			.map(|company| (company.id, company.name))
+			.collect();
+
+	// Create the success body.
+	let data = reviews
+		.into_iter()
+		.map(|review| ProductReview {
+			id: review.id.to_string(),
+			user_name: display_names.get(&review.cid).cloned().unwrap_or_default(),
+			rating: review.rating.into(),
+			comment: review.comment,
+			pros: review.pros,
+			cons: review.cons,
+			count_helpful: review.count_helpful as u32,
+			count_unhelpful: review.count_unhelpful as u32,
+			created_at: review.ctime.unix_timestamp() as u32,
+			response: responses.remove(&review.id).map(|response| {
+				ProductReviewResponse {
+					company_name: company_names
+						.get(&response.company_id)
+						.cloned()
+						.unwrap_or_default(),
+					body: response.body,
+					created_at: response.ctime.unix_timestamp() as u32,
+					updated_at: response.mtime.unix_timestamp() as u32,
+				}
+			}),
+		})
+		.collect();
 	let response = ProductReviewListResponse {
 		success: true,
-		data: vec![
-			ProductReview {
-				id: "1".to_string(),
-				user_name: "John Doe".to_string(),
-				rating: 4.0,
-				comment: "Great insurance!".to_string(),
-				pros: "Good coverage".to_string(),
-				cons: "Expensive".to_string(),
-				created_at: 1630000000,
-			},
-			ProductReview {
-				id: "2".to_string(),
-				user_name: "Jane Doe".to_string(),
-				rating: 3.0,
-				comment: "Not bad".to_string(),
-				pros: "Good coverage".to_string(),
-				cons: "Expensive".to_string(),
-				created_at: 1630000000,
-			},
-		],
+		data,
 	};
 	let body = Json(serde_json::to_value(response)?);
 
 	Ok(body)
 }
 
+#[serde_as]
 #[derive(Debug, Deserialize)]
 struct ProductDetailPayload {
-	id: String,
+	#[serde_as(as = "DisplayFromStr")]
+	id: i64,
+}
+
+/// The `sort` is one of "MostHelpful", "Newest", "HighestRating", "LowestRating",
+/// or "Wilson" (insertion order if none).
+#[serde_as]
+#[derive(Debug, Deserialize)]
+struct ProductReviewListPayload {
+	#[serde_as(as = "DisplayFromStr")]
+	id: i64,
+	sort: Option<ReviewSort>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -421,8 +728,58 @@ struct ProductDetail {
 	company: CompanyInfo,
 	banner: String,
 	categories: Vec<String>,
+	coverage: Vec<ProductCoverageItem>,
 	review_visibility: bool,
 	claim_historical_data_visibility: bool,
+	trust_score: TrustScore,
+	/// The daily trust scores, oldest first.
+	trust_score_history: Vec<TrustScoreHistoryItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrustScoreHistoryItem {
+	timestamp_day: u32,
+	score: f64,
+	weights_version: i32,
+}
+
+impl From<TrustScoreSnapshot> for TrustScoreHistoryItem {
+	fn from(snapshot: TrustScoreSnapshot) -> Self {
+		Self {
+			timestamp_day: snapshot.day.midnight().assume_utc().unix_timestamp()
+				as u32,
+			score: snapshot.score,
+			weights_version: snapshot.weights_version,
+		}
+	}
+}
+
+/// A benefit of the product coverage (amounts in IDR, None for no limit).
+#[derive(Debug, Serialize, Deserialize)]
+struct ProductCoverageItem {
+	id: String,
+	benefit_type: String,
+	name: String,
+	limit_amount: Option<i64>,
+	period_limit_amount: Option<i64>,
+	limit_period: Option<String>,
+	waiting_days: u32,
+	exclusions: String,
+}
+
+impl From<CoverageItem> for ProductCoverageItem {
+	fn from(item: CoverageItem) -> Self {
+		ProductCoverageItem {
+			id: item.id.to_string(),
+			benefit_type: item.benefit_typ.to_string(),
+			name: item.name,
+			limit_amount: item.limit_amount,
+			period_limit_amount: item.period_limit_amount,
+			limit_period: item.limit_period.map(|period| period.to_string()),
+			waiting_days: item.waiting_days as u32,
+			exclusions: item.exclusions,
+		}
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -432,18 +789,239 @@ struct ProductDetailResponse {
 }
 // endregion: --- Product Detail
 
+// region:    --- Product Compare
+async fn api_product_compare_handler(
+	State(mm): State<ModelManager>,
+	_cookies: Cookies,
+	Json(payload): Json<ProductComparePayload>,
+) -> Result<Json<Value>> {
+	debug!("{:<12} - api_product_compare_handler", "HANDLER");
+
+	let ProductComparePayload { ids } = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	// -- Build the comparison matrix (2 to 5 products).
+	let comparison =
+		product_comparison::compare_products(&root_ctx, &mm, &ids).await?;
+
+	// Create the success body.
+	let response = ProductCompareResponse {
+		success: true,
+		data: comparison,
+	};
+	let body = Json(serde_json::to_value(response)?);
+
+	Ok(body)
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+struct ProductComparePayload {
+	#[serde_as(as = "Vec<DisplayFromStr>")]
+	ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProductCompareResponse {
+	success: bool,
+	data: ProductComparison,
+}
+// endregion: --- Product Compare
+
+// region:    --- Product Recommended
+async fn api_product_recommended_handler(
+	State(mm): State<ModelManager>,
+	CtxW(ctx): CtxW,
+	Json(payload): Json<ProductRecommendedPayload>,
+) -> Result<Json<Value>> {
+	debug!("{:<12} - api_product_recommended_handler", "HANDLER");
+
+	let ProductRecommendedPayload { limit } = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	// -- Rank the products for the user of the ctx.
+	let recommendations =
+		recommendation::recommend_products(&ctx, &mm, limit).await?;
+
+	// -- Get the recommended products and their companies.
+	let product_ids: Vec<i64> =
+		recommendations.iter().map(|r| r.product_id).collect();
+	let product_filter = ProductFilter {
+		id: Some(OpValInt64::In(product_ids).into()),
+		..Default::default()
+	};
+	let mut products: HashMap<i64, Product> =
+		ProductBmc::list(&root_ctx, &mm, Some(vec![product_filter]), None)
+			.await?
+			.into_iter()
+			.map(|product| (product.id, product))
+			.collect();
+	let company_ids: Vec<i64> = products.values().map(|p| p.company_id).collect();
+	let company_filter = CompanyFilter {
+		id: Some(OpValInt64::In(company_ids).into()),
+		..Default::default()
+	};
+	let companies: HashMap<i64, Company> =
+		CompanyBmc::list(&root_ctx, &mm, Some(vec![company_filter]), None)
+			.await?
+			.into_iter()
+			.map(|company| (company.id, company))
+			.collect();
+	let stats = ProductStats::load(
+		&root_ctx,
+		&mm,
+		&products.values().cloned().collect::<Vec<_>>(),
+	)
+	.await?;
+
+	// Create the success body (in the recommendation order).
+	let data = recommendations
+		.into_iter()
+		.filter_map(|recommendation| {
+			let product = products.remove(&recommendation.product_id)?;
+			let company = companies.get(&product.company_id)?.clone();
+			Some(RecommendedProductResponse {
+				product: ProductResponse::new(product, company, &stats),
+				reason: recommendation.reason,
+				reasons: recommendation.reasons,
+			})
+		})
+		.collect();
+	let response = ProductRecommendedResponse {
+		success: true,
+		data,
+	};
+	let body = Json(serde_json::to_value(response)?);
+
+	Ok(body)
+}
+
+#[derive(Debug, Deserialize)]
+struct ProductRecommendedPayload {
+	/// At most `recommendation::RECOMMENDATION_LIMIT_MAX`.
+	#[serde(default)]
+	limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct RecommendedProductResponse {
+	#[serde(flatten)]
+	product: ProductResponse,
+	reason: String,
+	reasons: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProductRecommendedResponse {
+	success: bool,
+	data: Vec<RecommendedProductResponse>,
+}
+// endregion: --- Product Recommended
+
 #[derive(Debug, Serialize, Deserialize)]
 struct CompanyInfo {
+	id: String,
 	name: String,
 	logo: String,
 	description: String,
 	website_url: String,
 	location: String,
-	founded_year: u32,
+	founded_year: Option<i32>,
+}
+
+impl From<Company> for CompanyInfo {
+	fn from(company: Company) -> Self {
+		CompanyInfo {
+			id: company.id.to_string(),
+			name: company.name,
+			logo: company.logo.unwrap_or_default(),
+			description: company.description,
+			website_url: company.website_url.unwrap_or_default(),
+			location: company.location.unwrap_or_default(),
+			founded_year: company.founded_year,
+		}
+	}
+}
+
+// region:    --- Company Detail
+async fn api_company_detail_handler(
+	State(mm): State<ModelManager>,
+	_cookies: Cookies,
+	Json(payload): Json<CompanyDetailPayload>,
+) -> Result<Json<Value>> {
+	debug!("{:<12} - api_company_detail_handler", "HANDLER");
+
+	let CompanyDetailPayload { id } = payload;
+	let root_ctx = Ctx::root_ctx();
+
+	// -- Get the company and its products.
+	let CompanyWithProducts { company, products } =
+		CompanyBmc::get_with_products(&root_ctx, &mm, id).await?;
+
+	let stats = ProductStats::load(&root_ctx, &mm, &products).await?;
+	let complaint_count =
+		ComplaintBmc::count_by_company(&root_ctx, &mm, company.id, None).await?;
+	let trust_score =
+		TrustScoreBmc::daily_score_by_company(&root_ctx, &mm, company.id).await?;
+	let trust_score_history =
+		TrustScoreBmc::history_by_company(&root_ctx, &mm, company.id, None)
+			.await?
+			.into_iter()
+			.map(TrustScoreHistoryItem::from)
+			.collect();
+
+	// Create the success body.
+	let products = products
+		.into_iter()
+		.map(|product| ProductResponse::new(product, company.clone(), &stats))
+		.collect();
+	let response = CompanyDetailResponse {
+		success: true,
+		data: CompanyDetail {
+			company: company.into(),
+			products,
+			count_complaint: complaint_count.count_complaint as u32,
+			count_complaint_resolved: complaint_count.count_resolved as u32,
+			trust_score,
+			trust_score_history,
+		},
+	};
+	let body = Json(serde_json::to_value(response)?);
+
+	Ok(body)
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+struct CompanyDetailPayload {
+	#[serde_as(as = "DisplayFromStr")]
+	id: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct CompanyDetail {
+	#[serde(flatten)]
+	company: CompanyInfo,
+	products: Vec<ProductResponse>,
+	count_complaint: u32,
+	count_complaint_resolved: u32,
+	trust_score: TrustScore,
+	/// The daily trust scores, oldest first.
+	trust_score_history: Vec<TrustScoreHistoryItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CompanyDetailResponse {
+	success: bool,
+	data: CompanyDetail,
+}
+// endregion: --- Company Detail
+
+#[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 struct ProductClaimHistoricalDataPayload {
+	#[serde_as(as = "DisplayFromStr")]
+	id: i64,
 	resolution: String,
 }
 
@@ -459,6 +1037,44 @@ struct ProductClaimHistoricalData {
 	volume: u32,
 }
 
+/// The product or company id.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+struct ClaimMetricsPayload {
+	#[serde_as(as = "DisplayFromStr")]
+	id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ClaimMetricsResponse {
+	data: Vec<ClaimMetricsData>,
+	success: bool,
+}
+
+/// Note: The ratios and medians are `None` when there are no decided (or paid) claims.
+#[derive(Debug, Serialize, Deserialize)]
+struct ClaimMetricsData {
+	resolution: String,
+	count_claim: u32,
+	settlement_ratio: Option<f64>,
+	rejection_ratio: Option<f64>,
+	median_days_to_decision: Option<f64>,
+	median_days_to_payment: Option<f64>,
+}
+
+impl ClaimMetricsData {
+	fn new(resolution: ClaimHistoryResolution, metrics: ClaimMetrics) -> Self {
+		Self {
+			resolution: resolution.label().to_string(),
+			count_claim: metrics.count_claim as u32,
+			settlement_ratio: metrics.settlement_ratio(),
+			rejection_ratio: metrics.rejection_ratio(),
+			median_days_to_decision: metrics.median_days_to_decision,
+			median_days_to_payment: metrics.median_days_to_payment,
+		}
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ProductClaimHistoricalDataConfig {
 	resolutions: Vec<String>,
@@ -473,7 +1089,19 @@ struct ProductReview {
 	comment: String,
 	pros: String,
 	cons: String,
+	count_helpful: u32,
+	count_unhelpful: u32,
+	created_at: u32,
+	/// The official response of the insurer, if any.
+	response: Option<ProductReviewResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProductReviewResponse {
+	company_name: String,
+	body: String,
 	created_at: u32,
+	updated_at: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1,9 +1,12 @@
 -- User
+CREATE TYPE user_typ AS ENUM ('Sys', 'Admin', 'Moderator', 'User');
+
 CREATE TABLE IF NOT EXISTS "user" (
   id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
 
   username varchar(128) NOT NULL UNIQUE,
-  user_typ varchar(32) NOT NULL,
+  typ user_typ NOT NULL DEFAULT 'User',
+  deleted boolean NOT NULL DEFAULT false,
 
   -- Auth
   pwd varchar(256),
@@ -0,0 +1,41 @@
+-- Search (the full-text search configuration of the company and product search columns)
+-- Note: The `simple` parser and lowercasing, with the Indonesian snowball stemmer
+--       (e.g., "kesehatan" matches "sehat"), and without stop words.
+CREATE TEXT SEARCH DICTIONARY search_id_stem (TEMPLATE = snowball, Language = indonesian);
+CREATE TEXT SEARCH CONFIGURATION search_id (COPY = simple);
+ALTER TEXT SEARCH CONFIGURATION search_id
+  ALTER MAPPING FOR asciiword, asciihword, hword_asciipart, word, hword, hword_part
+  WITH search_id_stem;
+
+-- Company
+CREATE TABLE IF NOT EXISTS company (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  name varchar(256) NOT NULL,
+  description text NOT NULL DEFAULT '',
+  logo varchar(1024),
+  website_url varchar(1024),
+  location varchar(256),
+  founded_year integer,
+
+  -- Note: Both the stemmed (search_id) and the raw (simple) words, the latter for
+  --       the prefix matching of the partially typed words.
+  search tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('search_id', name), 'A') ||
+    setweight(to_tsvector('simple', name), 'A') ||
+    setweight(to_tsvector('search_id', coalesce(location, '')), 'B') ||
+    setweight(to_tsvector('simple', coalesce(location, '')), 'B') ||
+    setweight(to_tsvector('search_id', description), 'C') ||
+    setweight(to_tsvector('simple', description), 'C')
+  ) STORED,
+
+  deleted boolean NOT NULL DEFAULT false,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS company_search_idx ON company USING GIN (search);
@@ -0,0 +1,34 @@
+-- Product
+CREATE TABLE IF NOT EXISTS product (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  -- Note: The company is soft-deleted (with its products, see `CompanyBmc::delete`).
+  company_id BIGINT NOT NULL REFERENCES company(id) ON DELETE RESTRICT,
+
+  name varchar(256) NOT NULL,
+  description text NOT NULL DEFAULT '',
+  banner varchar(1024),
+
+  -- Visibility (toggled by the company members or an admin)
+  review_visibility boolean NOT NULL DEFAULT true,
+  claim_historical_data_visibility boolean NOT NULL DEFAULT true,
+
+  -- Note: See the company search column (same configurations).
+  search tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('search_id', name), 'A') ||
+    setweight(to_tsvector('simple', name), 'A') ||
+    setweight(to_tsvector('search_id', description), 'B') ||
+    setweight(to_tsvector('simple', description), 'B')
+  ) STORED,
+
+  deleted boolean NOT NULL DEFAULT false,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS product_company_id_idx ON product (company_id);
+CREATE INDEX IF NOT EXISTS product_search_idx ON product USING GIN (search);
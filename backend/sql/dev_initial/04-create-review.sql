@@ -0,0 +1,43 @@
+-- Review
+-- Note: The review author is the `cid` (set by `prep_fields_for_create`),
+--       and a user can only have one (non deleted) review per product.
+--       A review is `Pending` until a moderator publishes (or rejects) it,
+--       and only the `Published` reviews are public.
+CREATE TYPE review_state AS ENUM ('Pending', 'Published', 'Rejected', 'Hidden');
+
+CREATE TABLE IF NOT EXISTS review (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  product_id BIGINT NOT NULL REFERENCES product(id) ON DELETE RESTRICT,
+
+  rating smallint NOT NULL CHECK (rating BETWEEN 1 AND 5),
+  comment text NOT NULL DEFAULT '',
+  pros text NOT NULL DEFAULT '',
+  cons text NOT NULL DEFAULT '',
+
+  -- Moderation
+  state review_state NOT NULL DEFAULT 'Pending',
+  moderation_reason text NOT NULL DEFAULT '',
+
+  -- Helpful votes (maintained from the `review_vote` rows)
+  count_helpful integer NOT NULL DEFAULT 0,
+  count_unhelpful integer NOT NULL DEFAULT 0,
+  -- The Wilson score (lower bound) of the helpful votes, for the ranking.
+  helpful_score double precision NOT NULL DEFAULT 0,
+
+  deleted boolean NOT NULL DEFAULT false,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL
+);
+
+-- Note: Partial, so that a (soft) deleted review does not prevent a new one.
+CREATE UNIQUE INDEX IF NOT EXISTS review_cid_product_id_key ON review (cid, product_id)
+  WHERE NOT deleted;
+
+CREATE INDEX IF NOT EXISTS review_product_id_idx ON review (product_id);
+
+CREATE INDEX IF NOT EXISTS review_state_idx ON review (state);
@@ -0,0 +1,31 @@
+-- Claim
+-- Note: A claim is `Submitted` until the insurer decides on it (`decision_time`),
+--       and an approved claim is `Paid` once paid out (`payment_time`).
+--       The `Approved` and `Paid` claims are the settled ones.
+CREATE TYPE claim_status AS ENUM ('Submitted', 'Approved', 'Rejected', 'Paid');
+
+CREATE TABLE IF NOT EXISTS claim (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  product_id BIGINT NOT NULL REFERENCES product(id) ON DELETE RESTRICT,
+
+  status claim_status NOT NULL DEFAULT 'Submitted',
+  submit_time timestamp with time zone NOT NULL,
+  decision_time timestamp with time zone,
+  payment_time timestamp with time zone,
+
+  deleted boolean NOT NULL DEFAULT false,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  CHECK (status = 'Submitted' OR decision_time IS NOT NULL),
+  CHECK (status <> 'Paid' OR payment_time IS NOT NULL),
+  CHECK (decision_time >= submit_time),
+  CHECK (payment_time >= submit_time)
+);
+
+CREATE INDEX IF NOT EXISTS claim_product_id_submit_time_idx ON claim (product_id, submit_time);
@@ -0,0 +1,27 @@
+-- User Profile
+-- Note: The personal information collected at registration.
+CREATE TABLE IF NOT EXISTS user_profile (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  user_id BIGINT NOT NULL UNIQUE REFERENCES "user"(id) ON DELETE RESTRICT,
+
+  email varchar(256) NOT NULL,
+  phone varchar(64) NOT NULL,
+  full_name varchar(256) NOT NULL,
+  gender varchar(32) NOT NULL,
+  birth_date date NOT NULL,
+  address text NOT NULL,
+  marital_status varchar(32) NOT NULL,
+  occupation varchar(128) NOT NULL,
+  income double precision NOT NULL,
+  dependents integer NOT NULL,
+  region varchar(128) NOT NULL,
+  familiarity varchar(64) NOT NULL,
+  interests text NOT NULL,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL
+);
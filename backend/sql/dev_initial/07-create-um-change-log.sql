@@ -0,0 +1,18 @@
+-- User Management Change Log
+-- Note: The `cid`/`ctime` record who performed the action, and when.
+CREATE TYPE um_change_action AS ENUM ('Delete');
+
+CREATE TABLE IF NOT EXISTS um_change_log (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  user_id BIGINT NOT NULL REFERENCES "user"(id),
+  action um_change_action NOT NULL,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS um_change_log_user_id_idx ON um_change_log (user_id);
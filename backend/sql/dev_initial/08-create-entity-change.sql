@@ -0,0 +1,26 @@
+-- Entity Change (audit log)
+-- Note: Written by the base crud fns (create/update/delete), in the same transaction.
+--       The `cid`/`ctime` record who performed the change, and when.
+--       The foreign keys of the audited tables do not cascade (ON DELETE RESTRICT),
+--       so that every delete goes through the base crud fns and is recorded
+--       (the derived `trust_score` snapshots are the exception).
+CREATE TYPE entity_change_op AS ENUM ('Create', 'Update', 'Delete');
+
+CREATE TABLE IF NOT EXISTS entity_change (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  entity varchar(64) NOT NULL,
+  entity_id BIGINT NOT NULL,
+  op entity_change_op NOT NULL,
+  -- The changed fields (before/after). NULL before a create and after a (hard) delete.
+  before jsonb,
+  after jsonb,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS entity_change_entity_entity_id_idx ON entity_change (entity, entity_id);
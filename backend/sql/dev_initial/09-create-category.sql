@@ -0,0 +1,41 @@
+-- Category (hierarchical, e.g., Health > Hospitalization > Cashless)
+-- Note: The `parent_id` does not cascade, `CategoryBmc::delete` deletes the product
+--       links of the subtree, then the subtree categories, in one transaction
+--       (both recorded in the `entity_change`).
+CREATE TABLE IF NOT EXISTS category (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  parent_id BIGINT REFERENCES category(id),
+
+  name varchar(256) NOT NULL,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  UNIQUE NULLS NOT DISTINCT (parent_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS category_parent_id_idx ON category (parent_id);
+
+-- Product Category (many-to-many)
+-- Note: Has an `id` and timestamps, as the links are created/deleted through
+--       the base crud fns (see `CategoryBmc::add_product`).
+CREATE TABLE IF NOT EXISTS product_category (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  product_id BIGINT NOT NULL REFERENCES product(id) ON DELETE RESTRICT,
+  category_id BIGINT NOT NULL REFERENCES category(id) ON DELETE RESTRICT,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  UNIQUE (product_id, category_id)
+);
+
+CREATE INDEX IF NOT EXISTS product_category_category_id_idx ON product_category (category_id);
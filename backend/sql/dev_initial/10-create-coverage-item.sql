@@ -0,0 +1,35 @@
+-- Coverage Item (the structured benefit schedule of a product)
+-- Note: The amounts are in the product currency (IDR), NULL meaning no limit.
+CREATE TYPE coverage_benefit_typ AS ENUM (
+  'Hospitalization', 'Outpatient', 'Surgery', 'CriticalIllness', 'Maternity',
+  'Dental', 'Death', 'Disability', 'Other'
+);
+CREATE TYPE coverage_limit_period AS ENUM ('Day', 'Month', 'Year', 'Lifetime');
+
+CREATE TABLE IF NOT EXISTS coverage_item (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  product_id BIGINT NOT NULL REFERENCES product(id) ON DELETE RESTRICT,
+
+  benefit_typ coverage_benefit_typ NOT NULL,
+  name varchar(256) NOT NULL,
+
+  limit_amount bigint,
+  period_limit_amount bigint,
+  limit_period coverage_limit_period,
+  waiting_days integer NOT NULL DEFAULT 0,
+  exclusions text NOT NULL DEFAULT '',
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  CHECK ((period_limit_amount IS NULL) = (limit_period IS NULL)),
+  CHECK (limit_amount >= 0),
+  CHECK (period_limit_amount >= 0),
+  CHECK (waiting_days >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS coverage_item_product_id_idx ON coverage_item (product_id);
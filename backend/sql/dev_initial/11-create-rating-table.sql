@@ -0,0 +1,21 @@
+-- Rating Table (the versioned premium rating table of a product)
+-- Note: A version is never updated, a new rating is a new version (1, 2, ...).
+--       The yearly premium (IDR) is the `base_premium` multiplied by the
+--       age band, region, occupation class, and dependent factors of the `schedule`.
+CREATE TABLE IF NOT EXISTS rating_table (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  product_id BIGINT NOT NULL REFERENCES product(id) ON DELETE RESTRICT,
+  version integer NOT NULL,
+
+  base_premium bigint NOT NULL CHECK (base_premium > 0),
+  schedule jsonb NOT NULL,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  UNIQUE (product_id, version)
+);
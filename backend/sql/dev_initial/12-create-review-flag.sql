@@ -0,0 +1,19 @@
+-- Review Flag (the user reports of a review, for the moderation queue)
+-- Note: The reporter is the `cid`, and a user can only flag a review once.
+--       The flags of a review are resolved when a moderator acts on it.
+CREATE TABLE IF NOT EXISTS review_flag (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  review_id BIGINT NOT NULL REFERENCES review(id) ON DELETE RESTRICT,
+
+  reason text NOT NULL,
+  resolved boolean NOT NULL DEFAULT false,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  UNIQUE (review_id, cid)
+);
@@ -0,0 +1,18 @@
+-- Review Vote (the "helpful" up/down votes of a review)
+-- Note: The voter is the `cid`, and a user has at most one vote per review.
+--       The review `count_helpful`/`count_unhelpful` are updated on each vote.
+CREATE TABLE IF NOT EXISTS review_vote (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  review_id BIGINT NOT NULL REFERENCES review(id) ON DELETE RESTRICT,
+
+  helpful boolean NOT NULL,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  UNIQUE (review_id, cid)
+);
@@ -0,0 +1,18 @@
+-- Company Member (the users acting for an insurer company, e.g., its staff)
+-- Note: Only a `Sys` user can add or remove the members (see `CompanyMemberBmc`).
+CREATE TABLE IF NOT EXISTS company_member (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  company_id BIGINT NOT NULL REFERENCES company(id) ON DELETE RESTRICT,
+  user_id BIGINT NOT NULL REFERENCES "user"(id) ON DELETE RESTRICT,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  UNIQUE (company_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS company_member_user_id_idx ON company_member (user_id);
@@ -0,0 +1,17 @@
+-- Review Response (the official response of the insurer to a review)
+-- Note: One response per review, by a member of the company of the reviewed product
+--       (the `company_id`, set from the product, never from the payload).
+CREATE TABLE IF NOT EXISTS review_response (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  review_id BIGINT NOT NULL UNIQUE REFERENCES review(id) ON DELETE RESTRICT,
+  company_id BIGINT NOT NULL REFERENCES company(id) ON DELETE RESTRICT,
+
+  body text NOT NULL,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL
+);
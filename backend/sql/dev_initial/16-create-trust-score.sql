@@ -0,0 +1,24 @@
+-- Trust Score (daily snapshot of the trust score of a company or product)
+-- Note: The `product_id` is NULL for the company score.
+--       One snapshot per company/product and day, a new snapshot of the same day
+--       replaces it (see `TrustScoreBmc::snapshot_all`).
+--       The `factors` are the score breakdown, computed with the `weights_version`.
+CREATE TABLE IF NOT EXISTS trust_score (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  company_id BIGINT NOT NULL REFERENCES company(id) ON DELETE CASCADE,
+  product_id BIGINT REFERENCES product(id) ON DELETE CASCADE,
+  day date NOT NULL,
+
+  score double precision NOT NULL CHECK (score BETWEEN 0 AND 100),
+  weights_version integer NOT NULL,
+  factors jsonb NOT NULL,
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL,
+
+  UNIQUE NULLS NOT DISTINCT (company_id, product_id, day)
+);
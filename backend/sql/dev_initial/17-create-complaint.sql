@@ -0,0 +1,37 @@
+-- Complaint (a formal complaint of a user against an insurer)
+-- Note: The complainant is the `cid` (set by `prep_fields_for_create`).
+--       The lifecycle is Open -> InsurerResponded -> Escalated -> Resolved
+--       (see `ComplaintBmc` for who can do each transition), and each transition
+--       is an update recorded in the `entity_change` audit log.
+--       The `policy_number` is free text, as the policies are not (yet) modeled.
+CREATE TYPE complaint_category AS ENUM ('ClaimDenial', 'ClaimDelay', 'PolicyTerms', 'Premium', 'Service', 'Other');
+CREATE TYPE complaint_status AS ENUM ('Open', 'InsurerResponded', 'Escalated', 'Resolved');
+
+CREATE TABLE IF NOT EXISTS complaint (
+  id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+  company_id BIGINT NOT NULL REFERENCES company(id) ON DELETE RESTRICT,
+  product_id BIGINT REFERENCES product(id) ON DELETE RESTRICT,
+  claim_id BIGINT REFERENCES claim(id) ON DELETE RESTRICT,
+  policy_number varchar(128) NOT NULL DEFAULT '',
+
+  category complaint_category NOT NULL,
+  narrative text NOT NULL,
+  -- The attachment references (name and url), as a json array.
+  attachments jsonb NOT NULL DEFAULT '[]',
+
+  -- Lifecycle
+  status complaint_status NOT NULL DEFAULT 'Open',
+  insurer_response text NOT NULL DEFAULT '',
+  escalation_reason text NOT NULL DEFAULT '',
+  resolution text NOT NULL DEFAULT '',
+
+  -- Timestamps
+  cid bigint NOT NULL,
+  ctime timestamp with time zone NOT NULL,
+  mid bigint NOT NULL,
+  mtime timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS complaint_company_id_idx ON complaint (company_id);
+CREATE INDEX IF NOT EXISTS complaint_cid_idx ON complaint (cid);